@@ -1,100 +1,73 @@
-use js_sys::{Array, Float32Array, Reflect, Uint32Array, Uint8Array};
+#[cfg(target_arch = "wasm32")]
+use js_sys::{Array, Uint8Array};
+#[cfg(target_arch = "wasm32")]
 use wasm_bindgen::{prelude::*, JsCast};
+#[cfg(target_arch = "wasm32")]
 use web_sys::{DedicatedWorkerGlobalScope, MessageEvent};
+#[cfg(target_arch = "wasm32")]
 use wasm_bindgen::JsValue;
-use webworker_example::celestial_body::planet::planet_geometry::PlanetGeometry;
-use webworker_example::celestial_body::star::star_geometry::StarGeometry;
+#[cfg(target_arch = "wasm32")]
+use webworker_example::celestial_body::protocol::{WorkerInbox, WorkerRequest, WorkerResponse};
+#[cfg(target_arch = "wasm32")]
+use std::rc::Rc;
+#[cfg(target_arch = "wasm32")]
+use std::cell::RefCell;
+
+// En natif, la génération passe par le pool de threads de la bibliothèque
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    eprintln!("worker-geometry ne s'exécute que dans un Web Worker (cible wasm32)");
+}
+
+#[cfg(target_arch = "wasm32")]
+fn post(scope: &DedicatedWorkerGlobalScope, response: &WorkerResponse) {
+    let message = Uint8Array::from(&response.encode()[..]);
+    // Le tampon est transféré au thread principal plutôt que copié
+    scope
+        .post_message_with_transfer(&message, &Array::of1(&message.buffer()))
+        .expect("Worker send response");
+}
+
+#[cfg(target_arch = "wasm32")]
 fn main() {
     // Affiche erreur de rust dans la console JS
     console_error_panic_hook::set_once();
     web_sys::console::log_1(&"worker starting".into());
 
     let scope = DedicatedWorkerGlobalScope::from(JsValue::from(js_sys::global()));
+    let inbox = Rc::new(RefCell::new(WorkerInbox::default()));
 
+    // Le worker reste en vie et traite les demandes les unes après les autres
     let scope_clone = scope.clone();
     let onmessage = Closure::wrap(Box::new(move |msg: MessageEvent| {
-        web_sys::console::log_1(&"got message".into());
-
-        let data: JsValue = msg.data();
-        // Vérifie si on a reçu un SharedArrayBuffer
-        if let Ok(config) = Reflect::get(&data, &JsValue::from_str("config")) {
-            if !config.is_undefined() {
-
-                let config_data = Uint8Array::new(&config);
-                let lod = config_data.get_index(0);
-                let body_type = config_data.get_index(1);
-                let config_f32 = Reflect::get(&data, &JsValue::from_str("config_f32")).unwrap_or(JsValue::NULL);
-                let config_data_f32 = Float32Array::new(&config_f32);
-                let radius = config_data_f32.get_index(0);
-
-                if body_type == 0 {
-                    // Planète : avec normal
-                    web_sys::console::log_1(&"PLANET".into());
-                    let mut planet = PlanetGeometry::new(radius);
-                    planet.generate(lod);
-                    let planet_vertex = &planet.lod_levels[lod as usize];
-
-                    // Normal
-                    let lod9_normal = Reflect::get(&data, &JsValue::from_str("lod_nor")).unwrap_or(JsValue::NULL);
-                    let lod9_normal_arr = Float32Array::new(&lod9_normal);
-                    lod9_normal_arr.copy_from(&planet_vertex.normal[..]);
-
-                    // Position
-                    let lod9_position = Reflect::get(&data, &JsValue::from_str("lod_pos")).unwrap_or(JsValue::NULL);
-                    let lod9_position_arr = Float32Array::new(&lod9_position);
-                    lod9_position_arr.copy_from(&planet_vertex.position[..]);
-
-                    // Color
-                    let lod9_color = Reflect::get(&data, &JsValue::from_str("lod_col")).unwrap_or(JsValue::NULL);
-                    let lod9_color_arr = Float32Array::new(&lod9_color);
-                    lod9_color_arr.copy_from(&planet_vertex.color[..]);
-
-                    // Indice
-                    let lod9_indice = Reflect::get(&data, &JsValue::from_str("lod_ind")).unwrap_or(JsValue::NULL);
-                    let lod9_indice_arr = Uint32Array::new(&lod9_indice);
-                    lod9_indice_arr.copy_from(&planet_vertex.indice[..]);
-
-                    scope_clone.post_message(&data).expect("Worker send response");
-                    return;
-                } else if body_type == 1 {
-                    // Étoile : pas de normal
-                    let mut star = StarGeometry::new(radius);
-                    
-                    web_sys::console::log_1(&"STAR".into());
-                    star.generate(lod);
-                    let star_vertex = &star.lod_content[lod as usize];
-
-
-                    // Position
-                    let lod9_position = Reflect::get(&data, &JsValue::from_str("lod_pos")).unwrap_or(JsValue::NULL);
-                    let lod9_position_arr = Float32Array::new(&lod9_position);
-                    lod9_position_arr.copy_from(&star_vertex.position[..]);
-
-                    // Color
-                    let lod9_color = Reflect::get(&data, &JsValue::from_str("lod_col")).unwrap_or(JsValue::NULL);
-                    let lod9_color_arr = Float32Array::new(&lod9_color);
-                    lod9_color_arr.copy_from(&star_vertex.color[..]);
-
-                    // Indice
-                    let lod9_indice = Reflect::get(&data, &JsValue::from_str("lod_ind")).unwrap_or(JsValue::NULL);
-                    let lod9_indice_arr = Uint32Array::new(&lod9_indice);
-                    lod9_indice_arr.copy_from(&star_vertex.indice[..]);
-
-                    scope_clone.post_message(&data).expect("Worker send response");
-                    return;
+        let bytes = Uint8Array::new(&msg.data()).to_vec();
+        let request = match WorkerRequest::decode(&bytes) {
+            Ok(request) => request,
+            Err(error) => {
+                // Sans réponse, le thread principal attendrait ce travail indéfiniment
+                match WorkerRequest::job_of(&bytes) {
+                    Some(job) => post(&scope_clone, &WorkerResponse::Failed { job, error: error.to_string() }),
+                    None => web_sys::console::error_1(&format!("unreadable request: {}", error).into()),
                 }
+                return;
             }
+        };
+        if let Some(response) = inbox.borrow_mut().receive(request) {
+            post(&scope_clone, &response);
         }
-
+        // Exécution différée : une annulation déjà en file passe avant le travail
+        let inbox_clone = inbox.clone();
+        let scope_timeout = scope_clone.clone();
+        gloo_timers::callback::Timeout::new(0, move || {
+            inbox_clone.borrow_mut().run_next(&mut |response| post(&scope_timeout, &response));
+        }).forget();
     }) as Box<dyn Fn(MessageEvent)>);
 
     //  Cette ligne enregistre le closure Rust comme callback pour l’événement onmessage du worker
     scope.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
-    // Cette ligne indique à Rust de « lâcher » la gestion mémoire du closure, pour qu’il ne soit pas libéré à la fin de la fonction
+    // Cette ligne indique à Rust de « lâcher » la gestion mémoire du closure, pour qu’il ne soit pas libéré à la fin de la fonction
     onmessage.forget();
 
     // The worker must send a message to indicate that it's ready to receive messages.
-    scope
-        .post_message(&Array::new().into())
-        .expect("posting ready message succeeds");
-}
\ No newline at end of file
+    post(&scope, &WorkerResponse::Ready);
+}
@@ -1,10 +1,21 @@
+#[cfg(target_arch = "wasm32")]
 use js_sys::{Array, Uint32Array, Reflect};
+#[cfg(target_arch = "wasm32")]
 use wasm_bindgen::{prelude::*, JsCast};
+#[cfg(target_arch = "wasm32")]
 use web_sys::{DedicatedWorkerGlobalScope, MessageEvent};
+#[cfg(target_arch = "wasm32")]
 use wasm_bindgen::JsValue;
 // use crate::geometry::fbm::fbm_perlin_noise;
+#[cfg(target_arch = "wasm32")]
 use webworker_example::geometry::fbm::fbm_perlin_noise;
 
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    eprintln!("worker ne s'exécute que dans un Web Worker (cible wasm32)");
+}
+
+#[cfg(target_arch = "wasm32")]
 fn main() {
     // Affiche erreur de rust dans la console JS
     console_error_panic_hook::set_once();
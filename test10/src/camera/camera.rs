@@ -24,8 +24,9 @@ impl Plane {
 }
 
 pub struct Camera {
-    pub eye: glam::Vec3,
-    pub target: glam::Vec3,
+    // En f64 : à quelques mètres d'une surface, loin de l'origine de la scène, un f32 ne suffit plus
+    pub eye: glam::DVec3,
+    pub target: glam::DVec3,
     pub up: glam::Vec3,
     pub aspect: f32,
     pub fovy: f32,
@@ -36,8 +37,8 @@ pub struct Camera {
 impl Camera {
     pub fn new(aspect: f32) -> Self {
         Self {
-            eye: glam::Vec3::new(0.0, 0.0, 30.0),
-            target: glam::Vec3::new(0.0, 0.0, 0.0),
+            eye: glam::DVec3::new(0.0, 0.0, 30.0),
+            target: glam::DVec3::new(0.0, 0.0, 0.0),
             up: glam::Vec3::Y,
             aspect,
             fovy: 45.0,
@@ -47,7 +48,7 @@ impl Camera {
     }
 
     pub fn build_view_projection_matrix(&self) -> glam::Mat4 {
-        let view = glam::Mat4::look_at_rh(self.eye, self.target, self.up);
+        let view = glam::DMat4::look_at_rh(self.eye, self.target, self.up.as_dvec3()).as_mat4();
         let proj = glam::Mat4::perspective_rh_gl(
             self.fovy.to_radians(),
             self.aspect,
@@ -59,6 +60,24 @@ impl Camera {
         OPENGL_TO_WGPU_MATRIX * (proj * view)
     }
 
+    /// Vue-projection de la passe principale : même projection que la passe relative, pour que
+    /// les deux partagent le tampon de profondeur. Le frustum de visibilité est extrait de
+    /// `build_view_projection_matrix`, sans son plan lointain (`zfar`)
+    pub fn build_render_view_projection_matrix(&self, znear: f32) -> glam::Mat4 {
+        let view = glam::DMat4::look_at_rh(self.eye, self.target, self.up.as_dvec3()).as_mat4();
+        let proj = glam::Mat4::perspective_infinite_reverse_rh(self.fovy.to_radians(), self.aspect, znear);
+        proj * view
+    }
+
+    /// Vue-projection dont la caméra est à l'origine : les positions lui sont données relativement.
+    /// Profondeur inversée (1 au plan proche, 0 à l'infini) : sa précision reste la même de
+    /// `znear` à l'horizon
+    pub fn build_relative_view_projection_matrix(&self, znear: f32) -> glam::Mat4 {
+        let view = glam::Mat4::look_at_rh(glam::Vec3::ZERO, (self.target - self.eye).as_vec3(), self.up);
+        let proj = glam::Mat4::perspective_infinite_reverse_rh(self.fovy.to_radians(), self.aspect, znear);
+        proj * view
+    }
+
     /// Extrait les 6 plans du frustum à partir de la matrice view-projection (déjà transformée avec OPENGL_TO_WGPU_MATRIX)
     pub fn extract_frustum_planes(view_proj: &glam::Mat4) -> [Plane; 6] {
         // La matrice est en column-major, donc on transpose pour accéder aux lignes
@@ -108,16 +127,7 @@ impl CameraUniform {
         self.view_proj = mat.to_cols_array_2d();
     }
 
-    pub fn get_view_proj(&self) -> [[f32; 4]; 4] {
-        self.view_proj
-    }
-
-    pub fn mat4_from_array(m: [[f32; 4]; 4]) -> glam::Mat4 {
-        glam::Mat4::from_cols(
-            glam::Vec4::from(m[0]),
-            glam::Vec4::from(m[1]),
-            glam::Vec4::from(m[2]),
-            glam::Vec4::from(m[3]),
-        )
+    pub fn set_view_proj(&mut self, mat: glam::Mat4) {
+        self.view_proj = mat.to_cols_array_2d();
     }
 }
\ No newline at end of file
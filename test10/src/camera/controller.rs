@@ -1,6 +1,12 @@
+use glam::{Quat, Vec3};
 use winit::keyboard::KeyCode;
 use super::camera::Camera;
 
+// Altitude au-dessus du corps le plus proche à partir de laquelle la vitesse est pleine ;
+// en dessous, elle lui est proportionnelle pour pouvoir descendre jusqu'à quelques mètres du sol
+const FULL_SPEED_ALTITUDE: f32 = 2.0;
+const MIN_SPEED_SCALE: f32 = 1.0e-8;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum CameraMode {
     Orbital,
@@ -9,6 +15,7 @@ pub enum CameraMode {
 
 pub struct CameraController {
     speed: f32,
+    speed_scale: f32,
     sensitivity: f32,
     mode: CameraMode,
     // Contrôles clavier
@@ -27,6 +34,7 @@ impl CameraController {
     pub fn new(speed: f32) -> Self {
         Self {
             speed,
+            speed_scale: 1.0,
             sensitivity: 0.002, // Sensibilité souris pour FPS
             mode: CameraMode::Orbital,
             is_up_pressed: false,
@@ -48,6 +56,33 @@ impl CameraController {
         self.mode
     }
 
+    /// Adapte la vitesse à l'altitude de la caméra au-dessus du corps le plus proche
+    pub fn adapt_speed_to_altitude(&mut self, altitude: f32) {
+        self.speed_scale = (altitude / FULL_SPEED_ALTITUDE).clamp(MIN_SPEED_SCALE, 1.0);
+    }
+
+    fn step(&self) -> f64 {
+        (self.speed * self.speed_scale) as f64
+    }
+
+    /// Entraîne la caméra avec le corps survolé, passé de (`from_position`, `from_rotation`)
+    /// à (`to_position`, `to_rotation`) : elle garde sa place et son orientation par rapport au sol
+    pub fn carry(&mut self, camera: &mut Camera, (from_position, from_rotation): (Vec3, Quat), (to_position, to_rotation): (Vec3, Quat)) {
+        let rotation = (to_rotation * from_rotation.inverse()).as_dquat();
+        let (from_position, to_position) = (from_position.as_dvec3(), to_position.as_dvec3());
+        let front = rotation * (camera.target - camera.eye);
+        camera.eye = to_position + rotation * (camera.eye - from_position);
+        camera.target = camera.eye + front;
+        camera.up = (rotation * camera.up.as_dvec3()).as_vec3();
+        if self.mode == CameraMode::Fps {
+            // L'orientation FPS est recalculée à chaque image à partir du lacet et du tangage
+            let front = front.normalize();
+            self.yaw = front.z.atan2(front.x) as f32;
+            let limit = std::f32::consts::FRAC_PI_2 - 0.1;
+            self.pitch = (front.y.asin() as f32).clamp(-limit, limit);
+        }
+    }
+
     // Gestion des mouvements de souris pour le mode FPS
     pub fn handle_mouse_movement(&mut self, delta_x: f64, delta_y: f64) {
         if self.mode == CameraMode::Fps {
@@ -107,20 +142,21 @@ impl CameraController {
     }
 
     fn update_orbital_camera(&self, camera: &mut Camera) {
+        let step = self.step();
         let forward = camera.target - camera.eye;
         let forward_norm = forward.normalize();
         let forward_mag = forward.length();
 
         // Prevents glitching when camera gets too close to the
         // center of the scene.
-        if self.is_forward_pressed && forward_mag > self.speed {
-            camera.eye += forward_norm * self.speed;
+        if self.is_forward_pressed && forward_mag > step {
+            camera.eye += forward_norm * step;
         }
         if self.is_backward_pressed {
-            camera.eye -= forward_norm * self.speed;
+            camera.eye -= forward_norm * step;
         }
 
-        let right = forward_norm.cross(camera.up);
+        let right = forward_norm.cross(camera.up.as_dvec3());
 
         // Redo radius calc in case the up/ down is pressed.
         let forward = camera.target - camera.eye;
@@ -130,10 +166,10 @@ impl CameraController {
             // Rescale the distance between the target and eye so
             // that it doesn't change. The eye therefore still
             // lies on the circle made by the target and eye.
-            camera.eye = camera.target - (forward + right * self.speed).normalize() * forward_mag;
+            camera.eye = camera.target - (forward + right * step).normalize() * forward_mag;
         }
         if self.is_left_pressed {
-            camera.eye = camera.target - (forward - right * self.speed).normalize() * forward_mag;
+            camera.eye = camera.target - (forward - right * step).normalize() * forward_mag;
         }
     }
 
@@ -148,28 +184,30 @@ impl CameraController {
         let right = front.cross(camera.up).normalize();
         let actual_up = right.cross(front).normalize();
 
-        // Mouvement
+        // Mouvement, en f64 : un pas de quelques mètres doit rester visible loin de l'origine
+        let step = self.step();
+        let (front_f64, right_f64, up_f64) = (front.as_dvec3(), right.as_dvec3(), actual_up.as_dvec3());
         if self.is_forward_pressed {
-            camera.eye += front * self.speed;
+            camera.eye += front_f64 * step;
         }
         if self.is_backward_pressed {
-            camera.eye -= front * self.speed;
+            camera.eye -= front_f64 * step;
         }
         if self.is_right_pressed {
-            camera.eye += right * self.speed;
+            camera.eye += right_f64 * step;
         }
         if self.is_left_pressed {
-            camera.eye -= right * self.speed;
+            camera.eye -= right_f64 * step;
         }
         if self.is_up_pressed {
-            camera.eye += actual_up * self.speed;
+            camera.eye += up_f64 * step;
         }
         if self.is_down_pressed {
-            camera.eye -= actual_up * self.speed;
+            camera.eye -= up_f64 * step;
         }
 
         // Mettre à jour la target pour regarder dans la direction avant
-        camera.target = camera.eye + front;
+        camera.target = camera.eye + front_f64;
         camera.up = actual_up;
     }
 }
@@ -46,4 +46,22 @@ pub fn init_camera_scene(device: &wgpu::Device, config: &wgpu::SurfaceConfigurat
     });
 
     (camera, camera_controller, camera_uniform, camera_buffer, camera_bind_group_layout, camera_bind_group)
-}
\ No newline at end of file
+}
+/// Caméra placée à l'origine pour les morceaux de terrain, dont les positions lui sont relatives
+pub fn init_relative_camera(device: &wgpu::Device, layout: &BindGroupLayout, aspect_ratio: f32) -> (CameraUniform, Buffer, BindGroup) {
+    let camera_uniform = CameraUniform::new(aspect_ratio);
+    let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Relative Camera Buffer"),
+        contents: bytemuck::cast_slice(&[camera_uniform]),
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+    });
+    let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: camera_buffer.as_entire_binding(),
+        }],
+        label: Some("relative_camera_bind_group"),
+    });
+    (camera_uniform, camera_buffer, camera_bind_group)
+}
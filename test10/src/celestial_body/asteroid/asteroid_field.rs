@@ -0,0 +1,110 @@
+use glam::{Mat4, Vec3};
+use wgpu::util::DeviceExt;
+
+use crate::celestial_body::asteroid::asteroid_geometry::rock_mesh;
+use crate::celestial_body::planet::planet_instance::InstanceRaw;
+use crate::celestial_body::planet::planet_vertex::Vertex;
+use crate::stellar_system::asteroid_belt::{Asteroid, AsteroidBelt, ASTEROID_VARIANTS};
+use crate::stellar_system::satellites::EARTH_RADIUS;
+
+// Les corps sont rendus en rayons terrestres comme les planètes, grossis pour rester visibles
+const ASTEROID_RENDER_SCALE: f32 = 10.0;
+
+fn instance_raw(asteroid: &Asteroid, position: Vec3) -> InstanceRaw {
+    let scale = ASTEROID_RENDER_SCALE * (asteroid.radius * 1000.0 / EARTH_RADIUS) as f32;
+    InstanceRaw {
+        model: Mat4::from_scale_rotation_translation(Vec3::splat(scale), asteroid.orientation, position).to_cols_array_2d(),
+    }
+}
+
+/// Astéroïdes partageant un même maillage : un seul appel de dessin pour tous
+pub struct RockBatch {
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub instance_buffer: wgpu::Buffer,
+    pub num_indices: u32,
+    /// Indices dans `AsteroidBelt::asteroids` des astéroïdes de ce lot
+    pub members: Vec<usize>,
+}
+
+impl RockBatch {
+    pub fn num_instances(&self) -> u32 {
+        self.members.len() as u32
+    }
+}
+
+/// Ceinture d'astéroïdes prête au rendu, un lot instancié par maillage de roche
+pub struct AsteroidField {
+    pub batches: Vec<RockBatch>,
+    // Instant (s) des positions envoyées au GPU
+    time: f64,
+    // Mise à jour en cours, publiée une fois tous les lots propagés
+    pending: Option<PendingUpdate>,
+}
+
+/// Instances des lots déjà propagés, tous au même instant `time` (s)
+struct PendingUpdate {
+    time: f64,
+    instances: Vec<Vec<InstanceRaw>>,
+}
+
+impl AsteroidField {
+    pub fn new(device: &wgpu::Device, belt: &AsteroidBelt) -> Self {
+        let batches = (0..ASTEROID_VARIANTS).map(|variant| {
+            let mesh = rock_mesh(belt.mesh_seed.wrapping_add(variant as u64));
+            let members: Vec<usize> = belt.asteroids.iter().enumerate()
+                .filter(|(_, asteroid)| asteroid.variant == variant)
+                .map(|(i, _)| i)
+                .collect();
+            let instances: Vec<InstanceRaw> = members.iter().zip(belt.positions_of(&members, 0.0))
+                .map(|(&i, position)| instance_raw(&belt.asteroids[i], position))
+                .collect();
+            let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Asteroid Vertex Buffer"),
+                contents: bytemuck::cast_slice(&Vertex::planet_vertex_to_vertex(&mesh)),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+            let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Asteroid Index Buffer"),
+                contents: bytemuck::cast_slice(&mesh.indice),
+                usage: wgpu::BufferUsages::INDEX,
+            });
+            let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Asteroid Instance Buffer"),
+                contents: bytemuck::cast_slice(&instances),
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            });
+            RockBatch {
+                vertex_buffer,
+                index_buffer,
+                instance_buffer,
+                num_indices: mesh.indice.len() as u32,
+                members,
+            }
+        }).collect();
+        Self { batches, time: 0.0, pending: None }
+    }
+
+    /// Replace les astéroïdes sur leur orbite à l'instant `time` (s). L'équation de Kepler n'est
+    /// résolue que pour un lot par image, pour une fraction du coût ; tous les lots sont propagés
+    /// au même instant et envoyés ensemble, la ceinture reste cohérente. Une mise à jour commence
+    /// à l'instant de l'image qui la lance, rien n'est recalculé à l'arrêt
+    pub fn update(&mut self, queue: &wgpu::Queue, belt: &AsteroidBelt, time: f64) {
+        if self.pending.is_none() && time == self.time {
+            return;
+        }
+        let pending = self.pending.get_or_insert_with(|| PendingUpdate { time, instances: Vec::with_capacity(self.batches.len()) });
+        let batch = &self.batches[pending.instances.len()];
+        pending.instances.push(batch.members.iter().zip(belt.positions_of(&batch.members, pending.time))
+            .map(|(&i, position)| instance_raw(&belt.asteroids[i], position))
+            .collect());
+        if pending.instances.len() < self.batches.len() {
+            return;
+        }
+        for (batch, instances) in self.batches.iter().zip(&pending.instances) {
+            queue.write_buffer(&batch.instance_buffer, 0, bytemuck::cast_slice(instances));
+        }
+        self.time = pending.time;
+        self.pending = None;
+    }
+}
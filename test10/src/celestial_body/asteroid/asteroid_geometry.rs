@@ -0,0 +1,85 @@
+use glam::Vec3;
+
+use crate::celestial_body::planet::planet_geometry::PlanetVertex;
+use crate::geometry::fbm::fbm_perlin_noise;
+use crate::geometry::icosphere::IcoSphere;
+use crate::stellar_system::stellar_system::RNG;
+
+const ROCK_SUBDIVISIONS: u8 = 2;
+// Amplitude des bosses et cratères, en fraction du rayon
+const ROCK_ROUGHNESS: f32 = 0.35;
+const ROCK_OCTAVES: u8 = 4;
+const ROCK_PERSISTENCE: f32 = 0.5;
+const ROCK_NOISE_SCALE: f32 = 1.5;
+// Aplatissement minimal d'un axe (corps allongés de type patatoïde)
+const ROCK_MIN_ELONGATION: f64 = 0.55;
+const ROCK_DARK_COLOR: Vec3 = Vec3::new(0.22, 0.20, 0.18);
+const ROCK_LIGHT_COLOR: Vec3 = Vec3::new(0.52, 0.47, 0.41);
+
+/// Maillage de roche irrégulière de rayon moyen unitaire, déterminé par `seed`
+pub fn rock_mesh(seed: u64) -> PlanetVertex {
+    let mut rng = RNG::from_seed(seed);
+    let offset = Vec3::new(rng.f64(0.0, 16.0) as f32, rng.f64(0.0, 16.0) as f32, rng.f64(0.0, 16.0) as f32);
+    let elongation = Vec3::new(
+        1.0,
+        rng.f64(ROCK_MIN_ELONGATION, 1.0) as f32,
+        rng.f64(ROCK_MIN_ELONGATION, 1.0) as f32,
+    );
+
+    let mut solid = IcoSphere::new();
+    solid.generate(ROCK_SUBDIVISIONS);
+
+    let mut mesh = PlanetVertex::new();
+    for vertex in &solid.vertices {
+        let p = *vertex + offset;
+        let noise = fbm_perlin_noise(p.x, p.y, p.z, ROCK_OCTAVES, ROCK_PERSISTENCE, ROCK_NOISE_SCALE);
+        let position = *vertex * elongation * (1.0 + ROCK_ROUGHNESS * noise);
+        let color = ROCK_DARK_COLOR.lerp(ROCK_LIGHT_COLOR, (0.5 + noise).clamp(0.0, 1.0));
+        mesh.position.extend_from_slice(&position.to_array());
+        mesh.color.extend_from_slice(&color.to_array());
+    }
+    mesh.indice = solid.indices;
+    mesh.normal = compute_normals(&mesh.position, &mesh.indice);
+    mesh
+}
+
+/// Normales par sommet, moyenne des normales des faces adjacentes
+fn compute_normals(position: &[f32], indices: &[u32]) -> Vec<f32> {
+    let vertex = |i: u32| Vec3::from_slice(&position[3 * i as usize..]);
+    let mut normals = vec![Vec3::ZERO; position.len() / 3];
+    for triangle in indices.chunks(3) {
+        let (v0, v1, v2) = (vertex(triangle[0]), vertex(triangle[1]), vertex(triangle[2]));
+        let normal = (v1 - v0).cross(v2 - v0).normalize_or_zero();
+        for &i in triangle {
+            normals[i as usize] += normal;
+        }
+    }
+    normals.iter().flat_map(|normal| normal.normalize_or_zero().to_array()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn radii(mesh: &PlanetVertex) -> Vec<f32> {
+        mesh.position.chunks(3).map(|p| Vec3::from_slice(p).length()).collect()
+    }
+
+    #[test]
+    fn test_rock_is_irregular() {
+        let mesh = rock_mesh(11);
+        let radii = radii(&mesh);
+        let min = radii.iter().cloned().fold(f32::MAX, f32::min);
+        let max = radii.iter().cloned().fold(0.0, f32::max);
+        assert!(max - min > 0.2, "roche trop sphérique ({} à {})", min, max);
+        assert!(max <= 1.0 + ROCK_ROUGHNESS + 1.0e-4);
+        assert_eq!(mesh.normal.len(), mesh.position.len());
+        assert!(mesh.indice.iter().all(|&i| (i as usize) < radii.len()));
+    }
+
+    #[test]
+    fn test_rock_is_seeded() {
+        assert_eq!(rock_mesh(5).position, rock_mesh(5).position);
+        assert_ne!(rock_mesh(5).position, rock_mesh(6).position);
+    }
+}
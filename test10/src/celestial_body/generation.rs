@@ -0,0 +1,202 @@
+use crate::celestial_body::geometry_loader::{CelestialBodyGeometry, CelestialVertex};
+use crate::celestial_body::job_queue::JobId;
+use crate::celestial_body::planet::planet_geometry::{PlanetGeometry, PlanetVertex};
+use crate::celestial_body::planet::planet_vertex;
+use crate::celestial_body::planet::terrain_chunk::{generate_chunk, ChunkKey};
+use crate::celestial_body::planet::terrain_parameters::TerrainParameters;
+use crate::celestial_body::star::star_geometry::{StarGeometry, StarVertex};
+use crate::celestial_body::star::star_vertex;
+use crate::stellar_system::stellar_system::PlanetClass;
+
+// Moteur d'exécution des générations : Web Workers dans le navigateur, pool de threads en natif
+#[cfg(target_arch = "wasm32")]
+pub use crate::celestial_body::worker::GeometryPool;
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::celestial_body::thread_pool::GeometryPool;
+
+// Nombre de workers persistants et de générations pouvant attendre l'un d'eux
+pub const GEOMETRY_WORKERS: usize = 4;
+pub const GEOMETRY_QUEUE_CAPACITY: usize = 32;
+// Mémoire GPU des maillages des corps au-delà de laquelle les moins importants sont libérés
+pub const MESH_BUDGET_BYTES: u64 = 256 << 20;
+// Au-delà, une icosphère dépasse les dizaines de millions de triangles
+pub const MAX_LOD: u8 = 9;
+
+/// Description autonome d'une géométrie à générer, transmissible à un autre thread ou à un worker
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GeometryRequest {
+    Planet { radius: f32, class: PlanetClass, terrain: TerrainParameters, lod: u8 },
+    Star { radius: f32, lod: u8 },
+    /// Morceau de terrain d'une planète vue de près ; son niveau dans le quadtree tient lieu de LOD
+    Chunk { radius: f32, class: PlanetClass, terrain: TerrainParameters, chunk: ChunkKey },
+}
+
+/// Géométrie brute d'un niveau de détail, produite hors du thread de rendu
+#[derive(Clone)]
+pub enum GeneratedGeometry {
+    Planet(PlanetVertex),
+    Star(StarVertex),
+    /// Sommets relatifs au centre du morceau
+    Chunk(PlanetVertex),
+}
+
+/// Nouvelle d'une génération soumise au pool
+pub enum GenerationEvent {
+    /// Avancement entre 0 et 1
+    Progress { job: JobId, fraction: f32 },
+    Finished { job: JobId, geometry: GeneratedGeometry },
+    /// Demande invalide, panique ou worker perdu : la génération peut être soumise à nouveau
+    Failed { job: JobId, error: String },
+}
+
+impl GeometryRequest {
+    pub fn new(body: &CelestialBodyGeometry, lod: usize) -> Self {
+        match body {
+            CelestialBodyGeometry::Planet(planet) => GeometryRequest::Planet {
+                radius: planet.radius,
+                class: planet.class,
+                terrain: planet.terrain,
+                lod: lod as u8,
+            },
+            // Sphère unité mise à l'échelle par l'instance : un même maillage par LOD pour toutes les étoiles
+            CelestialBodyGeometry::Star(_) => GeometryRequest::Star { radius: 1.0, lod: lod as u8 },
+        }
+    }
+
+    pub fn lod(&self) -> usize {
+        match self {
+            GeometryRequest::Planet { lod, .. } | GeometryRequest::Star { lod, .. } => *lod as usize,
+            GeometryRequest::Chunk { chunk, .. } => chunk.level as usize,
+        }
+    }
+
+    /// Raison pour laquelle la demande ne peut pas être générée, le cas échéant
+    pub fn validate(&self) -> Result<(), String> {
+        let (radius, lod) = match *self {
+            GeometryRequest::Planet { radius, lod, .. } | GeometryRequest::Star { radius, lod } => (radius, lod),
+            GeometryRequest::Chunk { radius, chunk, .. } => {
+                if !chunk.is_valid() {
+                    return Err(format!("morceau invalide {:?}", chunk));
+                }
+                (radius, 0)
+            }
+        };
+        if lod > MAX_LOD {
+            return Err(format!("LOD {} au-delà du maximum {}", lod, MAX_LOD));
+        }
+        if !radius.is_finite() || radius <= 0.0 {
+            return Err(format!("rayon invalide {}", radius));
+        }
+        Ok(())
+    }
+
+    /// Génère le maillage demandé ; coûteux, à exécuter sur un worker ou un thread du pool
+    pub fn generate(&self) -> GeneratedGeometry {
+        self.generate_with_progress(&mut |_| {})
+    }
+
+    /// Comme `generate`, en signalant l'avancement entre 0 et 1
+    pub fn generate_with_progress(&self, progress: &mut dyn FnMut(f32)) -> GeneratedGeometry {
+        match *self {
+            GeometryRequest::Planet { radius, class, terrain, lod } => {
+                let mut planet = PlanetGeometry::with_terrain(radius, class, terrain);
+                planet.generate_with_progress(lod, progress);
+                GeneratedGeometry::Planet(planet.lod_levels.swap_remove(lod as usize))
+            }
+            GeometryRequest::Star { radius, lod } => {
+                // Sphère lisse, rapide à générer : pas d'avancement intermédiaire
+                let mut star = StarGeometry::new(radius);
+                star.generate(lod);
+                GeneratedGeometry::Star(star.lod_content.swap_remove(lod as usize))
+            }
+            GeometryRequest::Chunk { radius, class, terrain, chunk } => {
+                // Quelques milliers de sommets : pas d'avancement intermédiaire
+                let planet = PlanetGeometry::with_terrain(radius, class, terrain);
+                GeneratedGeometry::Chunk(generate_chunk(&planet, chunk))
+            }
+        }
+    }
+}
+
+/// Range le niveau `lod` généré dans `body` et renvoie les sommets et indices à envoyer au GPU,
+/// ou None si la géométrie ne correspond pas au type du corps
+pub fn store_geometry(body: &mut CelestialBodyGeometry, lod: usize, geometry: GeneratedGeometry) -> Option<(Vec<CelestialVertex>, Vec<u32>)> {
+    match (body, geometry) {
+        (CelestialBodyGeometry::Planet(planet), GeneratedGeometry::Planet(data)) => {
+            planet.lod_levels.resize(planet.lod_levels.len().max(lod + 1), PlanetVertex::new());
+            planet.lod_levels[lod] = data;
+            let vertices = planet_vertex::Vertex::planet_vertex_to_vertex(&planet.lod_levels[lod]);
+            let vertices = vertices.into_iter().map(CelestialVertex::Planet).collect();
+            Some((vertices, planet.get_indices(lod).to_vec()))
+        }
+        (CelestialBodyGeometry::Star(star), GeneratedGeometry::Star(data)) => {
+            star.lod_content.resize(star.lod_content.len().max(lod + 1), StarVertex::new());
+            star.lod_content[lod] = data;
+            let vertices = star_vertex::Vertex::planet_vertex_to_vertex(&star.lod_content[lod]);
+            let vertices = vertices.into_iter().map(CelestialVertex::Star).collect();
+            Some((vertices, star.get_indices(lod).to_vec()))
+        }
+        _ => {
+            log::warn!("Géométrie générée incompatible avec le type du corps");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_from_body() {
+        let planet = CelestialBodyGeometry::Planet(PlanetGeometry::with_terrain(2.0, PlanetClass::Ice, TerrainParameters::from_seed(3)));
+        let request = GeometryRequest::new(&planet, 3);
+        assert_eq!(request, GeometryRequest::Planet { radius: 2.0, class: PlanetClass::Ice, terrain: TerrainParameters::from_seed(3), lod: 3 });
+        assert_eq!(request.lod(), 3);
+        let star = CelestialBodyGeometry::Star(StarGeometry::new(1.5));
+        assert_eq!(GeometryRequest::new(&star, 2), GeometryRequest::Star { radius: 1.0, lod: 2 });
+    }
+
+    #[test]
+    fn test_request_validation() {
+        assert!(GeometryRequest::Star { radius: 1.0, lod: MAX_LOD }.validate().is_ok());
+        assert!(GeometryRequest::Star { radius: 1.0, lod: MAX_LOD + 1 }.validate().is_err());
+        let terrain = TerrainParameters::from_seed(1);
+        assert!(GeometryRequest::Planet { radius: f32::NAN, class: PlanetClass::Lava, terrain, lod: 2 }.validate().is_err());
+        assert!(GeometryRequest::Planet { radius: -1.0, class: PlanetClass::Lava, terrain, lod: 2 }.validate().is_err());
+        let chunk = ChunkKey { face: 2, level: 3, x: 7, y: 1 };
+        assert!(GeometryRequest::Chunk { radius: 1.0, class: PlanetClass::Ice, terrain, chunk }.validate().is_ok());
+        let chunk = ChunkKey { face: 2, level: 3, x: 8, y: 1 };
+        assert!(GeometryRequest::Chunk { radius: 1.0, class: PlanetClass::Ice, terrain, chunk }.validate().is_err());
+    }
+
+    #[test]
+    fn test_generation_progress() {
+        let request = GeometryRequest::Planet { radius: 1.0, class: PlanetClass::Terrestrial, terrain: TerrainParameters::from_seed(2), lod: 2 };
+        let mut fractions = Vec::new();
+        request.generate_with_progress(&mut |fraction| fractions.push(fraction));
+        assert!(fractions.len() > 1);
+        assert!(fractions.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(fractions[0] == 0.0 && *fractions.last().unwrap() < 1.0);
+    }
+
+    #[test]
+    fn test_generated_geometry_is_stored() {
+        let mut body = CelestialBodyGeometry::Star(StarGeometry::new(1.0));
+        let geometry = GeometryRequest::new(&body, 2).generate();
+        let (vertices, indices) = store_geometry(&mut body, 2, geometry).unwrap();
+        // Icosphère de subdivision 2 : 162 sommets, 320 triangles
+        assert_eq!(vertices.len(), 162);
+        assert_eq!(indices.len(), 3 * 320);
+        let CelestialBodyGeometry::Star(star) = &body else { unreachable!() };
+        assert_eq!(star.lod_content.len(), 3);
+        assert_eq!(star.get_indices(2), &indices[..]);
+    }
+
+    #[test]
+    fn test_mismatched_geometry_is_rejected() {
+        let mut body = CelestialBodyGeometry::Planet(PlanetGeometry::new(1.0));
+        let geometry = GeometryRequest::Star { radius: 1.0, lod: 1 }.generate();
+        assert!(store_geometry(&mut body, 1, geometry).is_none());
+    }
+}
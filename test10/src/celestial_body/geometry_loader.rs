@@ -2,13 +2,22 @@ use crate::celestial_body::star::star_geometry::StarGeometry;
 use crate::celestial_body::PlanetGeometry;
 use crate::celestial_body::planet::planet_vertex;
 use crate::celestial_body::planet::planet_instance;
+use crate::celestial_body::planet::planet_terrain::PlanetTerrain;
 use crate::celestial_body::star::star_vertex;
 use crate::celestial_body::star::star_instance;
-use crate::celestial_body::worker::generate_worker;
+use crate::celestial_body::generation::{store_geometry, GeneratedGeometry, GeometryPool, GeometryRequest};
+use crate::celestial_body::job_queue::{JobId, QueueFull};
+use crate::celestial_body::lod::{fallback_lod, select_lod, MIN_BODY_LOD};
+use crate::celestial_body::load_priority::LoadPriority;
+use std::collections::BTreeMap;
 use std::rc::Rc;
 use std::cell::RefCell;
 use glam::{Vec3, Quat};
 use wgpu::util::DeviceExt;
+
+// Nombre d'échecs de génération au-delà duquel un corps n'est plus redemandé
+pub const MAX_GENERATION_ATTEMPTS: u32 = 3;
+
 pub enum CelestialBodyGeometry {
     Planet(PlanetGeometry),
     Star(StarGeometry)
@@ -21,6 +30,13 @@ impl CelestialBodyGeometry {
             CelestialBodyGeometry::Star(_) => 1,
         }
     }
+
+    pub fn radius(&self) -> f32 {
+        match self {
+            CelestialBodyGeometry::Planet(planet) => planet.radius,
+            CelestialBodyGeometry::Star(star) => star.radius,
+        }
+    }
 }
 
 pub enum CelestialVertex {
@@ -67,16 +83,51 @@ impl CelestialInstance {
             CelestialInstance::Planet(p) => p.set_rotation(rotation),
         }
     }
+
+    pub fn set_position(&mut self, position: Vec3) {
+        match self {
+            CelestialInstance::Star(s) => s.set_position(position),
+            CelestialInstance::Planet(p) => p.set_position(position),
+        }
+    }
+}
+
+/// Maillage d'un niveau de détail envoyé au GPU
+#[derive(Clone)]
+pub struct BodyMesh {
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub num_indices: u32,
+}
+
+impl BodyMesh {
+    /// Mémoire GPU occupée par les sommets et les indices
+    pub fn bytes(&self) -> u64 {
+        self.vertex_buffer.size() + self.index_buffer.size()
+    }
 }
 
+/// Maillages communs à toutes les étoiles : une sphère unité par LOD, mise à l'échelle par l'instance
+pub type SharedMeshes = BTreeMap<usize, BodyMesh>;
+
 pub struct CelestialBodyHandle {
     pub body: Rc<RefCell<CelestialBodyGeometry>>,
     is_ready: Rc<RefCell<bool>>,
-    pending: Rc<RefCell<Option<(Vec<CelestialVertex>, Vec<u32>)>>>,
-    pub vertex_buffer: Option<wgpu::Buffer>,
-    pub index_buffer: Option<wgpu::Buffer>,
-    pub instance_buffer: Option<wgpu::Buffer>,
-    pub num_indices: u32,
+    // Génération en cours et LOD demandé
+    job: Option<(JobId, usize)>,
+    // Géométrie reçue et son LOD, en attente d'envoi au GPU
+    pending: Option<(usize, Vec<CelestialVertex>, Vec<u32>)>,
+    progress: f32,
+    // Échecs consécutifs
+    failures: u32,
+    last_error: Option<String>,
+    // LOD choisi d'après la taille à l'écran, et maillages déjà envoyés au GPU par LOD
+    lod: Option<u8>,
+    meshes: BTreeMap<usize, BodyMesh>,
+    // Importance du corps pour la caméra, pour ordonner ses chargements et ses libérations
+    priority: LoadPriority,
+    /// Surface découpée en morceaux pour les vues rapprochées (planètes seulement)
+    pub terrain: Option<PlanetTerrain>,
     pub instance: CelestialInstance,
     pub is_visible: bool,
     pub id: u32
@@ -86,89 +137,224 @@ impl CelestialBodyHandle {
     pub fn new(body: CelestialBodyGeometry,position: Vec3, rotation: Quat, id: u32) -> Self {
         let instance  = match &body {
             CelestialBodyGeometry::Planet(_) => CelestialInstance::Planet(planet_instance::PlanetInstance { position, rotation }),
-            CelestialBodyGeometry::Star(_) => CelestialInstance::Star(star_instance::StarInstance { position, rotation }),
+            CelestialBodyGeometry::Star(star) => CelestialInstance::Star(star_instance::StarInstance::with_temperature(position, rotation, star.radius, star.temperature, star.stage)),
+        };
+        let terrain = match &body {
+            CelestialBodyGeometry::Planet(planet) => Some(PlanetTerrain::new(planet.radius, planet.class, planet.terrain)),
+            CelestialBodyGeometry::Star(_) => None,
         };
         Self {
             body: Rc::new(RefCell::new(body)),
             is_ready: Rc::new(RefCell::new(false)),
-            pending: Rc::new(RefCell::new(None)),
-            vertex_buffer: None,
-            index_buffer: None,
-            instance_buffer: None,
-            num_indices: 0,
+            job: None,
+            pending: None,
+            progress: 0.0,
+            failures: 0,
+            last_error: None,
+            lod: None,
+            meshes: BTreeMap::new(),
+            priority: LoadPriority::LOWEST,
+            terrain,
             instance,
             is_visible: false,
             id
         }
     }
 
-    pub fn generate_async(&self, lod: usize) {
-        let body = self.body.clone();
-        let pending_flag = self.pending.clone();
+    /// Soumet la génération du niveau `lod` au pool (Web Workers ou threads) ;
+    /// refusée tant que la file du pool est pleine
+    pub fn generate_async(&mut self, pool: &mut GeometryPool, lod: usize) -> Result<JobId, QueueFull> {
+        let job = pool.submit(GeometryRequest::new(&self.body.borrow(), lod), self.priority)?;
+        self.job = Some((job, lod));
+        self.progress = 0.0;
+        Ok(job)
+    }
+
+    /// Choisit le LOD adapté à un diamètre apparent de `screen_diameter` pixels et le renvoie
+    pub fn update_lod(&mut self, screen_diameter: f32) -> usize {
+        self.lod = Some(select_lod(self.lod, screen_diameter));
+        self.desired_lod()
+    }
+
+    /// Nouvelle importance du corps, reportée sur sa génération en attente
+    pub fn set_priority(&mut self, priority: LoadPriority, pool: &mut GeometryPool) {
+        self.priority = priority;
+        if let Some((job, _)) = self.job {
+            pool.reprioritize(job, priority);
+        }
+    }
+
+    pub fn priority(&self) -> LoadPriority {
+        self.priority
+    }
+
+    /// Étoiles : reprend le maillage commun du LOD choisi s'il est déjà sur le GPU
+    pub fn share_meshes(&mut self, shared: &SharedMeshes) {
+        if self.get_type() != 1 {
+            return;
+        }
+        let lod = self.desired_lod();
+        if let (false, Some(mesh)) = (self.meshes.contains_key(&lod), shared.get(&lod)) {
+            self.meshes.insert(lod, mesh.clone());
+            *self.is_ready.borrow_mut() = true;
+        }
+    }
+
+    /// LOD choisi par le dernier `update_lod`
+    pub fn desired_lod(&self) -> usize {
+        self.lod.unwrap_or(MIN_BODY_LOD) as usize
+    }
+
+    /// LOD de la génération en cours
+    pub fn job_lod(&self) -> Option<usize> {
+        self.job.map(|(_, lod)| lod)
+    }
 
-        generate_worker(&body, pending_flag, lod);
+    /// Le LOD choisi n'est ni en mémoire GPU, ni en cours, ni abandonné après trop d'échecs
+    pub fn needs_generation(&self) -> bool {
+        !self.meshes.contains_key(&self.desired_lod())
+            && self.job.is_none()
+            && self.pending.is_none()
+            && self.failures < MAX_GENERATION_ATTEMPTS
     }
 
-    pub fn upload_if_ready(&mut self, device: &wgpu::Device) -> bool {
+    /// LOD affiché : celui choisi s'il est prêt, sinon le plus proche en attendant
+    pub fn displayed_lod(&self) -> Option<usize> {
+        fallback_lod(self.meshes.keys().map(|&lod| lod as u8), self.desired_lod() as u8).map(|lod| lod as usize)
+    }
+
+    /// Maillage à dessiner
+    pub fn mesh(&self) -> Option<&BodyMesh> {
+        self.meshes.get(&self.displayed_lod()?)
+    }
 
-        if let Some((vertices, indices)) = self.pending.borrow_mut().take() {
-            
-            match vertices.get(0) {
+    /// LOD déjà envoyés au GPU
+    pub fn cached_lods(&self) -> impl Iterator<Item = usize> + '_ {
+        self.meshes.keys().copied()
+    }
+
+    /// Libère le maillage du niveau `lod` ; il sera regénéré s'il redevient nécessaire
+    pub fn evict(&mut self, lod: usize) -> bool {
+        self.meshes.remove(&lod).is_some()
+    }
+
+    /// Génération soumise et pas encore reçue
+    pub fn job(&self) -> Option<JobId> {
+        self.job.map(|(job, _)| job)
+    }
+
+    pub fn cancel_generation(&mut self, pool: &mut GeometryPool) {
+        if let Some((job, _)) = self.job.take() {
+            pool.cancel(job);
+        }
+    }
+
+    /// Range la géométrie de `job` dans le corps ; false si elle ne lui était pas destinée
+    pub fn receive(&mut self, job: JobId, geometry: GeneratedGeometry) -> bool {
+        match self.job {
+            Some((expected, lod)) if expected == job => {
+                self.job = None;
+                self.pending = store_geometry(&mut self.body.borrow_mut(), lod, geometry)
+                    .map(|(vertices, indices)| (lod, vertices, indices));
+                if self.pending.is_some() {
+                    self.progress = 1.0;
+                    self.failures = 0;
+                } else {
+                    self.record_failure("géométrie incompatible avec le corps".to_string());
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Enregistre l'échec de `job` ; le corps sera redemandé tant qu'il reste des tentatives
+    pub fn fail(&mut self, job: JobId, error: String) -> bool {
+        if self.job() != Some(job) {
+            return false;
+        }
+        self.job = None;
+        self.record_failure(error);
+        true
+    }
+
+    fn record_failure(&mut self, error: String) {
+        self.failures += 1;
+        self.progress = 0.0;
+        self.last_error = Some(error);
+    }
+
+    pub fn set_progress(&mut self, job: JobId, fraction: f32) {
+        if self.job() == Some(job) {
+            self.progress = fraction;
+        }
+    }
+
+    /// Avancement de la génération en cours, entre 0 et 1
+    pub fn progress(&self) -> f32 {
+        self.progress
+    }
+
+    /// Nombre d'échecs de génération et dernière erreur
+    pub fn failures(&self) -> (u32, Option<&str>) {
+        (self.failures, self.last_error.as_deref())
+    }
+
+    /// Envoie au GPU la géométrie reçue ; renvoie son LOD et sa taille en octets. Une étoile
+    /// reprend le maillage de `shared` s'il y est déjà, sinon y dépose le sien
+    pub fn upload_if_ready(&mut self, device: &wgpu::Device, shared: &mut SharedMeshes) -> Option<(usize, u64)> {
+
+        if let Some((lod, vertices, indices)) = self.pending.take() {
+            let is_star = self.get_type() == 1;
+            if let Some(mesh) = shared.get(&lod).filter(|_| is_star) {
+                self.meshes.insert(lod, mesh.clone());
+                *self.is_ready.borrow_mut() = true;
+                return None;
+            }
+
+            let vertex_buffer = match vertices.first() {
                 Some(CelestialVertex::Planet(_)) => {
                     let verts: Vec<_> = vertices.into_iter().filter_map(|v| {
                         if let CelestialVertex::Planet(p) = v { Some(p) } else { None }
                     }).collect();
-                    self.vertex_buffer = Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
                         label: Some("Vertex Buffer"),
                         contents: bytemuck::cast_slice(&verts),
                         usage: wgpu::BufferUsages::VERTEX,
-                    }));
+                    })
                 }
                 Some(CelestialVertex::Star(_)) => {
                     let verts: Vec<_> = vertices.into_iter().filter_map(|v| {
                         if let CelestialVertex::Star(s) = v { Some(s) } else { None }
                     }).collect();
-                    self.vertex_buffer = Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
                         label: Some("Vertex Buffer"),
                         contents: bytemuck::cast_slice(&verts),
                         usage: wgpu::BufferUsages::VERTEX,
-                    }));
+                    })
                 }
-                None => {}
-            }
+                None => return None,
+            };
 
-            self.index_buffer = Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
                 label: Some("Index Buffer"),
                 contents: bytemuck::cast_slice(&indices),
                 usage: wgpu::BufferUsages::INDEX,
-            }));
-
-            match &self.instance {
-                CelestialInstance::Planet(p) => {
-                    let instance_data = vec![planet_instance::PlanetInstance::to_raw(p)];
-                    self.instance_buffer = Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                        label: Some("Instance Buffer"),
-                        contents: bytemuck::cast_slice(&instance_data),
-                        usage: wgpu::BufferUsages::VERTEX,
-                    }));
-                }
-                CelestialInstance::Star(s) => {
-                    let instance_data = vec![star_instance::StarInstance::to_raw(s)];
-                    self.instance_buffer = Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                        label: Some("Instance Buffer"),
-                        contents: bytemuck::cast_slice(&instance_data),
-                        usage: wgpu::BufferUsages::VERTEX,
-                    }));
-                }
+            });
+
+            let mesh = BodyMesh { vertex_buffer, index_buffer, num_indices: indices.len() as u32 };
+            let bytes = mesh.bytes();
+            if is_star {
+                shared.insert(lod, mesh.clone());
             }
+            self.meshes.insert(lod, mesh);
 
-            self.num_indices = indices.len() as u32;
-            log::info!("Planet is uploaded");
+            log::info!("Planet is uploaded (LOD {})", lod);
 
             *self.is_ready.borrow_mut() = true;
-            return true;
+            return Some((lod, bytes));
         }
-        return false;
+        return None;
     }
 
     pub fn is_ready(&self) -> bool {
@@ -179,25 +365,73 @@ impl CelestialBodyHandle {
         self.body.borrow().get_type()
     }
 
-    pub fn recompute_instance(&mut self, device: &wgpu::Device) {
-        match &self.instance {
-            CelestialInstance::Planet(p) => {
-                let instance_data = vec![planet_instance::PlanetInstance::to_raw(p)];
-                self.instance_buffer = Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("Instance Buffer"),
-                    contents: bytemuck::cast_slice(&instance_data),
-                    usage: wgpu::BufferUsages::VERTEX,
-                }));
-            }
-            CelestialInstance::Star(s) => {
-                let instance_data = vec![star_instance::StarInstance::to_raw(s)];
-                self.instance_buffer = Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("Instance Buffer"),
-                    contents: bytemuck::cast_slice(&instance_data),
-                    usage: wgpu::BufferUsages::VERTEX,
-                }));
-            }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::celestial_body::generation::GenerationEvent;
+    use std::time::{Duration, Instant};
+
+    fn star_handle(radius: f32) -> CelestialBodyHandle {
+        CelestialBodyHandle::new(CelestialBodyGeometry::Star(StarGeometry::new(radius)), Vec3::ZERO, Quat::IDENTITY, 0)
+    }
+
+    #[test]
+    fn test_failures_are_retried_then_abandoned() {
+        let mut pool = GeometryPool::new(1, 4);
+        let mut handle = star_handle(1.0);
+        for attempt in 1..=MAX_GENERATION_ATTEMPTS {
+            assert!(handle.needs_generation());
+            let job = handle.generate_async(&mut pool, 1).unwrap();
+            assert!(!handle.needs_generation());
+            // Réponse d'un autre travail : ignorée
+            assert!(!handle.fail(job + 100, "autre".to_string()));
+            assert!(handle.fail(job, "worker perdu".to_string()));
+            assert_eq!(handle.failures(), (attempt, Some("worker perdu")));
         }
+        assert!(!handle.needs_generation());
+    }
+
+    #[test]
+    fn test_lod_choice_drives_generation() {
+        let mut pool = GeometryPool::new(1, 4);
+        let mut handle = star_handle(1.0);
+        let far = handle.update_lod(10.0);
+        let near = handle.update_lod(1.0e4);
+        assert!(near > far);
+        assert!(handle.needs_generation());
+        handle.generate_async(&mut pool, near).unwrap();
+        assert_eq!(handle.job_lod(), Some(near));
+        assert!(!handle.needs_generation());
+        // Rien n'est encore prêt à dessiner
+        assert_eq!(handle.displayed_lod(), None);
+        handle.cancel_generation(&mut pool);
+        assert!(handle.needs_generation());
     }
 
+    #[test]
+    fn test_generation_reaches_the_handle() {
+        let mut pool = GeometryPool::new(1, 4);
+        let mut handle = star_handle(1.0);
+        let job = handle.generate_async(&mut pool, 2).unwrap();
+        let start = Instant::now();
+        while handle.job().is_some() {
+            assert!(start.elapsed() < Duration::from_secs(30), "génération trop longue");
+            for event in pool.poll() {
+                match event {
+                    GenerationEvent::Finished { job: finished, geometry } => assert!(handle.receive(finished, geometry)),
+                    GenerationEvent::Failed { error, .. } => panic!("échec inattendu : {}", error),
+                    GenerationEvent::Progress { job: running, fraction } => handle.set_progress(running, fraction),
+                }
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(!handle.receive(job, GeometryRequest::Star { radius: 1.0, lod: 0 }.generate()));
+        assert_eq!(handle.progress(), 1.0);
+        // Géométrie prête à envoyer au GPU : plus rien à demander
+        assert!(!handle.needs_generation());
+        let CelestialBodyGeometry::Star(star) = &*handle.body.borrow() else { unreachable!() };
+        assert_eq!(star.get_indices(2).len(), 3 * 320);
+    }
 }
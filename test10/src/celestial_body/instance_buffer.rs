@@ -0,0 +1,84 @@
+use std::ops::Range;
+
+// Taille minimale d'un tampon d'instances, en octets
+const MIN_INSTANCE_BUFFER_SIZE: usize = 1024;
+
+/// Tampon d'instances persistant, partagé par tous les corps d'un pipeline : réécrit à chaque
+/// image avec `queue.write_buffer`, recréé seulement quand il devient trop petit
+pub struct InstanceBuffer {
+    label: &'static str,
+    buffer: Option<wgpu::Buffer>,
+    // Taille allouée, en octets
+    capacity: usize,
+}
+
+impl InstanceBuffer {
+    pub fn new(label: &'static str) -> Self {
+        Self { label, buffer: None, capacity: 0 }
+    }
+
+    /// Remplace le contenu du tampon par `instances`, dans l'ordre des appels de dessin
+    pub fn write<T: bytemuck::Pod>(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, instances: &[T]) {
+        let bytes: &[u8] = bytemuck::cast_slice(instances);
+        if bytes.is_empty() {
+            return;
+        }
+        if self.buffer.is_none() || bytes.len() > self.capacity {
+            self.capacity = grown_capacity(self.capacity, bytes.len());
+            self.buffer = Some(device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(self.label),
+                size: self.capacity as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }));
+        }
+        if let Some(buffer) = &self.buffer {
+            queue.write_buffer(buffer, 0, bytes);
+        }
+    }
+
+    pub fn buffer(&self) -> Option<&wgpu::Buffer> {
+        self.buffer.as_ref()
+    }
+}
+
+// Capacité doublée jusqu'à contenir `needed` octets : quelques corps de plus ne recréent pas le tampon
+fn grown_capacity(current: usize, needed: usize) -> usize {
+    let mut capacity = current.max(MIN_INSTANCE_BUFFER_SIZE);
+    while capacity < needed {
+        capacity *= 2;
+    }
+    capacity
+}
+
+/// Plages d'instances consécutives de même clé, une par appel de dessin instancié ;
+/// `keys` doit être trié pour qu'une clé ne donne qu'une plage
+pub fn batches<K: Copy + PartialEq>(keys: &[K]) -> Vec<(K, Range<u32>)> {
+    let mut batches: Vec<(K, Range<u32>)> = Vec::new();
+    for (index, key) in keys.iter().enumerate() {
+        let index = index as u32;
+        match batches.last_mut() {
+            Some((last, range)) if *last == *key => range.end = index + 1,
+            _ => batches.push((*key, index..index + 1)),
+        }
+    }
+    batches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grown_capacity() {
+        assert_eq!(grown_capacity(0, 80), MIN_INSTANCE_BUFFER_SIZE);
+        assert_eq!(grown_capacity(1024, 1025), 2048);
+        assert_eq!(grown_capacity(2048, 5000), 8192);
+    }
+
+    #[test]
+    fn test_batches_by_lod() {
+        assert!(batches::<usize>(&[]).is_empty());
+        assert_eq!(batches(&[2, 2, 2, 4, 5, 5]), vec![(2, 0..3), (4, 3..4), (5, 4..6)]);
+    }
+}
@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+
+use crate::celestial_body::generation::GeometryRequest;
+use crate::celestial_body::load_priority::LoadPriority;
+
+/// Identifiant d'une génération soumise au pool, unique pour toute la durée du pool
+pub type JobId = u64;
+
+/// La file d'attente est pleine : la demande est à renouveler plus tard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueFull;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WorkerSlot {
+    // Le worker n'a pas encore signalé qu'il écoute
+    Starting,
+    Idle,
+    Busy { job: JobId, cancelled: bool },
+    // Écarté pour de bon : ne recevra plus rien
+    Retired,
+}
+
+struct QueuedJob {
+    job: JobId,
+    request: GeometryRequest,
+    priority: LoadPriority,
+}
+
+/// Ordonnancement des générations entre un nombre fixe de workers : file bornée servie par
+/// priorité (à priorité égale, dans l'ordre de soumission), un seul travail par worker,
+/// demandes identiques fusionnées, annulation avant ou pendant l'exécution.
+/// Indépendant du moteur (Web Workers ou threads), qui se charge du transport.
+pub struct JobQueue {
+    capacity: usize,
+    next_job: JobId,
+    queued: Vec<QueuedJob>,
+    workers: Vec<WorkerSlot>,
+    // Demandeurs de chaque travail en attente ou en cours : il n'est annulé qu'au départ du dernier
+    subscribers: HashMap<JobId, (GeometryRequest, u32)>,
+}
+
+impl JobQueue {
+    /// File pour `workers` workers, pas encore prêts, acceptant au plus `capacity` travaux en attente
+    pub fn new(workers: usize, capacity: usize) -> Self {
+        Self {
+            capacity,
+            next_job: 0,
+            queued: Vec::new(),
+            workers: vec![WorkerSlot::Starting; workers],
+            subscribers: HashMap::new(),
+        }
+    }
+
+    /// Le worker `worker` est prêt à recevoir des travaux
+    pub fn set_ready(&mut self, worker: usize) {
+        if self.workers[worker] == WorkerSlot::Starting {
+            self.workers[worker] = WorkerSlot::Idle;
+        }
+    }
+
+    /// Met `request` en attente avec l'importance `priority` ; refusé si la file est pleine,
+    /// pour que l'appelant ralentisse. Une demande identique déjà en attente ou en cours est
+    /// partagée : son identifiant est renvoyé et son résultat servira les deux demandeurs
+    pub fn submit(&mut self, request: GeometryRequest, priority: LoadPriority) -> Result<JobId, QueueFull> {
+        if let Some((&job, (_, count))) = self.subscribers.iter_mut().find(|(_, (pending, _))| *pending == request) {
+            *count += 1;
+            if let Some(queued) = self.queued.iter_mut().find(|queued| queued.job == job) {
+                queued.priority = queued.priority.max(priority);
+            }
+            return Ok(job);
+        }
+        if self.queued.len() >= self.capacity {
+            return Err(QueueFull);
+        }
+        let job = self.next_job;
+        self.next_job += 1;
+        self.queued.push(QueuedJob { job, request, priority });
+        self.subscribers.insert(job, (request, 1));
+        Ok(job)
+    }
+
+    /// Nouvelle importance de `job` s'il attend encore un worker. Partagé, il garde la plus
+    /// haute de ses demandeurs, comme dans `submit` : un demandeur lointain ne retarde pas
+    /// celui qui en a besoin tout de suite
+    pub fn reprioritize(&mut self, job: JobId, priority: LoadPriority) {
+        let shared = self.subscribers.get(&job).is_some_and(|(_, count)| *count > 1);
+        if let Some(queued) = self.queued.iter_mut().find(|queued| queued.job == job) {
+            queued.priority = if shared { queued.priority.max(priority) } else { priority };
+        }
+    }
+
+    /// Un demandeur de `job` y renonce ; au départ du dernier, le travail est retiré de la file
+    /// s'il attend encore, son résultat ignoré s'il est en cours. Renvoie alors le worker qui
+    /// l'exécute, le cas échéant
+    pub fn cancel(&mut self, job: JobId) -> Option<usize> {
+        let (_, count) = self.subscribers.get_mut(&job)?;
+        *count -= 1;
+        if *count > 0 {
+            return None;
+        }
+        self.subscribers.remove(&job);
+        self.queued.retain(|queued| queued.job != job);
+        let worker = self.workers.iter().position(|slot| matches!(slot, WorkerSlot::Busy { job: busy, .. } if *busy == job))?;
+        self.workers[worker] = WorkerSlot::Busy { job, cancelled: true };
+        Some(worker)
+    }
+
+    /// Attribue aux workers libres les travaux en attente les plus importants
+    pub fn dispatch(&mut self) -> Vec<(usize, JobId, GeometryRequest)> {
+        let mut dispatched = Vec::new();
+        for (worker, slot) in self.workers.iter_mut().enumerate() {
+            if *slot != WorkerSlot::Idle {
+                continue;
+            }
+            let best = self.queued.iter().enumerate()
+                .max_by(|(_, a), (_, b)| a.priority.cmp(&b.priority).then_with(|| b.job.cmp(&a.job)))
+                .map(|(index, _)| index);
+            let Some(best) = best else { break };
+            let QueuedJob { job, request, .. } = self.queued.swap_remove(best);
+            *slot = WorkerSlot::Busy { job, cancelled: false };
+            dispatched.push((worker, job, request));
+        }
+        dispatched
+    }
+
+    /// Le worker `worker` a terminé `job` et redevient libre ; renvoie false si le résultat
+    /// doit être ignoré (travail annulé ou inconnu)
+    pub fn complete(&mut self, worker: usize, job: JobId) -> bool {
+        match self.workers.get(worker) {
+            Some(&WorkerSlot::Busy { job: busy, cancelled }) if busy == job => {
+                self.workers[worker] = WorkerSlot::Idle;
+                self.subscribers.remove(&job);
+                !cancelled
+            }
+            _ => false,
+        }
+    }
+
+    /// `job` est en cours d'exécution et son résultat attendu
+    pub fn is_active(&self, job: JobId) -> bool {
+        self.workers.contains(&WorkerSlot::Busy { job, cancelled: false })
+    }
+
+    /// Le worker `worker` est perdu et va être recréé : il redevient en attente de démarrage.
+    /// Renvoie le travail qu'il exécutait, dont le résultat ne viendra jamais
+    pub fn restart(&mut self, worker: usize) -> Option<JobId> {
+        self.release(worker, WorkerSlot::Starting)
+    }
+
+    /// Le worker `worker` est écarté sans être remplacé. Renvoie le travail qu'il exécutait
+    pub fn retire(&mut self, worker: usize) -> Option<JobId> {
+        self.release(worker, WorkerSlot::Retired)
+    }
+
+    /// Quand tous les workers sont écartés, plus rien ne sera exécuté : les travaux en attente
+    /// sont retirés de la file et renvoyés, pour être signalés en échec
+    pub fn abandon_if_no_workers(&mut self) -> Vec<JobId> {
+        if !self.workers.iter().all(|slot| *slot == WorkerSlot::Retired) {
+            return Vec::new();
+        }
+        let abandoned: Vec<JobId> = self.queued.drain(..).map(|queued| queued.job).collect();
+        for job in &abandoned {
+            self.subscribers.remove(job);
+        }
+        abandoned
+    }
+
+    fn release(&mut self, worker: usize, next: WorkerSlot) -> Option<JobId> {
+        let slot = std::mem::replace(&mut self.workers[worker], next);
+        match slot {
+            WorkerSlot::Busy { job, cancelled: false } => {
+                self.subscribers.remove(&job);
+                Some(job)
+            }
+            _ => None,
+        }
+    }
+
+    /// Nombre de travaux en attente d'un worker
+    pub fn queued(&self) -> usize {
+        self.queued.len()
+    }
+
+    /// Nombre de travaux en cours d'exécution, annulés compris
+    pub fn running(&self) -> usize {
+        self.workers.iter().filter(|slot| matches!(slot, WorkerSlot::Busy { .. })).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn star(lod: u8) -> GeometryRequest {
+        GeometryRequest::Star { radius: 1.0, lod }
+    }
+
+    #[test]
+    fn test_jobs_wait_for_ready_workers() {
+        let mut queue = JobQueue::new(2, 8);
+        let first = queue.submit(star(1), LoadPriority::LOWEST).unwrap();
+        let second = queue.submit(star(2), LoadPriority::LOWEST).unwrap();
+        let third = queue.submit(star(3), LoadPriority::LOWEST).unwrap();
+        assert!(first < second && second < third);
+        assert!(queue.dispatch().is_empty());
+
+        queue.set_ready(1);
+        assert_eq!(queue.dispatch(), vec![(1, first, star(1))]);
+        queue.set_ready(0);
+        assert_eq!(queue.dispatch(), vec![(0, second, star(2))]);
+        // Un seul travail par worker
+        assert!(queue.dispatch().is_empty());
+        assert_eq!((queue.queued(), queue.running()), (1, 2));
+
+        assert!(queue.complete(1, first));
+        assert_eq!(queue.dispatch(), vec![(1, third, star(3))]);
+    }
+
+    #[test]
+    fn test_back_pressure() {
+        let mut queue = JobQueue::new(1, 2);
+        queue.submit(star(1), LoadPriority::LOWEST).unwrap();
+        queue.submit(star(2), LoadPriority::LOWEST).unwrap();
+        assert_eq!(queue.submit(star(3), LoadPriority::LOWEST), Err(QueueFull));
+        // Un travail parti chez un worker libère une place
+        queue.set_ready(0);
+        queue.dispatch();
+        assert!(queue.submit(star(3), LoadPriority::LOWEST).is_ok());
+    }
+
+    #[test]
+    fn test_cancellation() {
+        let mut queue = JobQueue::new(1, 8);
+        queue.set_ready(0);
+        let running = queue.submit(star(1), LoadPriority::LOWEST).unwrap();
+        let waiting = queue.submit(star(2), LoadPriority::LOWEST).unwrap();
+        let next = queue.submit(star(3), LoadPriority::LOWEST).unwrap();
+        queue.dispatch();
+
+        // En attente : simplement retiré de la file
+        assert_eq!(queue.cancel(waiting), None);
+        // En cours : le worker termine, mais le résultat est écarté
+        assert_eq!(queue.cancel(running), Some(0));
+        assert!(!queue.complete(0, running));
+        assert_eq!(queue.dispatch(), vec![(0, next, star(3))]);
+        // Réponse d'un travail inconnu ou d'un autre worker
+        assert!(!queue.complete(0, waiting));
+        assert!(queue.complete(0, next));
+    }
+
+    #[test]
+    fn test_priority_dispatch() {
+        let mut queue = JobQueue::new(1, 8);
+        let far = queue.submit(star(1), LoadPriority::new(10.0, 40.0)).unwrap();
+        let near = queue.submit(star(2), LoadPriority::new(500.0, 3.0)).unwrap();
+        let later = queue.submit(star(3), LoadPriority::new(10.0, 40.0)).unwrap();
+        let moved = queue.submit(star(4), LoadPriority::LOWEST).unwrap();
+        // Le corps s'est rapproché depuis sa demande
+        queue.reprioritize(moved, LoadPriority::new(2000.0, 1.0));
+        queue.set_ready(0);
+        let mut order = Vec::new();
+        for _ in 0..4 {
+            let [(worker, job, _)] = queue.dispatch()[..] else { panic!("un travail attendu") };
+            assert!(queue.complete(worker, job));
+            order.push(job);
+        }
+        // À priorité égale, l'ordre de soumission
+        assert_eq!(order, vec![moved, near, far, later]);
+    }
+
+    #[test]
+    fn test_shared_job_keeps_highest_priority() {
+        let mut queue = JobQueue::new(1, 8);
+        let other = queue.submit(star(1), LoadPriority::new(100.0, 5.0)).unwrap();
+        let shared = queue.submit(star(2), LoadPriority::new(500.0, 2.0)).unwrap();
+        assert_eq!(queue.submit(star(2), LoadPriority::LOWEST), Ok(shared));
+        // Le demandeur lointain ne fait pas passer le travail derrière les autres
+        queue.reprioritize(shared, LoadPriority::LOWEST);
+        queue.set_ready(0);
+        assert_eq!(queue.dispatch(), vec![(0, shared, star(2))]);
+        assert!(queue.complete(0, shared));
+        // Un travail qui n'a qu'un demandeur suit son importance, même à la baisse
+        let alone = queue.submit(star(3), LoadPriority::new(800.0, 1.0)).unwrap();
+        queue.reprioritize(alone, LoadPriority::LOWEST);
+        assert_eq!(queue.dispatch(), vec![(0, other, star(1))]);
+    }
+
+    #[test]
+    fn test_identical_requests_are_shared() {
+        let mut queue = JobQueue::new(1, 1);
+        let first = queue.submit(star(2), LoadPriority::LOWEST).unwrap();
+        // Pas de place prise dans la file pour une demande déjà présente
+        assert_eq!(queue.submit(star(2), LoadPriority::new(100.0, 1.0)), Ok(first));
+        assert_eq!(queue.queued(), 1);
+        // Un demandeur renonce : le travail reste dû à l'autre
+        assert_eq!(queue.cancel(first), None);
+        queue.set_ready(0);
+        assert_eq!(queue.dispatch(), vec![(0, first, star(2))]);
+        assert_eq!(queue.submit(star(2), LoadPriority::LOWEST), Ok(first));
+        assert_eq!(queue.cancel(first), None);
+        assert!(queue.is_active(first));
+        assert_eq!(queue.cancel(first), Some(0));
+        assert!(!queue.complete(0, first));
+        // Terminé : une nouvelle demande identique repart de zéro
+        assert_ne!(queue.submit(star(2), LoadPriority::LOWEST), Ok(first));
+    }
+
+    #[test]
+    fn test_lost_worker() {
+        let mut queue = JobQueue::new(2, 8);
+        queue.set_ready(0);
+        queue.set_ready(1);
+        let lost = queue.submit(star(1), LoadPriority::LOWEST).unwrap();
+        let other = queue.submit(star(2), LoadPriority::LOWEST).unwrap();
+        let waiting = queue.submit(star(3), LoadPriority::LOWEST).unwrap();
+        queue.dispatch();
+        assert!(queue.is_active(lost) && !queue.is_active(waiting));
+
+        assert_eq!(queue.restart(0), Some(lost));
+        assert!(!queue.is_active(lost));
+        // Le worker recréé ne reçoit rien avant d'être prêt
+        assert!(queue.dispatch().is_empty());
+        queue.set_ready(0);
+        assert_eq!(queue.dispatch(), vec![(0, waiting, star(3))]);
+        // Un travail annulé n'est pas signalé comme perdu
+        queue.cancel(other);
+        assert_eq!(queue.restart(1), None);
+    }
+
+    #[test]
+    fn test_all_workers_lost() {
+        let mut queue = JobQueue::new(2, 8);
+        queue.set_ready(0);
+        let running = queue.submit(star(1), LoadPriority::LOWEST).unwrap();
+        queue.dispatch();
+        let waiting = queue.submit(star(2), LoadPriority::LOWEST).unwrap();
+        let shared = queue.submit(star(3), LoadPriority::LOWEST).unwrap();
+        queue.submit(star(3), LoadPriority::LOWEST).unwrap();
+
+        assert_eq!(queue.retire(0), Some(running));
+        // Un worker peut encore démarrer : la file attend
+        assert!(queue.abandon_if_no_workers().is_empty());
+        assert_eq!(queue.retire(1), None);
+        let mut abandoned = queue.abandon_if_no_workers();
+        abandoned.sort();
+        assert_eq!(abandoned, vec![waiting, shared]);
+        assert_eq!(queue.queued(), 0);
+        // Un écarté ne redevient jamais prêt
+        queue.set_ready(0);
+        queue.submit(star(4), LoadPriority::LOWEST).unwrap();
+        assert!(queue.dispatch().is_empty());
+        assert_eq!(queue.abandon_if_no_workers().len(), 1);
+    }
+}
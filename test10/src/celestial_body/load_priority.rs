@@ -0,0 +1,115 @@
+use std::cmp::Ordering;
+
+/// Importance d'un chargement : les corps les plus grands à l'écran d'abord, à l'octave près,
+/// puis à taille comparable les plus proches
+#[derive(Debug, Clone, Copy)]
+pub struct LoadPriority {
+    /// Diamètre apparent en pixels, infini quand la caméra est à l'intérieur
+    pub screen_diameter: f32,
+    pub distance: f32,
+}
+
+impl LoadPriority {
+    /// Corps hors du champ : derniers servis, premiers libérés
+    pub const LOWEST: LoadPriority = LoadPriority { screen_diameter: 0.0, distance: f32::INFINITY };
+
+    pub fn new(screen_diameter: f32, distance: f32) -> Self {
+        Self { screen_diameter, distance }
+    }
+
+    // Octave du diamètre apparent : une différence de quelques pixels ne l'emporte pas sur la distance
+    fn size_class(&self) -> f32 {
+        if self.screen_diameter > 1.0 { self.screen_diameter.log2().floor() } else { 0.0 }
+    }
+}
+
+impl Ord for LoadPriority {
+    /// Greater pour le chargement le plus important
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.size_class().total_cmp(&other.size_class())
+            .then_with(|| other.distance.total_cmp(&self.distance))
+    }
+}
+
+impl PartialOrd for LoadPriority {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for LoadPriority {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for LoadPriority {}
+
+/// Maillage en mémoire GPU, candidat à la libération
+#[derive(Debug, Clone, Copy)]
+pub struct Resident<K> {
+    pub key: K,
+    pub bytes: u64,
+    /// Dernière image où le maillage a été vu
+    pub last_visible: u64,
+    pub priority: LoadPriority,
+    /// Affiché en ce moment : jamais libéré
+    pub in_use: bool,
+}
+
+/// Maillages à libérer pour revenir sous `budget` octets : ceux vus il y a le plus longtemps
+/// d'abord, puis les moins importants ; si les maillages affichés suffisent à dépasser le budget,
+/// il reste dépassé
+pub fn select_evictions<K: Copy>(residents: &[Resident<K>], budget: u64) -> Vec<K> {
+    let mut resident_bytes: u64 = residents.iter().map(|resident| resident.bytes).sum();
+    let mut candidates: Vec<&Resident<K>> = residents.iter().filter(|resident| !resident.in_use).collect();
+    candidates.sort_by_key(|resident| (resident.last_visible, resident.priority));
+    let mut evicted = Vec::new();
+    for candidate in candidates {
+        if resident_bytes <= budget {
+            break;
+        }
+        resident_bytes -= candidate.bytes;
+        evicted.push(candidate.key);
+    }
+    evicted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority_order() {
+        let big_far = LoadPriority::new(300.0, 50.0);
+        let big_near = LoadPriority::new(260.0, 10.0);
+        let small_near = LoadPriority::new(40.0, 1.0);
+        // Même octave : le plus proche l'emporte ; sinon le plus grand à l'écran
+        assert!(big_near > big_far);
+        assert!(big_far > small_near);
+        assert!(LoadPriority::new(f32::INFINITY, 0.5) > big_near);
+        assert!(LoadPriority::LOWEST < small_near);
+        assert!(LoadPriority::new(0.1, 2.0) > LoadPriority::LOWEST);
+        assert_eq!(LoadPriority::new(300.0, 5.0), LoadPriority::new(400.0, 5.0));
+    }
+
+    #[test]
+    fn test_evictions_follow_priority() {
+        let resident = |key, bytes, priority, in_use| Resident { key, bytes, last_visible: 7, priority, in_use };
+        let mut residents = vec![
+            resident(0, 100, LoadPriority::new(1000.0, 2.0), true),
+            resident(1, 100, LoadPriority::new(500.0, 4.0), false),
+            resident(2, 100, LoadPriority::new(20.0, 30.0), false),
+            resident(3, 100, LoadPriority::LOWEST, false),
+            resident(4, 100, LoadPriority::LOWEST, true),
+        ];
+        assert!(select_evictions(&residents, 500).is_empty());
+        assert_eq!(select_evictions(&residents, 400), vec![3]);
+        assert_eq!(select_evictions(&residents, 250), vec![3, 2, 1]);
+        // Les maillages affichés restent, quitte à dépasser le budget
+        assert_eq!(select_evictions(&residents, 0), vec![3, 2, 1]);
+        // Un maillage vu il y a longtemps part avant les moins importants vus récemment
+        residents[1].last_visible = 2;
+        assert_eq!(select_evictions(&residents, 400), vec![1]);
+    }
+}
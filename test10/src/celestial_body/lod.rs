@@ -0,0 +1,96 @@
+use std::f32::consts::PI;
+
+// Longueur visée d'une arête de triangle à l'écran, en pixels
+const TARGET_EDGE_PIXELS: f32 = 6.0;
+// Arêtes le long d'un grand cercle de l'icosaèdre non subdivisé ; doublées à chaque subdivision
+const BASE_GREAT_CIRCLE_EDGES: f32 = 5.0;
+// Marge, en niveaux, à franchir avant de quitter le LOD courant
+const LOD_HYSTERESIS: f32 = 0.35;
+/// Niveaux extrêmes d'un corps entier ; au-delà, le maillage de la sphère complète devient trop lourd
+pub const MIN_BODY_LOD: u8 = 1;
+pub const MAX_BODY_LOD: u8 = 7;
+
+/// Diamètre apparent, en pixels, d'une sphère de rayon `radius` vue à `distance` par une caméra
+/// d'ouverture verticale `fovy` (radians) sur un écran de `viewport_height` pixels
+pub fn screen_diameter(radius: f32, distance: f32, fovy: f32, viewport_height: f32) -> f32 {
+    if distance <= radius {
+        return f32::INFINITY;
+    }
+    viewport_height * radius / (distance * (0.5 * fovy).tan())
+}
+
+/// Niveau de subdivision, non arrondi, dont les arêtes mesurent TARGET_EDGE_PIXELS à l'écran
+pub fn ideal_lod(screen_diameter: f32) -> f32 {
+    (PI * screen_diameter / (TARGET_EDGE_PIXELS * BASE_GREAT_CIRCLE_EDGES)).log2()
+}
+
+/// LOD à afficher pour un diamètre apparent `screen_diameter`, sachant que `current` est affiché :
+/// il n'en change qu'une fois le niveau idéal sorti de sa plage d'une marge LOD_HYSTERESIS,
+/// pour éviter les allers-retours quand la distance oscille autour d'un seuil
+pub fn select_lod(current: Option<u8>, screen_diameter: f32) -> u8 {
+    let ideal = ideal_lod(screen_diameter);
+    let target = ideal.ceil().clamp(MIN_BODY_LOD as f32, MAX_BODY_LOD as f32) as u8;
+    match current {
+        // `current` convient exactement pour un niveau idéal dans ]current - 1, current]
+        Some(current) if ideal > current as f32 - 1.0 - LOD_HYSTERESIS && ideal <= current as f32 + LOD_HYSTERESIS => {
+            current.clamp(MIN_BODY_LOD, MAX_BODY_LOD)
+        }
+        _ => target,
+    }
+}
+
+/// LOD disponible le plus proche de `desired`, le plus fin en cas d'égalité
+pub fn fallback_lod(available: impl IntoIterator<Item = u8>, desired: u8) -> Option<u8> {
+    available.into_iter().min_by_key(|&lod| (lod.abs_diff(desired), u8::MAX - lod))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_screen_diameter() {
+        let fovy = 90.0_f32.to_radians();
+        // Ouverture de 90° : à distance d, l'écran couvre une hauteur 2d
+        assert!((screen_diameter(1.0, 10.0, fovy, 1000.0) - 100.0).abs() < 1.0e-3);
+        assert!((screen_diameter(1.0, 20.0, fovy, 1000.0) - 50.0).abs() < 1.0e-3);
+        assert_eq!(screen_diameter(1.0, 0.5, fovy, 1000.0), f32::INFINITY);
+    }
+
+    #[test]
+    fn test_lod_grows_with_screen_size() {
+        let lods: Vec<u8> = [1.0, 30.0, 100.0, 300.0, 1000.0, 1.0e5, f32::INFINITY].iter().map(|&d| select_lod(None, d)).collect();
+        assert!(lods.windows(2).all(|pair| pair[0] <= pair[1]), "{:?}", lods);
+        assert_eq!(lods[0], MIN_BODY_LOD);
+        assert_eq!(*lods.last().unwrap(), MAX_BODY_LOD);
+        // Un diamètre deux fois plus grand demande un niveau de plus
+        assert_eq!(select_lod(None, 400.0), select_lod(None, 200.0) + 1);
+    }
+
+    #[test]
+    fn test_hysteresis() {
+        // Diamètre pour lequel le niveau idéal vaut exactement `level`
+        let diameter = |level: f32| 2.0_f32.powf(level) * TARGET_EDGE_PIXELS * BASE_GREAT_CIRCLE_EDGES / PI;
+        assert_eq!(select_lod(None, diameter(4.05)), 5);
+        // Juste sous le seuil : sans historique on descend, avec le niveau 5 affiché on le garde
+        assert_eq!(select_lod(None, diameter(3.9)), 4);
+        assert_eq!(select_lod(Some(5), diameter(3.9)), 5);
+        assert_eq!(select_lod(Some(5), diameter(3.6)), 4);
+        // Et symétriquement en montant
+        assert_eq!(select_lod(Some(4), diameter(4.2)), 4);
+        assert_eq!(select_lod(Some(4), diameter(4.4)), 5);
+        // Un saut de plusieurs niveaux va directement à la cible
+        assert_eq!(select_lod(Some(2), diameter(5.5)), 6);
+        // Le niveau courant reste dans les bornes
+        assert_eq!(select_lod(Some(9), f32::INFINITY), MAX_BODY_LOD);
+    }
+
+    #[test]
+    fn test_fallback_lod() {
+        assert_eq!(fallback_lod([2, 5], 5), Some(5));
+        assert_eq!(fallback_lod([2, 5], 4), Some(5));
+        assert_eq!(fallback_lod([2, 6], 4), Some(6));
+        assert_eq!(fallback_lod([2, 7], 3), Some(2));
+        assert_eq!(fallback_lod([], 3), None);
+    }
+}
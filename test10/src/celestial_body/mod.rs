@@ -3,29 +3,57 @@ pub mod planet {
     pub mod render_pipeline;
     pub mod planet_vertex;
     pub mod planet_instance;
+    pub mod terrain_parameters;
+    pub mod terrain_chunk;
+    pub mod planet_terrain;
 }
 
 pub mod star {
+    pub mod blackbody;
     pub mod star_geometry;
     pub mod star_vertex;
     pub mod star_instance;
     pub mod render_pipeline;
 }
 
+pub mod ring {
+    pub mod ring_geometry;
+    pub mod ring_vertex;
+    pub mod render_pipeline;
+}
+
+pub mod asteroid {
+    pub mod asteroid_geometry;
+    pub mod asteroid_field;
+}
+
+pub mod starfield {
+    pub mod starfield_geometry;
+    pub mod starfield_vertex;
+    pub mod render_pipeline;
+}
+
 pub mod geometry_loader;
+pub mod generation;
+pub mod instance_buffer;
+pub mod job_queue;
+pub mod load_priority;
+pub mod lod;
+pub mod protocol;
+pub mod residency;
+#[cfg(target_arch = "wasm32")]
 pub mod worker;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod thread_pool;
 
 pub use planet::planet_geometry::{PlanetGeometry, PlanetVertex};
+pub use planet::terrain_parameters::TerrainParameters;
 pub use planet::planet_vertex::Vertex;
 pub use planet::planet_instance;
 pub use planet::render_pipeline::planet_render_pipeline;
 pub use star::render_pipeline::star_render_pipeline;
+pub use ring::render_pipeline::ring_render_pipeline;
 
 pub use star::star_geometry::{StarVertex};
 pub use star::star_instance;
 
-
-pub const LOD_SHARED_ARRAY_BUFFER_POS: [u32; 10] = [144, 504, 1944, 7704, 30744, 122904, 491544, 1966104, 7864344, 31457304];
-pub const LOD_SHARED_ARRAY_BUFFER_COL: [u32; 10] = [144, 504, 1944, 7704, 30744, 122904, 491544, 1966104, 7864344, 31457304];
-pub const LOD_SHARED_ARRAY_BUFFER_NOR: [u32; 10] = [144, 504, 1944, 7704, 30744, 122904, 491544, 1966104, 7864344, 31457304];
-pub const LOD_SHARED_ARRAY_BUFFER_IND: [u32; 10] = [240, 960, 3840, 15360, 61440, 245760, 983040, 3932160, 15728640, 62914560];
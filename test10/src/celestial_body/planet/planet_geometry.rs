@@ -2,8 +2,13 @@ use glam::{Vec3};
 use crate::geometry::{icosphere::IcoSphere};
 use crate::geometry::kdtree3d::KDTree3D;
 use crate::geometry::fbm::fbm_perlin_noise;
+use super::terrain_parameters::TerrainParameters;
+use crate::stellar_system::stellar_system::PlanetClass;
 use std::f32::consts::PI;
 
+// Nombre de signalements d'avancement pendant une génération
+const PROGRESS_STEPS: usize = 8;
+
 
 
 // fn worker_new(name: &str) -> Worker {
@@ -64,6 +69,7 @@ struct Vec3Padded {
 // unsafe impl bytemuck::Pod for Params {}
 // unsafe impl bytemuck::Zeroable for Params {}
 
+#[derive(Clone, Copy)]
 struct ColorPoint {
     color: Vec3,
     key: f32,
@@ -84,6 +90,125 @@ impl ColorPoint {
     }
 }
 
+/// Palettes des six biomes (océan, désert, forêt, toundra, montagne, neige)
+fn biome_palettes(class: PlanetClass, terrain: &TerrainParameters) -> [Vec<ColorPoint>; 6] {
+    if class == PlanetClass::Desert {
+        return desert_palettes();
+    }
+    // Les océans gelés deviennent de la banquise ; un monde océan a des abysses plus profonds
+    let ocean = if terrain.frozen_ocean {
+        vec![
+            ColorPoint::from_hex(0x8FA9BF, -0.2),
+            ColorPoint::from_hex(0xB9D3E6, -0.1),
+            ColorPoint::from_hex(0xDDEBF5, -0.005),
+            ColorPoint::from_hex(0xF2F8FC, 0.0)
+        ]
+    } else if class == PlanetClass::Ocean {
+        vec![
+            ColorPoint::from_hex(0x00001A, -0.6),
+            ColorPoint::from_hex(0x000030, -0.2),
+            ColorPoint::from_hex(0x0B3D6B, -0.05),
+            ColorPoint::from_hex(0x2A8FB8, 0.0)
+        ]
+    } else {
+        vec![
+            ColorPoint::from_hex(0x000030, -0.2),
+            ColorPoint::from_hex(0x000041, -0.1),
+            ColorPoint::from_hex(0x35698C, -0.005),
+            ColorPoint::from_hex(0x40E0D0, 0.0)
+        ]
+    };
+    [
+        ocean,
+        vec![
+            ColorPoint::from_hex(0xC2B280, 0.0),
+            ColorPoint::from_hex(0xEEDC82, 0.5),
+            ColorPoint::from_hex(0xFFE4B5, 1.0),
+        ],
+        vec![
+            ColorPoint::from_hex(0x05400A, -1.0),
+            ColorPoint::from_hex(0x527048, 0.0),
+            ColorPoint::from_hex(0x7CFC00, 1.0),
+        ],
+        vec![
+            ColorPoint::from_hex(0x9FA8A3, 0.0),
+            ColorPoint::from_hex(0xDCE3E1, 1.0),
+        ],
+        vec![
+            ColorPoint::from_hex(0x000000, 0.0),
+            ColorPoint::from_hex(0x222222, 0.01),
+            ColorPoint::from_hex(0x333333, 0.05),
+            ColorPoint::from_hex(0x666666, 0.09),
+            ColorPoint::from_hex(0x777777, 0.1),
+            ColorPoint::from_hex(0x8c8c9c, 0.9),
+        ],
+        vec![
+            ColorPoint::from_hex(0xEEEEEE, 0.0),
+            ColorPoint::from_hex(0xFFFFFF, 1.0),
+        ],
+    ]
+}
+
+/// Palette des bandes d'une géante gazeuse ; les océans gelés indiquent une géante de glace
+fn gas_giant_palette(terrain: &TerrainParameters) -> Vec<ColorPoint> {
+    if terrain.frozen_ocean {
+        vec![
+            ColorPoint::from_hex(0x4F7FA8, -1.0),
+            ColorPoint::from_hex(0x7FB3D5, 0.0),
+            ColorPoint::from_hex(0xC4E3F0, 1.0),
+        ]
+    } else {
+        vec![
+            ColorPoint::from_hex(0x8C5A3C, -1.0),
+            ColorPoint::from_hex(0xC99E6E, -0.3),
+            ColorPoint::from_hex(0xE8D3AE, 0.3),
+            ColorPoint::from_hex(0xF5EEDC, 1.0),
+        ]
+    }
+}
+
+/// Palettes d'un monde désert : dunes à la place des forêts, roche nue et sel à la place de la neige
+fn desert_palettes() -> [Vec<ColorPoint>; 6] {
+    let dunes = vec![
+        ColorPoint::from_hex(0xB5835A, -1.0),
+        ColorPoint::from_hex(0xD9A066, 0.0),
+        ColorPoint::from_hex(0xF0C987, 1.0),
+    ];
+    [
+        // Sans mer : fonds asséchés
+        vec![
+            ColorPoint::from_hex(0x8A6F55, -0.2),
+            ColorPoint::from_hex(0xA88B6A, 0.0),
+        ],
+        dunes.clone(),
+        dunes,
+        vec![
+            ColorPoint::from_hex(0x9C6B4E, 0.0),
+            ColorPoint::from_hex(0xC48A5F, 1.0),
+        ],
+        vec![
+            ColorPoint::from_hex(0x3A2A20, 0.0),
+            ColorPoint::from_hex(0x5C4033, 0.05),
+            ColorPoint::from_hex(0x7A5A45, 0.1),
+            ColorPoint::from_hex(0x9E8068, 0.9),
+        ],
+        vec![
+            ColorPoint::from_hex(0xE8DCC8, 0.0),
+            ColorPoint::from_hex(0xF5EFE3, 1.0),
+        ],
+    ]
+}
+
+/// Palette d'un monde de lave : basalte refroidi jusqu'à la lave en fusion (couleurs saturées, émissives)
+fn lava_palette() -> Vec<ColorPoint> {
+    vec![
+        ColorPoint::from_hex(0x1A1412, 0.0),
+        ColorPoint::from_hex(0x3B2520, 0.4),
+        ColorPoint::from_hex(0xD9420B, 0.7),
+        ColorPoint::from_hex(0xFFB627, 1.0),
+    ]
+}
+
 fn get_biome_index(temperature: f32, humidity: f32, altitude: f32, sea_level: f32) -> usize {
     if altitude < sea_level {
         // Biome::Ocean
@@ -194,20 +319,8 @@ impl PlanetVertex {
 pub struct PlanetGeometry {
     max_subdivision: u8,
     pub radius: f32,
-    level_sea: f32,
-    height_amplitude: f32,
-    continent_octaves: u8,
-    continent_persistence: f32,
-    continent_noise_scale: f32,
-    big_mountain_octaves: u8,
-    big_mountain_persistence: f32,
-    big_mountain_noise_scale: f32,
-    mountain_octaves: u8,
-    mountain_persistence: f32,
-    mountain_noise_scale: f32,
-    biome_octaves: u8,
-    biome_persistence: f32,
-    biome_noise_scale: f32,
+    pub class: PlanetClass,
+    pub terrain: TerrainParameters,
     biome_palettes: [Vec<ColorPoint>; 6],
     sphere_vertices: Vec<f32>,
     sphere_indices: Vec<u32>,
@@ -222,56 +335,16 @@ pub struct PlanetGeometry {
 
 impl PlanetGeometry {
     pub fn new(radius: f32) -> Self {
+        Self::with_terrain(radius, PlanetClass::Terrestrial, TerrainParameters::default())
+    }
+
+    pub fn with_terrain(radius: f32, class: PlanetClass, terrain: TerrainParameters) -> Self {
         PlanetGeometry {
             max_subdivision: 5,
             radius,
-            level_sea: 0.998,
-            height_amplitude: 0.05,
-            continent_octaves: 3,
-            continent_persistence: 0.5,
-            continent_noise_scale: 0.8,
-            big_mountain_octaves: 8,
-            big_mountain_persistence: 0.7,
-            big_mountain_noise_scale: 4.0,
-            mountain_octaves: 8,
-            mountain_persistence: 0.9,
-            mountain_noise_scale: 2.0,
-            biome_octaves: 3,
-            biome_persistence: 0.6,
-            biome_noise_scale: 5.0,
-            biome_palettes: [
-                vec![
-                    ColorPoint::from_hex(0x000030, -0.2),
-                    ColorPoint::from_hex(0x000041, -0.1),
-                    ColorPoint::from_hex(0x35698C, -0.005),
-                    ColorPoint::from_hex(0x40E0D0, 0.0)
-                ],
-                vec![
-                    ColorPoint::from_hex(0xC2B280, 0.0),
-                    ColorPoint::from_hex(0xEEDC82, 0.5),
-                    ColorPoint::from_hex(0xFFE4B5, 1.0),
-                ],
-                vec![
-                    ColorPoint::from_hex(0x05400A, -1.0),
-                    ColorPoint::from_hex(0x527048, 0.0),
-                    ColorPoint::from_hex(0x7CFC00, 1.0),
-                ],
-                vec![
-                    ColorPoint::from_hex(0x9FA8A3, 0.0),
-                    ColorPoint::from_hex(0xDCE3E1, 1.0),
-                ],
-                vec![
-                    ColorPoint::from_hex(0x000000, 0.0),
-                    ColorPoint::from_hex(0x222222, 0.01),
-                    ColorPoint::from_hex(0x333333, 0.05),
-                    ColorPoint::from_hex(0x666666, 0.09),
-                    ColorPoint::from_hex(0x777777, 0.1),
-                    ColorPoint::from_hex(0x8c8c9c, 0.9),
-                ],
-                vec![
-                    ColorPoint::from_hex(0xEEEEEE, 0.0),
-                    ColorPoint::from_hex(0xFFFFFF, 1.0),
-                ]],
+            class,
+            biome_palettes: biome_palettes(class, &terrain),
+            terrain,
             sphere_vertices: Vec::new(),
             sphere_indices: Vec::new(),
             lod_max_solid: None,
@@ -699,6 +772,11 @@ impl PlanetGeometry {
 
 
     pub fn generate(&mut self, subdivision: u8) {
+        self.generate_with_progress(subdivision, &mut |_| {});
+    }
+
+    /// Comme `generate`, en signalant l'avancement (entre 0 et 1) pendant le calcul du relief
+    pub fn generate_with_progress(&mut self, subdivision: u8, progress: &mut dyn FnMut(f32)) {
         let mut solid = IcoSphere::new();
         solid.generate(subdivision);
 
@@ -718,7 +796,11 @@ impl PlanetGeometry {
         indice.reserve(indice_count);
 
         // Remplir les vertices
+        let progress_step = vertex_count.div_ceil(PROGRESS_STEPS).max(1);
         for (i, vertex) in vertices.iter().enumerate() {
+            if i % progress_step == 0 {
+                progress(i as f32 / vertex_count as f32);
+            }
 
             let (v, c) = self.compute_vertex_data(*vertex);
 
@@ -931,24 +1013,77 @@ impl PlanetGeometry {
     }
 
     // Fonction helper pour calculer les vertices avec Perlin noise (thread-safe)
-    fn compute_vertex_data(&self, v: Vec3) -> (Vec3, Vec3) {
+    pub fn compute_vertex_data(&self, v: Vec3) -> (Vec3, Vec3) {
+        match self.class {
+            PlanetClass::GasGiant => self.compute_gas_giant_vertex(v),
+            PlanetClass::Lava => self.compute_lava_vertex(v),
+            PlanetClass::Terrestrial | PlanetClass::Ice | PlanetClass::Ocean | PlanetClass::Desert => self.compute_terrain_vertex(v),
+        }
+    }
+
+    /// Géante gazeuse : sphère non déformée, bandes de latitude perturbées par le bruit
+    fn compute_gas_giant_vertex(&self, v: Vec3) -> (Vec3, Vec3) {
+        let terrain = &self.terrain;
+        let p: Vec3 = v + terrain.noise_offset;
+        let turbulence: f32 = fbm_perlin_noise(p.x, p.y, p.z, terrain.biome_octaves, terrain.biome_persistence, terrain.biome_noise_scale);
+        let latitude: f32 = v.y.acos() / PI;
+        // Nombre de bandes propre à la planète
+        let bands: f32 = 6.0 + terrain.continent_noise_scale * 5.0;
+        let band: f32 = ((latitude + 0.04 * turbulence) * bands * PI).sin();
+        let color = get_color_from_noise(band, &gas_giant_palette(terrain));
+        (self.radius * v, color)
+    }
+
+    /// Monde de lave : relief terrestre, mers de lave et fissures émissives sur les terres
+    fn compute_lava_vertex(&self, v: Vec3) -> (Vec3, Vec3) {
+        let terrain = &self.terrain;
+        let p: Vec3 = v + terrain.noise_offset;
+        let continent_noise : f32 = fbm_perlin_noise(p.x, p.y, p.z, terrain.continent_octaves, terrain.continent_persistence, terrain.continent_noise_scale);
+        let moutain_noise : f32 = fbm_perlin_noise(p.x, p.y, p.z, terrain.mountain_octaves, terrain.mountain_persistence, terrain.mountain_noise_scale);
+
+        let height = self.radius * terrain.height_amplitude;
+        let mut deformed_radius: f32 = self.radius + (continent_noise * 0.6 + moutain_noise * 0.4) * height;
+        let level_lava: f32 = self.radius * (1.0 - 0.2 * terrain.height_amplitude);
+        let molten: bool = deformed_radius <= level_lava;
+        if molten {
+            deformed_radius = level_lava;
+        }
+
+        // Les fissures suivent les crêtes (passages à zéro) du bruit de montagne
+        let crack: f32 = 1.0 - smoothstep(0.0, 0.04, moutain_noise.abs());
+        let palette = lava_palette();
+        let color = if molten {
+            get_color_from_noise(1.0, &palette)
+        } else {
+            get_color_from_noise(crack, &palette)
+        };
+        (deformed_radius * v, color)
+    }
+
+    /// Planète tellurique (ou glacée) : continents, montagnes, océans et biomes
+    fn compute_terrain_vertex(&self, v: Vec3) -> (Vec3, Vec3) {
+        let terrain = &self.terrain;
+        // Coordonnées du bruit décalées selon la graine de la planète
+        let p: Vec3 = v + terrain.noise_offset;
         // Calculer la position finale avec le rayon
-        let continent_noise : f32 = fbm_perlin_noise(v.x, v.y, v.z, self.continent_octaves, self.continent_persistence, self.continent_noise_scale);
-        let big_moutain_noise : f32 = fbm_perlin_noise(v.x, v.y, v.z, self.big_mountain_octaves, self.big_mountain_persistence, self.big_mountain_noise_scale);
-        let moutain_noise : f32 = fbm_perlin_noise(v.x, v.y, v.z, self.mountain_octaves, self.mountain_persistence, self.mountain_noise_scale);
-        let biome_noise : f32 = fbm_perlin_noise(v.x, v.y, v.z, self.biome_octaves, self.biome_persistence, self.biome_noise_scale);
+        let continent_noise : f32 = fbm_perlin_noise(p.x, p.y, p.z, terrain.continent_octaves, terrain.continent_persistence, terrain.continent_noise_scale);
+        let big_moutain_noise : f32 = fbm_perlin_noise(p.x, p.y, p.z, terrain.big_mountain_octaves, terrain.big_mountain_persistence, terrain.big_mountain_noise_scale);
+        let moutain_noise : f32 = fbm_perlin_noise(p.x, p.y, p.z, terrain.mountain_octaves, terrain.mountain_persistence, terrain.mountain_noise_scale);
+        let biome_noise : f32 = fbm_perlin_noise(p.x, p.y, p.z, terrain.biome_octaves, terrain.biome_persistence, terrain.biome_noise_scale);
 
         let latitude: f32 = v.y.acos() / PI;
         let continent_factor: f32 = (moutain_noise * big_moutain_noise * 0.6) + (continent_noise * 0.4);
         let weight_continent: f32 = smoothstep(0.0, 0.1, continent_noise);
         let weight_big_mountain: f32 = smoothstep(0.0, 0.2, big_moutain_noise);
 
-        let mut deformed_radius: f32 = self.radius + (continent_factor * self.height_amplitude);
-        deformed_radius += weight_big_mountain * weight_continent * big_moutain_noise * self.height_amplitude / 4.0;
+        let height = self.radius * terrain.height_amplitude;
+        let mut deformed_radius: f32 = self.radius + (continent_factor * height);
+        deformed_radius += weight_big_mountain * weight_continent * big_moutain_noise * height / 4.0;
 
-        let under_water: bool = deformed_radius <= self.level_sea;
+        let level_sea: f32 = terrain.sea_radius(self.radius);
+        let under_water: bool = deformed_radius <= level_sea;
         if under_water {
-            deformed_radius = self.level_sea;
+            deformed_radius = level_sea;
         }
 
         let final_vertex = deformed_radius * v;
@@ -956,11 +1091,11 @@ impl PlanetGeometry {
         let final_color = if under_water {
             get_color_from_noise(continent_factor, &self.biome_palettes[0])
         } else {
-            let altitude_normalized: f32 = (deformed_radius - self.radius) / self.height_amplitude;
-            let temperature: f32 = compute_temperature(latitude, altitude_normalized, v);
-            let humidity: f32 = compute_humidity(v);
+            let altitude_normalized: f32 = (deformed_radius - self.radius) / height;
+            let temperature: f32 = compute_temperature(latitude, altitude_normalized, p) + terrain.temperature_offset;
+            let humidity: f32 = compute_humidity(p);
 
-            let biome_idx = get_biome_index(temperature, humidity, deformed_radius, self.level_sea);
+            let biome_idx = get_biome_index(temperature, humidity, deformed_radius, level_sea);
             let biome_color: Vec3 = get_color_from_noise(biome_noise, &self.biome_palettes[biome_idx]);
             let factor: f32 = moutain_noise * big_moutain_noise;
             let mountain_color: Vec3 = get_color_from_noise(factor, &self.biome_palettes[4]);
@@ -1126,3 +1261,101 @@ impl PlanetGeometry {
 //     }
 
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate(radius: f32, class: PlanetClass, terrain: TerrainParameters) -> PlanetVertex {
+        let mut planet = PlanetGeometry::with_terrain(radius, class, terrain);
+        planet.generate(2);
+        planet.lod_levels[2].clone()
+    }
+
+    fn height_field(terrain: TerrainParameters) -> Vec<f32> {
+        generate(1.0, PlanetClass::Terrestrial, terrain).position
+    }
+
+    fn radii(vertex: &PlanetVertex) -> Vec<f32> {
+        vertex.position.chunks(3).map(|v| Vec3::new(v[0], v[1], v[2]).length()).collect()
+    }
+
+    #[test]
+    fn test_same_seed_same_height_field() {
+        let a = height_field(TerrainParameters::from_seed(7));
+        let b = height_field(TerrainParameters::from_seed(7));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_different_seeds_different_height_fields() {
+        let a = height_field(TerrainParameters::from_seed(7));
+        let b = height_field(TerrainParameters::from_seed(8));
+        assert_eq!(a.len(), b.len());
+        let differing = a.iter().zip(b.iter()).filter(|(x, y)| (*x - *y).abs() > 1.0e-4).count();
+        assert!(differing > a.len() / 4, "{} sommets différents sur {}", differing, a.len());
+    }
+
+    #[test]
+    fn test_no_ocean_keeps_relief() {
+        let mut terrain = TerrainParameters::from_seed(3);
+        terrain.sea_level = crate::celestial_body::planet::terrain_parameters::NO_SEA_LEVEL;
+        let dry = generate(1.0, PlanetClass::Terrestrial, terrain);
+        let min = radii(&dry).iter().cloned().fold(f32::MAX, f32::min);
+        // Sans océan, le relief descend sous l'ancien niveau de la mer
+        assert!(min < TerrainParameters::default().sea_radius(1.0));
+    }
+
+    #[test]
+    fn test_relief_scales_with_radius() {
+        let terrain = TerrainParameters::from_seed(11);
+        let small = radii(&generate(1.0, PlanetClass::Terrestrial, terrain));
+        let large = radii(&generate(3.0, PlanetClass::Terrestrial, terrain));
+        for (a, b) in small.iter().zip(large.iter()) {
+            assert!((b - 3.0 * a).abs() < 1.0e-4);
+        }
+    }
+
+    #[test]
+    fn test_gas_giant_is_not_displaced() {
+        let giant = generate(5.0, PlanetClass::GasGiant, TerrainParameters::from_seed(5));
+        assert!(radii(&giant).iter().all(|r| (r - 5.0).abs() < 1.0e-4));
+        // Bandes : les couleurs varient avec la latitude
+        let first = &giant.color[0..3];
+        assert!(giant.color.chunks(3).any(|c| c != first));
+    }
+
+    #[test]
+    fn test_lava_world_has_emissive_cracks() {
+        let lava = generate(1.0, PlanetClass::Lava, TerrainParameters::from_seed(9));
+        // Couleurs de lave en fusion (rouge dominant et lumineux)
+        assert!(lava.color.chunks(3).any(|c| c[0] > 0.8 && c[0] > c[2] * 2.0));
+        assert!(lava.color.chunks(3).any(|c| c[0] < 0.3));
+    }
+
+    #[test]
+    fn test_ice_world_has_no_liquid_sea() {
+        let mut terrain = TerrainParameters::from_seed(4);
+        terrain.frozen_ocean = true;
+        let ice = generate(1.0, PlanetClass::Ice, terrain);
+        // Aucun sommet ne porte la couleur d'eau liquide de l'océan
+        let ocean = ColorPoint::from_hex(0x40E0D0, 0.0).color;
+        assert!(ice.color.chunks(3).all(|c| (Vec3::new(c[0], c[1], c[2]) - ocean).length() > 1.0e-3));
+    }
+
+    #[test]
+    fn test_ocean_and_desert_palettes() {
+        let mut terrain = TerrainParameters::from_seed(12);
+        terrain.sea_level = 0.6;
+        let ocean = generate(1.0, PlanetClass::Ocean, terrain);
+        let sea = terrain.sea_radius(1.0);
+        // Presque toute la surface est sous la mer
+        let submerged = radii(&ocean).iter().filter(|r| (**r - sea).abs() < 1.0e-5).count();
+        assert!(submerged > ocean.position.len() / 3 * 3 / 4, "{} sommets immergés", submerged);
+
+        terrain.sea_level = crate::celestial_body::planet::terrain_parameters::NO_SEA_LEVEL;
+        let desert = generate(1.0, PlanetClass::Desert, terrain);
+        // Ni végétation ni eau : rouge dominant sur le bleu partout
+        assert!(desert.color.chunks(3).all(|c| c[0] >= c[2]));
+    }
+}
@@ -0,0 +1,294 @@
+use std::collections::{HashMap, HashSet};
+use glam::{DQuat, DVec3, Mat4, Quat, Vec3};
+use wgpu::util::DeviceExt;
+
+use crate::celestial_body::generation::{GeneratedGeometry, GeometryPool, GeometryRequest};
+use crate::celestial_body::geometry_loader::{BodyMesh, MAX_GENERATION_ATTEMPTS};
+use crate::celestial_body::instance_buffer::InstanceBuffer;
+use crate::celestial_body::job_queue::JobId;
+use crate::celestial_body::load_priority::LoadPriority;
+use crate::celestial_body::planet::planet_geometry::{PlanetGeometry, PlanetVertex};
+use crate::celestial_body::planet::planet_instance::InstanceRaw;
+use crate::celestial_body::planet::planet_vertex::Vertex;
+use crate::celestial_body::planet::terrain_chunk::{max_relief, select_chunks, ChunkKey};
+use crate::celestial_body::planet::terrain_parameters::TerrainParameters;
+use crate::stellar_system::stellar_system::PlanetClass;
+
+/// Distance au centre, en rayons, en deçà de laquelle une planète est dessinée par morceaux
+pub const TERRAIN_DISTANCE: f64 = 4.0;
+// Plan proche en fraction de l'altitude, et au plus près de la surface
+const NEAR_PLANE_FRACTION: f64 = 0.5;
+const MIN_NEAR_PLANE: f64 = 1.0e-9;
+
+/// Position de la caméra dans le repère d'une planète : centre à l'origine, rotation annulée
+pub fn local_eye(position: Vec3, rotation: Quat, eye: DVec3) -> DVec3 {
+    rotation.as_dquat().inverse() * (eye - position.as_dvec3())
+}
+
+/// Surface d'une planète vue de près, découpée en morceaux d'un quadtree par face du cube,
+/// générés à la demande par le pool et conservés en mémoire GPU jusqu'à ce que le budget
+/// des maillages les libère
+pub struct PlanetTerrain {
+    planet: PlanetGeometry,
+    relief: f64,
+    // Morceau de chaque demande en cours, et son importance à la dernière image
+    jobs: HashMap<JobId, (ChunkKey, LoadPriority)>,
+    failures: HashMap<ChunkKey, u32>,
+    // Morceaux reçus, en attente d'envoi au GPU
+    pending: Vec<(ChunkKey, LoadPriority, PlanetVertex)>,
+    meshes: HashMap<ChunkKey, BodyMesh>,
+    draw: Vec<ChunkKey>,
+    complete: bool,
+    // Une matrice par morceau dessiné, relative à la caméra
+    instance_buffer: InstanceBuffer,
+}
+
+impl PlanetTerrain {
+    pub fn new(radius: f32, class: PlanetClass, terrain: TerrainParameters) -> Self {
+        Self {
+            planet: PlanetGeometry::with_terrain(radius, class, terrain),
+            relief: max_relief(radius as f64, &terrain),
+            jobs: HashMap::new(),
+            failures: HashMap::new(),
+            pending: Vec::new(),
+            meshes: HashMap::new(),
+            draw: Vec::new(),
+            complete: false,
+            instance_buffer: InstanceBuffer::new("Terrain Instance Buffer"),
+        }
+    }
+
+    /// Choisit les morceaux pour une caméra en `eye` (repère de la planète), demande au pool
+    /// ceux qui manquent et abandonne les demandes devenues inutiles. `priority` donne
+    /// l'importance d'un morceau d'après son rayon et sa distance à la caméra. Renvoie les
+    /// morceaux dessinés et leur importance, vus pendant l'image
+    pub fn update(&mut self, eye: DVec3, pool: &mut GeometryPool, priority: &dyn Fn(f64, f64) -> LoadPriority) -> Vec<(ChunkKey, LoadPriority)> {
+        let radius = self.planet.radius as f64;
+        let chunk_priority = |key: &ChunkKey| priority(key.size(radius) / 2.0, eye.distance(key.center(radius)));
+        let selection = select_chunks(eye, self.planet.radius as f64, self.relief, |key| self.meshes.contains_key(key));
+
+        let wanted: HashSet<ChunkKey> = selection.wanted.iter().copied().collect();
+        let stale: Vec<JobId> = self.jobs.iter().filter(|(_, (key, _))| !wanted.contains(key)).map(|(job, _)| *job).collect();
+        for job in stale {
+            self.jobs.remove(&job);
+            pool.cancel(job);
+        }
+        for (job, (key, job_priority)) in &mut self.jobs {
+            *job_priority = chunk_priority(key);
+            pool.reprioritize(*job, *job_priority);
+        }
+        for key in selection.wanted {
+            let requested = self.jobs.values().any(|(pending, _)| *pending == key)
+                || self.pending.iter().any(|(pending, _, _)| *pending == key);
+            if requested || self.failures.get(&key).is_some_and(|&failures| failures >= MAX_GENERATION_ATTEMPTS) {
+                continue;
+            }
+            let request = GeometryRequest::Chunk {
+                radius: self.planet.radius,
+                class: self.planet.class,
+                terrain: self.planet.terrain,
+                chunk: key,
+            };
+            // File pleine : la demande sera renouvelée à la prochaine image
+            let Ok(job) = pool.submit(request, chunk_priority(&key)) else { break };
+            self.jobs.insert(job, (key, chunk_priority(&key)));
+        }
+
+        self.draw = selection.draw;
+        self.complete = selection.complete;
+        self.draw.iter().map(|key| (*key, chunk_priority(key))).collect()
+    }
+
+    /// Quitte le mode rapproché : les demandes en cours sont abandonnées ; les morceaux
+    /// restent en mémoire jusqu'à ce que le budget les libère
+    pub fn deactivate(&mut self, pool: &mut GeometryPool) {
+        for (job, _) in self.jobs.drain() {
+            pool.cancel(job);
+        }
+        self.pending.clear();
+        self.draw.clear();
+        self.complete = false;
+    }
+
+    pub fn has_job(&self, job: JobId) -> bool {
+        self.jobs.contains_key(&job)
+    }
+
+    /// Nombre de morceaux demandés et pas encore reçus
+    pub fn jobs(&self) -> usize {
+        self.jobs.len()
+    }
+
+    /// Range le morceau généré par `job` ; false s'il ne lui était pas destiné
+    pub fn receive(&mut self, job: JobId, geometry: GeneratedGeometry) -> bool {
+        let Some((key, priority)) = self.jobs.remove(&job) else { return false };
+        match geometry {
+            GeneratedGeometry::Chunk(chunk) => {
+                self.failures.remove(&key);
+                self.pending.push((key, priority, chunk));
+            }
+            _ => *self.failures.entry(key).or_default() += 1,
+        }
+        true
+    }
+
+    /// Enregistre l'échec de `job` ; le morceau sera redemandé tant qu'il reste des tentatives
+    pub fn fail(&mut self, job: JobId) -> Option<ChunkKey> {
+        let (key, _) = self.jobs.remove(&job)?;
+        *self.failures.entry(key).or_default() += 1;
+        Some(key)
+    }
+
+    /// Envoie au GPU les morceaux reçus ; renvoie leur taille en octets et leur importance,
+    /// pour la comptabilité des maillages
+    pub fn upload_if_ready(&mut self, device: &wgpu::Device) -> Vec<(ChunkKey, u64, LoadPriority)> {
+        let mut uploaded = Vec::new();
+        for (key, priority, chunk) in self.pending.drain(..) {
+            let vertices = Vertex::planet_vertex_to_vertex(&chunk);
+            let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Terrain Chunk Vertex Buffer"),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+            let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Terrain Chunk Index Buffer"),
+                contents: bytemuck::cast_slice(&chunk.indice),
+                usage: wgpu::BufferUsages::INDEX,
+            });
+            let mesh = BodyMesh { vertex_buffer, index_buffer, num_indices: chunk.indice.len() as u32 };
+            uploaded.push((key, mesh.bytes(), priority));
+            self.meshes.insert(key, mesh);
+        }
+        uploaded
+    }
+
+    /// Libère le maillage du morceau `key`, désigné par le budget des maillages
+    pub fn evict(&mut self, key: ChunkKey) {
+        self.meshes.remove(&key);
+    }
+
+    /// Morceaux dessinés à cette image, à ne pas libérer
+    pub fn drawn(&self) -> &[ChunkKey] {
+        &self.draw
+    }
+
+    /// Les morceaux couvrent toute la partie visible : ils remplacent le maillage du corps entier
+    pub fn is_drawn(&self) -> bool {
+        self.complete && !self.draw.is_empty()
+    }
+
+    /// Rayon de la surface dans la direction `direction` (repère de la planète)
+    pub fn surface_radius(&self, direction: DVec3) -> f64 {
+        self.planet.compute_vertex_data(direction.normalize_or_zero().as_vec3()).0.length() as f64
+    }
+
+    /// Altitude de la caméra en `eye` au-dessus du sol
+    pub fn altitude(&self, eye: DVec3) -> f64 {
+        eye.length() - self.surface_radius(eye)
+    }
+
+    /// Plan proche adapté à l'altitude ; le tampon de profondeur inversé garde sa précision
+    /// jusqu'à l'horizon
+    pub fn near_plane(&self, eye: DVec3) -> f32 {
+        (self.altitude(eye) * NEAR_PLANE_FRACTION).max(MIN_NEAR_PLANE) as f32
+    }
+
+    /// Écrit la matrice de chaque morceau dessiné, translation relative à la caméra : calculée
+    /// en f64 dans le repère de la planète, elle reste précise à quelques mètres du sol
+    pub fn write_instances(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, rotation: Quat, eye: DVec3) {
+        let radius = self.planet.radius as f64;
+        let rotation_f64: DQuat = rotation.as_dquat();
+        let instances: Vec<InstanceRaw> = self.draw.iter().map(|key| {
+            let translation = (rotation_f64 * (key.center(radius) - eye)).as_vec3();
+            InstanceRaw { model: (Mat4::from_translation(translation) * Mat4::from_quat(rotation)).to_cols_array_2d() }
+        }).collect();
+        self.instance_buffer.write(device, queue, &instances);
+    }
+
+    /// Dessine les morceaux retenus ; le pipeline et la caméra relative sont déjà en place
+    pub fn render(&self, render_pass: &mut wgpu::RenderPass) {
+        let Some(instance_buffer) = self.instance_buffer.buffer() else { return };
+        render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+        for (instance, key) in self.draw.iter().enumerate() {
+            let Some(mesh) = self.meshes.get(key) else { continue };
+            let instance = instance as u32;
+            render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+            render_pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..mesh.num_indices, 0, instance..instance + 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::celestial_body::generation::GenerationEvent;
+    use std::time::{Duration, Instant};
+
+    fn distance_only(_: f64, distance: f64) -> LoadPriority {
+        LoadPriority::new(0.0, distance as f32)
+    }
+
+    fn terrain() -> PlanetTerrain {
+        PlanetTerrain::new(1.0, PlanetClass::Terrestrial, TerrainParameters::from_seed(6))
+    }
+
+    #[test]
+    fn test_faces_are_requested_first() {
+        let mut pool = GeometryPool::new(1, 16);
+        let mut terrain = terrain();
+        let eye = DVec3::new(0.0, 0.0, 1.5);
+        terrain.update(eye, &mut pool, &distance_only);
+        // Face arrière cachée par la planète
+        assert_eq!(terrain.jobs(), 5);
+        assert!(!terrain.is_drawn());
+        // Pas de nouvelle demande tant que les premières sont en cours
+        terrain.update(eye, &mut pool, &distance_only);
+        assert_eq!(terrain.jobs(), 5);
+        // De l'autre côté, la face avant devient inutile et sa demande est abandonnée
+        terrain.update(-eye, &mut pool, &distance_only);
+        assert!(terrain.jobs.values().all(|(key, _)| key.face != 4));
+
+        let job = *terrain.jobs.keys().next().unwrap();
+        assert!(terrain.fail(job).is_some());
+        assert!(!terrain.has_job(job));
+        terrain.deactivate(&mut pool);
+        assert_eq!(terrain.jobs(), 0);
+    }
+
+    #[test]
+    fn test_chunks_reach_the_terrain() {
+        let mut pool = GeometryPool::new(2, 16);
+        let mut terrain = terrain();
+        terrain.update(DVec3::new(0.0, 3.0, 0.0), &mut pool, &distance_only);
+        let start = Instant::now();
+        while terrain.jobs() > 0 {
+            assert!(start.elapsed() < Duration::from_secs(30), "génération trop longue");
+            for event in pool.poll() {
+                match event {
+                    GenerationEvent::Finished { job, geometry } => assert!(terrain.receive(job, geometry)),
+                    GenerationEvent::Failed { error, .. } => panic!("échec inattendu : {}", error),
+                    GenerationEvent::Progress { .. } => {}
+                }
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(terrain.pending.len(), 5);
+        assert!(terrain.pending.iter().all(|(key, _, chunk)| key.level == 0 && !chunk.indice.is_empty()));
+        assert!(terrain.failures.is_empty());
+    }
+
+    #[test]
+    fn test_altitude() {
+        let terrain = terrain();
+        let direction = DVec3::new(0.2, -0.5, 0.8).normalize();
+        let ground = terrain.surface_radius(direction);
+        assert!((ground - 1.0).abs() <= terrain.relief);
+        let eye = direction * (ground + 1.0e-6);
+        assert!((terrain.altitude(eye) - 1.0e-6).abs() < 1.0e-7);
+        assert!(terrain.near_plane(eye) > 0.0 && (terrain.near_plane(eye) as f64) < 1.0e-6);
+        // Sous le sol, le plan proche reste positif
+        assert!(terrain.near_plane(direction * 0.5) > 0.0);
+        assert!(local_eye(Vec3::new(1.0, 2.0, 3.0), Quat::from_rotation_y(1.0), DVec3::new(1.0, 2.0, 5.0)).distance(Quat::from_rotation_y(-1.0).as_dquat() * DVec3::Z * 2.0) < 1.0e-6);
+    }
+}
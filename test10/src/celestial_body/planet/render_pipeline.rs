@@ -1,11 +1,66 @@
 use crate::celestial_body::planet::planet_vertex::Vertex;
 use crate::celestial_body::planet::planet_instance::InstanceRaw;
 
+/// Format du tampon de profondeur partagé par la passe principale et celle des morceaux de terrain
+pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// Test de profondeur d'un pipeline de la scène. Profondeur inversée (effacée à 0) : Greater
+/// garde le plus proche ; Always sans écriture pour ce qui ne masque ni n'est masqué
+pub fn depth_stencil_state(depth_compare: wgpu::CompareFunction, depth_write_enabled: bool) -> wgpu::DepthStencilState {
+    wgpu::DepthStencilState {
+        format: DEPTH_FORMAT,
+        depth_write_enabled,
+        depth_compare,
+        stencil: wgpu::StencilState::default(),
+        bias: wgpu::DepthBiasState::default(),
+    }
+}
+
 pub fn planet_render_pipeline(
     device: &wgpu::Device,
     bind_group_layouts: &[&wgpu::BindGroupLayout],
     config: &wgpu::SurfaceConfiguration
 ) -> wgpu::RenderPipeline
+{
+    build_render_pipeline(device, bind_group_layouts, config, depth_stencil_state(wgpu::CompareFunction::Greater, true))
+}
+
+/// Même shader que les planètes, sur la caméra relative : de près, le relief se masque lui-même
+/// et masque les corps situés derrière lui
+pub fn terrain_render_pipeline(
+    device: &wgpu::Device,
+    bind_group_layouts: &[&wgpu::BindGroupLayout],
+    config: &wgpu::SurfaceConfiguration
+) -> wgpu::RenderPipeline
+{
+    build_render_pipeline(device, bind_group_layouts, config, depth_stencil_state(wgpu::CompareFunction::Greater, true))
+}
+
+/// Tampon de profondeur à la taille de la surface, à recréer quand elle change
+pub fn create_depth_view(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> wgpu::TextureView {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Depth Texture"),
+        size: wgpu::Extent3d {
+            width: config.width.max(1),
+            height: config.height.max(1),
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: DEPTH_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    texture.create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+fn build_render_pipeline(
+    device: &wgpu::Device,
+    bind_group_layouts: &[&wgpu::BindGroupLayout],
+    config: &wgpu::SurfaceConfiguration,
+    depth_stencil: wgpu::DepthStencilState,
+) -> wgpu::RenderPipeline
 {
     let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
         label: Some("Shader"),
@@ -54,7 +109,7 @@ pub fn planet_render_pipeline(
             // Requires Features::CONSERVATIVE_RASTERIZATION
             conservative: false,
         },
-        depth_stencil: None,
+        depth_stencil: Some(depth_stencil),
         multisample: wgpu::MultisampleState {
             count: 1,
             mask: !0,
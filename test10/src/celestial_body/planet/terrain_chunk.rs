@@ -0,0 +1,364 @@
+use std::f64::consts::FRAC_PI_2;
+use glam::{DVec3, Vec3};
+use crate::celestial_body::planet::planet_geometry::{PlanetGeometry, PlanetVertex};
+use crate::celestial_body::planet::terrain_parameters::TerrainParameters;
+
+/// Quadrilatères le long d'un côté de morceau
+pub const CHUNK_RESOLUTION: u32 = 32;
+/// Profondeur maximale du quadtree : les sommets d'une planète de la taille de la Terre
+/// y sont espacés d'environ un mètre
+pub const MAX_CHUNK_LEVEL: u8 = 18;
+// Un morceau est subdivisé quand la caméra s'en approche à moins de SPLIT_DISTANCE fois sa taille
+const SPLIT_DISTANCE: f64 = 1.5;
+// Hauteur des jupes, en fraction de la taille du morceau : elles masquent les fissures
+// entre morceaux de niveaux différents
+const SKIRT_DEPTH: f64 = 0.05;
+// Relief maximal en unités de height_amplitude (continents et grandes montagnes cumulés)
+const RELIEF_FACTOR: f64 = 1.25;
+
+// Normale puis axes u et v de chaque face du cube, avec u × v = normale
+const FACES: [[DVec3; 3]; 6] = [
+    [DVec3::X, DVec3::NEG_Z, DVec3::Y],
+    [DVec3::NEG_X, DVec3::Z, DVec3::Y],
+    [DVec3::Y, DVec3::X, DVec3::NEG_Z],
+    [DVec3::NEG_Y, DVec3::X, DVec3::Z],
+    [DVec3::Z, DVec3::X, DVec3::Y],
+    [DVec3::NEG_Z, DVec3::NEG_X, DVec3::Y],
+];
+
+/// Morceau de terrain : case (x, y) du niveau `level` du quadtree de la face `face` du cube
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ChunkKey {
+    pub face: u8,
+    pub level: u8,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl ChunkKey {
+    /// Les six faces entières du cube
+    pub fn roots() -> impl Iterator<Item = ChunkKey> {
+        (0..6).map(|face| ChunkKey { face, level: 0, x: 0, y: 0 })
+    }
+
+    pub fn children(&self) -> [ChunkKey; 4] {
+        let child = |dx, dy| ChunkKey { face: self.face, level: self.level + 1, x: 2 * self.x + dx, y: 2 * self.y + dy };
+        [child(0, 0), child(1, 0), child(0, 1), child(1, 1)]
+    }
+
+    pub fn parent(&self) -> Option<ChunkKey> {
+        (self.level > 0).then(|| ChunkKey { face: self.face, level: self.level - 1, x: self.x / 2, y: self.y / 2 })
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.face < 6 && self.level <= MAX_CHUNK_LEVEL && self.x < 1 << self.level && self.y < 1 << self.level
+    }
+
+    /// Direction du sommet (i, j) de la grille du morceau, i et j entre 0 et CHUNK_RESOLUTION.
+    /// Calculée à partir d'indices entiers sur toute la face : les sommets communs à deux
+    /// morceaux voisins, même de niveaux différents, sont rigoureusement identiques
+    pub fn grid_direction(&self, i: u32, j: u32) -> DVec3 {
+        let cells = (CHUNK_RESOLUTION as u64) << self.level;
+        let coordinate = |cell: u32, index: u32| {
+            -1.0 + 2.0 * (cell as u64 * CHUNK_RESOLUTION as u64 + index as u64) as f64 / cells as f64
+        };
+        cube_to_sphere(self.face, coordinate(self.x, i), coordinate(self.y, j))
+    }
+
+    pub fn center_direction(&self) -> DVec3 {
+        let cells = 2u64 << self.level;
+        let coordinate = |cell: u32| -1.0 + 2.0 * (2 * cell as u64 + 1) as f64 / cells as f64;
+        cube_to_sphere(self.face, coordinate(self.x), coordinate(self.y))
+    }
+
+    /// Centre du morceau sur la sphère non déformée de rayon `radius`, origine de ses sommets
+    pub fn center(&self, radius: f64) -> DVec3 {
+        self.center_direction() * radius
+    }
+
+    /// Longueur approchée d'un côté du morceau sur une sphère de rayon `radius`
+    pub fn size(&self, radius: f64) -> f64 {
+        radius * FRAC_PI_2 / (1u64 << self.level) as f64
+    }
+}
+
+/// Projette le point (a, b) ∈ [-1, 1]² de la face `face` du cube sur la sphère unité ;
+/// la projection « sphérifiée » répartit les sommets plus uniformément qu'une normalisation
+pub fn cube_to_sphere(face: u8, a: f64, b: f64) -> DVec3 {
+    let [normal, u, v] = FACES[face as usize];
+    let p = normal + u * a + v * b;
+    let (x2, y2, z2) = (p.x * p.x, p.y * p.y, p.z * p.z);
+    DVec3::new(
+        p.x * (1.0 - y2 / 2.0 - z2 / 2.0 + y2 * z2 / 3.0).sqrt(),
+        p.y * (1.0 - z2 / 2.0 - x2 / 2.0 + z2 * x2 / 3.0).sqrt(),
+        p.z * (1.0 - x2 / 2.0 - y2 / 2.0 + x2 * y2 / 3.0).sqrt(),
+    )
+}
+
+/// Écart maximal du relief au rayon d'une planète
+pub fn max_relief(radius: f64, terrain: &TerrainParameters) -> f64 {
+    radius * terrain.height_amplitude as f64 * RELIEF_FACTOR
+}
+
+/// Maillage d'un morceau : grille de (CHUNK_RESOLUTION + 1)² sommets calculés par
+/// `compute_vertex_data`, suivie d'une jupe qui descend sous chacun des sommets du bord.
+/// Les positions sont relatives à `key.center(radius)` pour garder la précision des f32
+/// jusqu'à quelques mètres de la surface
+pub fn generate_chunk(planet: &PlanetGeometry, key: ChunkKey) -> PlanetVertex {
+    let n = CHUNK_RESOLUTION;
+    let side = n + 1;
+    let radius = planet.radius as f64;
+    let center = key.center(radius);
+    let skirt = SKIRT_DEPTH * key.size(radius);
+
+    let mut positions: Vec<Vec3> = Vec::with_capacity((side * side + 4 * n) as usize);
+    let mut colors: Vec<Vec3> = Vec::with_capacity(positions.capacity());
+    let mut heights: Vec<f64> = Vec::with_capacity((side * side) as usize);
+    let mut directions: Vec<DVec3> = Vec::with_capacity((side * side) as usize);
+    for j in 0..side {
+        for i in 0..side {
+            let direction = key.grid_direction(i, j);
+            let (position, color) = planet.compute_vertex_data(direction.as_vec3());
+            let height = position.length() as f64;
+            positions.push((direction * height - center).as_vec3());
+            colors.push(color);
+            heights.push(height);
+            directions.push(direction);
+        }
+    }
+
+    let index = |i: u32, j: u32| j * side + i;
+    let mut indices: Vec<u32> = Vec::with_capacity((6 * n * n + 24 * n) as usize);
+    for j in 0..n {
+        for i in 0..n {
+            let (a, b, c, d) = (index(i, j), index(i + 1, j), index(i + 1, j + 1), index(i, j + 1));
+            indices.extend_from_slice(&[a, b, c, a, c, d]);
+        }
+    }
+
+    // Normales par accumulation, sur la grille seule
+    let mut normals = vec![Vec3::ZERO; positions.len()];
+    for triangle in indices.chunks(3) {
+        let [v0, v1, v2] = [0, 1, 2].map(|k| positions[triangle[k] as usize]);
+        let normal = (v1 - v0).cross(v2 - v0).normalize_or_zero();
+        triangle.iter().for_each(|&k| normals[k as usize] += normal);
+    }
+
+    // Tour du bord dans le sens direct vu de l'extérieur : l'intérieur du morceau reste à gauche
+    let border: Vec<u32> = (0..n).map(|i| index(i, 0))
+        .chain((0..n).map(|j| index(n, j)))
+        .chain((0..n).map(|i| index(n - i, n)))
+        .chain((0..n).map(|j| index(0, n - j)))
+        .collect();
+    let first_skirt = positions.len() as u32;
+    for &top in &border {
+        let top = top as usize;
+        positions.push((directions[top] * (heights[top] - skirt) - center).as_vec3());
+        colors.push(colors[top]);
+        normals.push(normals[top]);
+    }
+    for k in 0..border.len() {
+        let next = (k + 1) % border.len();
+        let (p0, p1) = (border[k], border[next]);
+        let (q0, q1) = (first_skirt + k as u32, first_skirt + next as u32);
+        indices.extend_from_slice(&[p0, q0, q1, p0, q1, p1]);
+    }
+
+    let mut chunk = PlanetVertex::new();
+    chunk.position = positions.iter().flat_map(|p| p.to_array()).collect();
+    chunk.color = colors.iter().flat_map(|c| c.to_array()).collect();
+    chunk.normal = normals.iter().flat_map(|n| n.normalize_or_zero().to_array()).collect();
+    chunk.indice = indices;
+    chunk
+}
+
+/// Morceaux retenus pour une position de caméra
+#[derive(Debug, Default, PartialEq)]
+pub struct ChunkSelection {
+    /// Morceaux prêts à dessiner ; ils couvrent sans recouvrement la partie visible de la planète
+    pub draw: Vec<ChunkKey>,
+    /// Morceaux à générer, les plus grossiers en premier
+    pub wanted: Vec<ChunkKey>,
+    /// Toute la partie visible est couverte ; sinon le maillage du corps entier reste affiché
+    pub complete: bool,
+}
+
+/// Parcourt les quadtrees des six faces pour une caméra en `eye`, dans le repère de la planète
+/// (centre à l'origine, rotation annulée). Un morceau est subdivisé près de la caméra jusqu'à
+/// MAX_CHUNK_LEVEL, mais seulement une fois tous ses enfants visibles prêts : il reste affiché
+/// en attendant. Les morceaux au-delà de l'horizon ne sont ni dessinés ni demandés
+pub fn select_chunks(eye: DVec3, radius: f64, relief: f64, is_ready: impl Fn(&ChunkKey) -> bool) -> ChunkSelection {
+    let mut selection = ChunkSelection { complete: true, ..Default::default() };
+    let horizon = Horizon::new(eye, radius, relief);
+    for root in ChunkKey::roots().filter(|root| horizon.sees(root)) {
+        if is_ready(&root) {
+            visit(root, eye, radius, &horizon, &is_ready, &mut selection);
+        } else {
+            selection.wanted.push(root);
+            selection.complete = false;
+        }
+    }
+    selection.wanted.sort_by_key(|key| key.level);
+    selection
+}
+
+fn visit(key: ChunkKey, eye: DVec3, radius: f64, horizon: &Horizon, is_ready: &impl Fn(&ChunkKey) -> bool, selection: &mut ChunkSelection) {
+    let size = key.size(radius);
+    if key.level < MAX_CHUNK_LEVEL && eye.distance(key.center(radius)) < SPLIT_DISTANCE * size {
+        let children: Vec<ChunkKey> = key.children().into_iter().filter(|child| horizon.sees(child)).collect();
+        let missing: Vec<ChunkKey> = children.iter().copied().filter(|child| !is_ready(child)).collect();
+        if missing.is_empty() {
+            children.into_iter().for_each(|child| visit(child, eye, radius, horizon, is_ready, selection));
+            return;
+        }
+        selection.wanted.extend(missing);
+    }
+    selection.draw.push(key);
+}
+
+// Cône des directions visibles depuis la caméra, relief compris
+struct Horizon {
+    direction: DVec3,
+    angle: f64,
+}
+
+impl Horizon {
+    fn new(eye: DVec3, radius: f64, relief: f64) -> Self {
+        let low = (radius - relief).max(0.0);
+        let distance = eye.length().max(low);
+        // Horizon du sol le plus bas, prolongé jusqu'aux sommets les plus hauts qui le dépassent
+        let angle = if low > 0.0 { (low / distance).acos() + (low / (radius + relief)).acos() } else { std::f64::consts::PI };
+        Self { direction: eye.normalize_or_zero(), angle }
+    }
+
+    fn sees(&self, key: &ChunkKey) -> bool {
+        let angle = |a: DVec3, b: DVec3| a.dot(b).clamp(-1.0, 1.0).acos();
+        let center = key.center_direction();
+        // Rayon angulaire du morceau : ses coins sont ses points les plus éloignés du centre, à une marge près
+        let n = CHUNK_RESOLUTION;
+        let extent = [(0, 0), (n, 0), (0, n), (n, n)].iter()
+            .map(|&(i, j)| angle(center, key.grid_direction(i, j)))
+            .fold(0.0, f64::max) * 1.1;
+        angle(self.direction, center) <= self.angle + extent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stellar_system::stellar_system::PlanetClass;
+
+    fn position(chunk: &PlanetVertex, key: ChunkKey, radius: f64, vertex: u32) -> DVec3 {
+        let k = 3 * vertex as usize;
+        Vec3::new(chunk.position[k], chunk.position[k + 1], chunk.position[k + 2]).as_dvec3() + key.center(radius)
+    }
+
+    #[test]
+    fn test_chunk_hierarchy() {
+        let roots: Vec<ChunkKey> = ChunkKey::roots().collect();
+        assert_eq!(roots.len(), 6);
+        let key = ChunkKey { face: 3, level: 4, x: 9, y: 15 };
+        assert!(key.is_valid());
+        assert!(key.children().iter().all(|child| child.is_valid() && child.parent() == Some(key)));
+        assert_eq!(roots[0].parent(), None);
+        assert!(!ChunkKey { face: 6, level: 0, x: 0, y: 0 }.is_valid());
+        assert!(!ChunkKey { face: 0, level: 2, x: 4, y: 0 }.is_valid());
+        assert!(!ChunkKey { face: 0, level: MAX_CHUNK_LEVEL + 1, x: 0, y: 0 }.is_valid());
+        // Les enfants se partagent exactement l'aire de leur parent
+        assert!((key.children()[0].size(1.0) * 2.0 - key.size(1.0)).abs() < 1.0e-12);
+    }
+
+    #[test]
+    fn test_cube_faces_join() {
+        for face in 0..6 {
+            let [normal, u, v] = FACES[face as usize];
+            assert_eq!(u.cross(v), normal);
+            assert!((cube_to_sphere(face, 0.3, -0.7).length() - 1.0).abs() < 1.0e-12);
+        }
+        // Arête commune aux faces +X et +Z
+        for b in [-1.0, -0.25, 0.5, 1.0] {
+            assert!(cube_to_sphere(0, -1.0, b).distance(cube_to_sphere(4, 1.0, b)) < 1.0e-12);
+        }
+        // Le centre d'une face est sa normale
+        assert!(cube_to_sphere(2, 0.0, 0.0).distance(DVec3::Y) < 1.0e-12);
+    }
+
+    #[test]
+    fn test_chunk_mesh() {
+        let planet = PlanetGeometry::with_terrain(1.0, PlanetClass::Terrestrial, TerrainParameters::from_seed(4));
+        let key = ChunkKey { face: 1, level: 3, x: 2, y: 5 };
+        let chunk = generate_chunk(&planet, key);
+        let n = CHUNK_RESOLUTION;
+        let grid = (n + 1) * (n + 1);
+        assert_eq!(chunk.position.len(), 3 * (grid + 4 * n) as usize);
+        assert_eq!(chunk.color.len(), chunk.position.len());
+        assert_eq!(chunk.normal.len(), chunk.position.len());
+        assert_eq!(chunk.indice.len(), (6 * n * n + 24 * n) as usize);
+        assert!(chunk.indice.iter().all(|&i| i < grid + 4 * n));
+        // Positions relatives au centre : bornées par la taille du morceau et le relief
+        let bound = (key.size(1.0) + max_relief(1.0, &planet.terrain)) as f32;
+        assert!(chunk.position.iter().all(|p| p.abs() <= bound));
+        // Chaque jupe descend sous le premier sommet du bord
+        let (top, bottom) = (position(&chunk, key, 1.0, 0), position(&chunk, key, 1.0, grid));
+        assert!(bottom.length() < top.length());
+        assert!(top.normalize().distance(bottom.normalize()) < 1.0e-6);
+    }
+
+    #[test]
+    fn test_neighbouring_chunks_share_edges() {
+        let planet = PlanetGeometry::with_terrain(1.0, PlanetClass::Terrestrial, TerrainParameters::from_seed(8));
+        let side = CHUNK_RESOLUTION + 1;
+        let left = ChunkKey { face: 4, level: 2, x: 1, y: 1 };
+        let right = ChunkKey { face: 4, level: 2, x: 2, y: 1 };
+        let (left_mesh, right_mesh) = (generate_chunk(&planet, left), generate_chunk(&planet, right));
+        for j in 0..side {
+            let a = position(&left_mesh, left, 1.0, j * side + CHUNK_RESOLUTION);
+            let b = position(&right_mesh, right, 1.0, j * side);
+            assert!(a.distance(b) < 1.0e-6, "{:?} {:?}", a, b);
+        }
+        // Un enfant reprend les sommets de son parent un sur deux
+        let parent = left;
+        let child = parent.children()[0];
+        for i in 0..=CHUNK_RESOLUTION / 2 {
+            assert!(parent.grid_direction(i, 0).distance(child.grid_direction(2 * i, 0)) < 1.0e-15);
+        }
+    }
+
+    #[test]
+    fn test_selection_refines_near_camera() {
+        let everything = |_: &ChunkKey| true;
+        // De loin, les six faces entières, moins celles cachées derrière la planète
+        let far = select_chunks(DVec3::new(0.0, 0.0, 20.0), 1.0, 0.05, everything);
+        assert!(far.complete && far.wanted.is_empty());
+        assert!(far.draw.iter().all(|key| key.level == 0));
+        assert!(!far.draw.iter().any(|key| key.face == 5));
+
+        // Quelques mètres au-dessus de la surface (rayon terrestre : 1 ≈ 6371 km)
+        let eye = DVec3::new(0.3, 0.2, 1.0).normalize() * (1.0 + 1.0e-6);
+        let near = select_chunks(eye, 1.0, 0.05, everything);
+        let deepest = near.draw.iter().max_by_key(|key| key.level).unwrap();
+        assert_eq!(deepest.level, MAX_CHUNK_LEVEL);
+        assert!(deepest.center_direction().distance(eye.normalize()) < 1.0e-4);
+        // Le détail décroît avec la distance et l'horizon limite le nombre de morceaux
+        let far_away = near.draw.iter().filter(|key| key.center(1.0).distance(eye) > 0.7).max_by_key(|key| key.level).unwrap();
+        assert!(far_away.level < 4);
+        assert!(near.draw.len() < 600, "{} morceaux", near.draw.len());
+    }
+
+    #[test]
+    fn test_selection_waits_for_children() {
+        let eye = DVec3::new(0.0, 0.0, 1.01);
+        let nothing = select_chunks(eye, 1.0, 0.05, |_| false);
+        assert!(!nothing.complete && nothing.draw.is_empty());
+        assert!(nothing.wanted.iter().all(|key| key.level == 0));
+
+        // Seules les faces sont prêtes : elles restent affichées et leurs enfants sont demandés
+        let roots = select_chunks(eye, 1.0, 0.05, |key| key.level == 0);
+        assert!(roots.complete);
+        assert!(roots.draw.iter().all(|key| key.level == 0));
+        let front = ChunkKey { face: 4, level: 0, x: 0, y: 0 };
+        assert!(roots.draw.contains(&front));
+        assert!(front.children().iter().all(|child| roots.wanted.contains(child)));
+        assert!(roots.wanted.iter().all(|key| key.level == 1));
+    }
+}
@@ -0,0 +1,267 @@
+use glam::Vec3;
+use crate::stellar_system::stellar_system::{PlanetClass, PlanetPhysicalProperties, RNG};
+
+// Paramètres de référence (planète de type terrestre)
+const REFERENCE_HEIGHT_AMPLITUDE: f32 = 0.05;
+const REFERENCE_TEMPERATURE: f64 = 255.0; // température d'équilibre de la Terre, en Kelvin
+// Au-delà, l'eau de surface s'évapore ; en deçà, les océans gèlent (en Kelvin)
+const OCEAN_BOIL_START: f64 = 330.0;
+const OCEAN_BOIL_END: f64 = 400.0;
+const OCEAN_FREEZE: f64 = 230.0;
+// Niveau de la mer d'un monde océan : seuls les plus hauts sommets émergent
+const OCEAN_WORLD_SEA_LEVEL: f32 = 0.6;
+// Niveau de la mer (en unités de height_amplitude) sous lequel aucun relief ne descend
+pub const NO_SEA_LEVEL: f32 = -10.0;
+// Le bruit de Perlin est périodique (256) : on reste loin de la période pour garder la précision f32
+const NOISE_OFFSET_RANGE: f64 = 16.0;
+
+/// Paramètres de génération du relief et des biomes d'une planète
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TerrainParameters {
+    /// Décalage appliqué aux coordonnées du bruit, propre à chaque planète
+    pub noise_offset: Vec3,
+    /// Niveau de la mer relatif au rayon, en unités de height_amplitude
+    pub sea_level: f32,
+    /// Amplitude du relief, en fraction du rayon
+    pub height_amplitude: f32,
+    pub continent_octaves: u8,
+    pub continent_persistence: f32,
+    pub continent_noise_scale: f32,
+    pub big_mountain_octaves: u8,
+    pub big_mountain_persistence: f32,
+    pub big_mountain_noise_scale: f32,
+    pub mountain_octaves: u8,
+    pub mountain_persistence: f32,
+    pub mountain_noise_scale: f32,
+    pub biome_octaves: u8,
+    pub biome_persistence: f32,
+    pub biome_noise_scale: f32,
+    /// Décalage de la température normalisée des biomes (négatif : calottes glaciaires étendues)
+    pub temperature_offset: f32,
+    /// Océans gelés (banquise au lieu d'eau liquide)
+    pub frozen_ocean: bool,
+}
+
+impl Default for TerrainParameters {
+    /// Planète de type terrestre, sans graine
+    fn default() -> Self {
+        Self {
+            noise_offset: Vec3::ZERO,
+            sea_level: -0.04,
+            height_amplitude: REFERENCE_HEIGHT_AMPLITUDE,
+            continent_octaves: 3,
+            continent_persistence: 0.5,
+            continent_noise_scale: 0.8,
+            big_mountain_octaves: 8,
+            big_mountain_persistence: 0.7,
+            big_mountain_noise_scale: 4.0,
+            mountain_octaves: 8,
+            mountain_persistence: 0.9,
+            mountain_noise_scale: 2.0,
+            biome_octaves: 3,
+            biome_persistence: 0.6,
+            biome_noise_scale: 5.0,
+            temperature_offset: 0.0,
+            frozen_ocean: false,
+        }
+    }
+}
+
+fn smoothstep(edge0: f64, edge1: f64, x: f64) -> f64 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+impl TerrainParameters {
+    /// Nombre de flottants échangés avec le worker de géométrie
+    pub const CONFIG_LEN: usize = 19;
+
+    /// Paramètres de référence dont le bruit et les échelles varient selon `seed`
+    pub fn from_seed(seed: u64) -> Self {
+        let mut rng = RNG::from_seed(seed);
+        let reference = Self::default();
+        let mut jitter = |value: f32, amount: f64| value * rng.f64(1.0 - amount, 1.0 + amount) as f32;
+        let continent_noise_scale = jitter(reference.continent_noise_scale, 0.3);
+        let big_mountain_noise_scale = jitter(reference.big_mountain_noise_scale, 0.3);
+        let mountain_noise_scale = jitter(reference.mountain_noise_scale, 0.3);
+        let biome_noise_scale = jitter(reference.biome_noise_scale, 0.3);
+        let continent_persistence = jitter(reference.continent_persistence, 0.2);
+        let big_mountain_persistence = jitter(reference.big_mountain_persistence, 0.1);
+        Self {
+            noise_offset: Vec3::new(
+                rng.f64(0.0, NOISE_OFFSET_RANGE) as f32,
+                rng.f64(0.0, NOISE_OFFSET_RANGE) as f32,
+                rng.f64(0.0, NOISE_OFFSET_RANGE) as f32,
+            ),
+            continent_noise_scale,
+            big_mountain_noise_scale,
+            mountain_noise_scale,
+            biome_noise_scale,
+            continent_persistence,
+            big_mountain_persistence,
+            ..reference
+        }
+    }
+
+    /// Relief et biomes déduits de la physique de la planète : les planètes chaudes perdent
+    /// leurs océans, les froides gèlent et étendent leurs calottes
+    pub fn from_physics(props: &PlanetPhysicalProperties) -> Self {
+        let mut terrain = Self::from_seed(props.terrain_seed);
+        let temperature = props.equilibrium_temperature;
+
+        // Relief plus marqué sous faible gravité de surface (g ∝ M / R²)
+        let surface_gravity = props.mass / (props.radius * props.radius);
+        terrain.height_amplitude = (REFERENCE_HEIGHT_AMPLITUDE as f64 / surface_gravity.sqrt()).clamp(0.01, 0.15) as f32;
+
+        // Eau de surface restante après évaporation
+        let liquid_water = props.water_fraction * (1.0 - smoothstep(OCEAN_BOIL_START, OCEAN_BOIL_END, temperature));
+        terrain.sea_level = if liquid_water > 0.01 {
+            (-0.6 + 0.8 * liquid_water) as f32
+        } else {
+            NO_SEA_LEVEL
+        };
+        match props.class {
+            PlanetClass::Ocean => terrain.sea_level = terrain.sea_level.max(OCEAN_WORLD_SEA_LEVEL),
+            PlanetClass::Desert => terrain.sea_level = NO_SEA_LEVEL,
+            _ => {}
+        }
+        // Les mondes glacés n'ont pas de mer liquide
+        terrain.frozen_ocean = temperature < OCEAN_FREEZE || props.class == PlanetClass::Ice;
+        terrain.temperature_offset = ((temperature - REFERENCE_TEMPERATURE) / 150.0).clamp(-1.0, 1.0) as f32;
+        terrain
+    }
+
+    /// Rayon absolu du niveau de la mer pour une planète de rayon `radius`
+    pub fn sea_radius(&self, radius: f32) -> f32 {
+        radius * (1.0 + self.sea_level * self.height_amplitude)
+    }
+
+    pub fn has_ocean(&self) -> bool {
+        self.sea_level > NO_SEA_LEVEL
+    }
+
+    /// Sérialisation pour le worker de géométrie
+    pub fn to_config(&self) -> [f32; Self::CONFIG_LEN] {
+        [
+            self.noise_offset.x,
+            self.noise_offset.y,
+            self.noise_offset.z,
+            self.sea_level,
+            self.height_amplitude,
+            self.continent_octaves as f32,
+            self.continent_persistence,
+            self.continent_noise_scale,
+            self.big_mountain_octaves as f32,
+            self.big_mountain_persistence,
+            self.big_mountain_noise_scale,
+            self.mountain_octaves as f32,
+            self.mountain_persistence,
+            self.mountain_noise_scale,
+            self.biome_octaves as f32,
+            self.biome_persistence,
+            self.biome_noise_scale,
+            self.temperature_offset,
+            if self.frozen_ocean { 1.0 } else { 0.0 },
+        ]
+    }
+
+    /// Lecture depuis le worker ; les paramètres de référence sont utilisés si `config` est trop court
+    pub fn from_config(config: &[f32]) -> Self {
+        if config.len() < Self::CONFIG_LEN {
+            return Self::default();
+        }
+        Self {
+            noise_offset: Vec3::new(config[0], config[1], config[2]),
+            sea_level: config[3],
+            height_amplitude: config[4],
+            continent_octaves: config[5] as u8,
+            continent_persistence: config[6],
+            continent_noise_scale: config[7],
+            big_mountain_octaves: config[8] as u8,
+            big_mountain_persistence: config[9],
+            big_mountain_noise_scale: config[10],
+            mountain_octaves: config[11] as u8,
+            mountain_persistence: config[12],
+            mountain_noise_scale: config[13],
+            biome_octaves: config[14] as u8,
+            biome_persistence: config[15],
+            biome_noise_scale: config[16],
+            temperature_offset: config[17],
+            frozen_ocean: config[18] > 0.5,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stellar_system::habitability::Habitability;
+
+    fn planet(equilibrium_temperature: f64, water_fraction: f64) -> PlanetPhysicalProperties {
+        PlanetPhysicalProperties {
+            mass: 1.0,
+            radius: 1.0,
+            albedo: 0.3,
+            water_fraction,
+            terrain_seed: 42,
+            insolation: 1.0,
+            equilibrium_temperature,
+            habitability: Habitability::Habitable,
+            class: PlanetClass::classify(1.0, 1.0, equilibrium_temperature, water_fraction),
+        }
+    }
+
+    #[test]
+    fn test_earth_like_matches_reference() {
+        let terrain = TerrainParameters::from_physics(&planet(255.0, 0.7));
+        assert!((terrain.sea_level - TerrainParameters::default().sea_level).abs() < 1.0e-6);
+        assert!((terrain.height_amplitude - REFERENCE_HEIGHT_AMPLITUDE).abs() < 1.0e-6);
+        assert_eq!(terrain.temperature_offset, 0.0);
+        assert!(terrain.has_ocean() && !terrain.frozen_ocean);
+    }
+
+    #[test]
+    fn test_hot_planet_loses_oceans() {
+        let terrain = TerrainParameters::from_physics(&planet(500.0, 0.9));
+        assert!(!terrain.has_ocean());
+        assert!(terrain.temperature_offset > 0.5);
+    }
+
+    #[test]
+    fn test_cold_planet_freezes() {
+        let terrain = TerrainParameters::from_physics(&planet(150.0, 0.5));
+        assert!(terrain.frozen_ocean);
+        assert!(terrain.temperature_offset < -0.5);
+    }
+
+    #[test]
+    fn test_ice_world_has_no_liquid_sea() {
+        let mut props = planet(240.0, 0.5);
+        assert!(!TerrainParameters::from_physics(&props).frozen_ocean);
+        props.class = PlanetClass::Ice;
+        assert!(TerrainParameters::from_physics(&props).frozen_ocean);
+    }
+
+    #[test]
+    fn test_ocean_and_desert_sea_levels() {
+        let ocean = planet(280.0, 0.95);
+        assert_eq!(ocean.class, PlanetClass::Ocean);
+        assert!(TerrainParameters::from_physics(&ocean).sea_level >= OCEAN_WORLD_SEA_LEVEL);
+        let desert = planet(255.0, 0.05);
+        assert_eq!(desert.class, PlanetClass::Desert);
+        assert!(!TerrainParameters::from_physics(&desert).has_ocean());
+    }
+
+    #[test]
+    fn test_sea_level_relative_to_radius() {
+        let terrain = TerrainParameters::default();
+        assert!((terrain.sea_radius(4.0) - 4.0 * terrain.sea_radius(1.0)).abs() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_config_round_trip() {
+        let terrain = TerrainParameters::from_physics(&planet(210.0, 0.4));
+        assert_eq!(TerrainParameters::from_config(&terrain.to_config()), terrain);
+        assert_eq!(TerrainParameters::from_config(&[]), TerrainParameters::default());
+    }
+}
@@ -0,0 +1,509 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::celestial_body::generation::{GeneratedGeometry, GeometryRequest};
+use crate::celestial_body::job_queue::JobId;
+use crate::celestial_body::planet::planet_geometry::PlanetVertex;
+use crate::celestial_body::planet::terrain_chunk::ChunkKey;
+use crate::celestial_body::planet::terrain_parameters::TerrainParameters;
+use crate::celestial_body::star::star_geometry::StarVertex;
+use crate::stellar_system::stellar_system::PlanetClass;
+
+/// Version du protocole, en tête de chaque message : un worker d'une autre version
+/// (script resté en cache) est détecté au lieu de produire des géométries incohérentes
+pub const PROTOCOL_VERSION: u8 = 2;
+
+// Étiquettes des messages et des types de corps
+const TAG_GENERATE: u8 = 0;
+const TAG_CANCEL: u8 = 1;
+const TAG_READY: u8 = 0;
+const TAG_GENERATED: u8 = 1;
+const TAG_PROGRESS: u8 = 2;
+const TAG_FAILED: u8 = 3;
+const TAG_CANCELLED: u8 = 4;
+const BODY_PLANET: u8 = 0;
+const BODY_STAR: u8 = 1;
+const BODY_CHUNK: u8 = 2;
+
+/// Message du thread principal vers un worker de génération
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WorkerRequest {
+    Generate { job: JobId, request: GeometryRequest },
+    /// Abandonne `job` s'il n'a pas encore commencé
+    Cancel { job: JobId },
+}
+
+/// Message d'un worker de génération vers le thread principal
+pub enum WorkerResponse {
+    /// Le worker écoute et peut recevoir des travaux
+    Ready,
+    Generated { job: JobId, geometry: GeneratedGeometry },
+    /// Avancement de `job`, entre 0 et 1
+    Progress { job: JobId, fraction: f32 },
+    /// `job` n'a pas pu être généré : demande illisible ou invalide, panique
+    Failed { job: JobId, error: String },
+    /// `job` a été abandonné avant d'avoir commencé
+    Cancelled { job: JobId },
+}
+
+/// Message qui ne peut pas être décodé
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtocolError {
+    Version(u8),
+    UnknownMessage(u8),
+    UnknownBody(u8),
+    /// Morceau de terrain hors du quadtree : face inexistante, niveau trop profond ou case hors de la face
+    InvalidChunk(ChunkKey),
+    /// Message tronqué ou suivi d'octets en trop
+    Malformed,
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtocolError::Version(version) => write!(f, "protocole version {} au lieu de {}", version, PROTOCOL_VERSION),
+            ProtocolError::UnknownMessage(tag) => write!(f, "type de message inconnu {}", tag),
+            ProtocolError::UnknownBody(body) => write!(f, "type de corps inconnu {}", body),
+            ProtocolError::InvalidChunk(chunk) => write!(f, "morceau de terrain invalide {:?}", chunk),
+            ProtocolError::Malformed => write!(f, "message mal formé"),
+        }
+    }
+}
+
+impl WorkerRequest {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoder = Encoder::default();
+        match self {
+            WorkerRequest::Generate { job, request } => {
+                encoder.header(TAG_GENERATE);
+                encoder.u64(*job);
+                match request {
+                    GeometryRequest::Planet { radius, class, terrain, lod } => {
+                        encoder.u8(BODY_PLANET);
+                        encoder.u8(*lod);
+                        encoder.f32(*radius);
+                        encoder.u8(*class as u8);
+                        encoder.f32s(&terrain.to_config());
+                    }
+                    GeometryRequest::Star { radius, lod } => {
+                        encoder.u8(BODY_STAR);
+                        encoder.u8(*lod);
+                        encoder.f32(*radius);
+                    }
+                    GeometryRequest::Chunk { radius, class, terrain, chunk } => {
+                        encoder.u8(BODY_CHUNK);
+                        encoder.u8(chunk.level);
+                        encoder.f32(*radius);
+                        encoder.u8(*class as u8);
+                        encoder.f32s(&terrain.to_config());
+                        encoder.u8(chunk.face);
+                        encoder.u32(chunk.x);
+                        encoder.u32(chunk.y);
+                    }
+                }
+            }
+            WorkerRequest::Cancel { job } => {
+                encoder.header(TAG_CANCEL);
+                encoder.u64(*job);
+            }
+        }
+        encoder.bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, ProtocolError> {
+        let mut decoder = Decoder { bytes, offset: 0 };
+        let request = match decoder.header()? {
+            TAG_GENERATE => {
+                let job = decoder.u64()?;
+                let body = decoder.u8()?;
+                let lod = decoder.u8()?;
+                let radius = decoder.f32()?;
+                let request = match body {
+                    BODY_PLANET => {
+                        let class = PlanetClass::from_u8(decoder.u8()?);
+                        let terrain = TerrainParameters::from_config(&decoder.f32s()?);
+                        GeometryRequest::Planet { radius, class, terrain, lod }
+                    }
+                    BODY_STAR => GeometryRequest::Star { radius, lod },
+                    BODY_CHUNK => {
+                        let class = PlanetClass::from_u8(decoder.u8()?);
+                        let terrain = TerrainParameters::from_config(&decoder.f32s()?);
+                        let chunk = ChunkKey { face: decoder.u8()?, level: lod, x: decoder.u32()?, y: decoder.u32()? };
+                        if !chunk.is_valid() {
+                            return Err(ProtocolError::InvalidChunk(chunk));
+                        }
+                        GeometryRequest::Chunk { radius, class, terrain, chunk }
+                    }
+                    _ => return Err(ProtocolError::UnknownBody(body)),
+                };
+                WorkerRequest::Generate { job, request }
+            }
+            TAG_CANCEL => WorkerRequest::Cancel { job: decoder.u64()? },
+            tag => return Err(ProtocolError::UnknownMessage(tag)),
+        };
+        decoder.finish(request)
+    }
+
+    /// Travail visé par un message, lisible même si la suite du message ne l'est pas :
+    /// l'en-tête (version, type, travail) est le même pour toutes les versions
+    pub fn job_of(bytes: &[u8]) -> Option<JobId> {
+        let mut decoder = Decoder { bytes, offset: 2 };
+        decoder.u64().ok()
+    }
+}
+
+impl WorkerResponse {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoder = Encoder::default();
+        match self {
+            WorkerResponse::Ready => encoder.header(TAG_READY),
+            WorkerResponse::Generated { job, geometry } => {
+                encoder.header(TAG_GENERATED);
+                encoder.u64(*job);
+                match geometry {
+                    GeneratedGeometry::Planet(planet) | GeneratedGeometry::Chunk(planet) => {
+                        encoder.u8(if matches!(geometry, GeneratedGeometry::Planet(_)) { BODY_PLANET } else { BODY_CHUNK });
+                        encoder.f32s(&planet.position);
+                        encoder.f32s(&planet.color);
+                        encoder.f32s(&planet.normal);
+                        encoder.u32s(&planet.indice);
+                    }
+                    GeneratedGeometry::Star(star) => {
+                        encoder.u8(BODY_STAR);
+                        encoder.f32s(&star.position);
+                        encoder.f32s(&star.color);
+                        encoder.u32s(&star.indice);
+                    }
+                }
+            }
+            WorkerResponse::Progress { job, fraction } => {
+                encoder.header(TAG_PROGRESS);
+                encoder.u64(*job);
+                encoder.f32(*fraction);
+            }
+            WorkerResponse::Failed { job, error } => {
+                encoder.header(TAG_FAILED);
+                encoder.u64(*job);
+                encoder.u32(error.len() as u32);
+                encoder.bytes.extend_from_slice(error.as_bytes());
+            }
+            WorkerResponse::Cancelled { job } => {
+                encoder.header(TAG_CANCELLED);
+                encoder.u64(*job);
+            }
+        }
+        encoder.bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, ProtocolError> {
+        let mut decoder = Decoder { bytes, offset: 0 };
+        let response = match decoder.header()? {
+            TAG_READY => WorkerResponse::Ready,
+            TAG_GENERATED => {
+                let job = decoder.u64()?;
+                let geometry = match decoder.u8()? {
+                    body @ (BODY_PLANET | BODY_CHUNK) => {
+                        let planet = PlanetVertex {
+                            position: decoder.f32s()?,
+                            color: decoder.f32s()?,
+                            normal: decoder.f32s()?,
+                            indice: decoder.u32s()?,
+                        };
+                        if body == BODY_PLANET { GeneratedGeometry::Planet(planet) } else { GeneratedGeometry::Chunk(planet) }
+                    }
+                    BODY_STAR => GeneratedGeometry::Star(StarVertex {
+                        position: decoder.f32s()?,
+                        color: decoder.f32s()?,
+                        indice: decoder.u32s()?,
+                    }),
+                    body => return Err(ProtocolError::UnknownBody(body)),
+                };
+                WorkerResponse::Generated { job, geometry }
+            }
+            TAG_PROGRESS => WorkerResponse::Progress { job: decoder.u64()?, fraction: decoder.f32()? },
+            TAG_FAILED => {
+                let job = decoder.u64()?;
+                let len = decoder.u32()? as usize;
+                let error = String::from_utf8_lossy(decoder.take_slice(len)?).into_owned();
+                WorkerResponse::Failed { job, error }
+            }
+            TAG_CANCELLED => WorkerResponse::Cancelled { job: decoder.u64()? },
+            tag => return Err(ProtocolError::UnknownMessage(tag)),
+        };
+        decoder.finish(response)
+    }
+}
+
+/// Génère `request` pour `job` en transmettant l'avancement puis le résultat à `report` ;
+/// une demande invalide ou une panique pendant la génération donnent Failed
+pub fn run_job(job: JobId, request: GeometryRequest, report: &mut dyn FnMut(WorkerResponse)) {
+    if let Err(error) = request.validate() {
+        report(WorkerResponse::Failed { job, error });
+        return;
+    }
+    let generated = panic::catch_unwind(AssertUnwindSafe(|| {
+        request.generate_with_progress(&mut |fraction| report(WorkerResponse::Progress { job, fraction }))
+    }));
+    report(match generated {
+        Ok(geometry) => WorkerResponse::Generated { job, geometry },
+        Err(payload) => {
+            let error = payload.downcast_ref::<&str>().map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "panique pendant la génération".to_string());
+            WorkerResponse::Failed { job, error }
+        }
+    });
+}
+
+/// Côté worker : travaux reçus et pas encore commencés. Le worker n'en exécute un qu'après
+/// avoir rendu la main, pour qu'une annulation déjà arrivée puisse être prise en compte
+#[derive(Default)]
+pub struct WorkerInbox {
+    pending: VecDeque<(JobId, GeometryRequest)>,
+}
+
+impl WorkerInbox {
+    /// Prend en compte un message ; renvoie la réponse immédiate éventuelle
+    pub fn receive(&mut self, request: WorkerRequest) -> Option<WorkerResponse> {
+        match request {
+            WorkerRequest::Generate { job, request } => {
+                self.pending.push_back((job, request));
+                None
+            }
+            WorkerRequest::Cancel { job } => {
+                // Un travail déjà terminé a envoyé son résultat : rien à répondre
+                let index = self.pending.iter().position(|(pending, _)| *pending == job)?;
+                self.pending.remove(index);
+                Some(WorkerResponse::Cancelled { job })
+            }
+        }
+    }
+
+    /// Exécute le plus ancien travail en attente ; false s'il n'y en avait aucun
+    pub fn run_next(&mut self, report: &mut dyn FnMut(WorkerResponse)) -> bool {
+        let Some((job, request)) = self.pending.pop_front() else { return false };
+        run_job(job, request, report);
+        true
+    }
+}
+
+// Sérialisation petit-boutiste ; les tableaux sont précédés de leur longueur
+#[derive(Default)]
+struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    fn header(&mut self, tag: u8) {
+        self.u8(PROTOCOL_VERSION);
+        self.u8(tag);
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn f32s(&mut self, values: &[f32]) {
+        self.u32(values.len() as u32);
+        self.bytes.reserve(4 * values.len());
+        values.iter().for_each(|value| self.f32(*value));
+    }
+
+    fn u32s(&mut self, values: &[u32]) {
+        self.u32(values.len() as u32);
+        self.bytes.reserve(4 * values.len());
+        values.iter().for_each(|value| self.u32(*value));
+    }
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl Decoder<'_> {
+    fn take_slice(&mut self, len: usize) -> Result<&[u8], ProtocolError> {
+        let end = self.offset.checked_add(len).ok_or(ProtocolError::Malformed)?;
+        let chunk = self.bytes.get(self.offset..end).ok_or(ProtocolError::Malformed)?;
+        self.offset = end;
+        Ok(chunk)
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], ProtocolError> {
+        self.take_slice(N)?.try_into().map_err(|_| ProtocolError::Malformed)
+    }
+
+    /// Vérifie la version et renvoie le type du message
+    fn header(&mut self) -> Result<u8, ProtocolError> {
+        let version = self.u8()?;
+        if version != PROTOCOL_VERSION {
+            return Err(ProtocolError::Version(version));
+        }
+        self.u8()
+    }
+
+    fn u8(&mut self) -> Result<u8, ProtocolError> {
+        self.take::<1>().map(|[value]| value)
+    }
+
+    fn u32(&mut self) -> Result<u32, ProtocolError> {
+        self.take().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64, ProtocolError> {
+        self.take().map(u64::from_le_bytes)
+    }
+
+    fn f32(&mut self) -> Result<f32, ProtocolError> {
+        self.take().map(f32::from_le_bytes)
+    }
+
+    fn array(&mut self) -> Result<&[u8], ProtocolError> {
+        let len = self.u32()? as usize;
+        self.take_slice(4 * len)
+    }
+
+    fn f32s(&mut self) -> Result<Vec<f32>, ProtocolError> {
+        Ok(self.array()?.chunks_exact(4).map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]])).collect())
+    }
+
+    fn u32s(&mut self) -> Result<Vec<u32>, ProtocolError> {
+        Ok(self.array()?.chunks_exact(4).map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]])).collect())
+    }
+
+    /// `message` s'il occupe exactement tous les octets
+    fn finish<T>(&self, message: T) -> Result<T, ProtocolError> {
+        if self.offset == self.bytes.len() { Ok(message) } else { Err(ProtocolError::Malformed) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn star(lod: u8) -> GeometryRequest {
+        GeometryRequest::Star { radius: 1.0, lod }
+    }
+
+    #[test]
+    fn test_request_round_trip() {
+        let requests = [
+            WorkerRequest::Generate {
+                job: 42,
+                request: GeometryRequest::Planet { radius: 1.5, class: PlanetClass::Lava, terrain: TerrainParameters::from_seed(9), lod: 4 },
+            },
+            WorkerRequest::Generate { job: u64::MAX, request: star(2) },
+            WorkerRequest::Generate {
+                job: 3,
+                request: GeometryRequest::Planet { radius: 1.0, class: PlanetClass::Ocean, terrain: TerrainParameters::from_seed(2), lod: 3 },
+            },
+            WorkerRequest::Generate {
+                job: 4,
+                request: GeometryRequest::Planet { radius: 0.7, class: PlanetClass::Desert, terrain: TerrainParameters::from_seed(3), lod: 3 },
+            },
+            WorkerRequest::Generate {
+                job: 8,
+                request: GeometryRequest::Chunk {
+                    radius: 0.8,
+                    class: PlanetClass::Ice,
+                    terrain: TerrainParameters::from_seed(5),
+                    chunk: ChunkKey { face: 5, level: 17, x: 100_000, y: 3 },
+                },
+            },
+            WorkerRequest::Cancel { job: 5 },
+        ];
+        for request in requests {
+            let bytes = request.encode();
+            assert_eq!(WorkerRequest::decode(&bytes), Ok(request));
+            let (WorkerRequest::Generate { job, .. } | WorkerRequest::Cancel { job }) = request;
+            assert_eq!(WorkerRequest::job_of(&bytes), Some(job));
+        }
+    }
+
+    #[test]
+    fn test_response_round_trip() {
+        let request = GeometryRequest::Planet { radius: 1.0, class: PlanetClass::Terrestrial, terrain: TerrainParameters::from_seed(1), lod: 1 };
+        let mut responses = Vec::new();
+        run_job(7, request, &mut |response| responses.push(response));
+        let Some(WorkerResponse::Generated { job: 7, geometry: GeneratedGeometry::Planet(planet) }) = responses.pop() else {
+            panic!("réponse planète attendue");
+        };
+        let encoded = WorkerResponse::Generated { job: 7, geometry: GeneratedGeometry::Planet(planet.clone()) }.encode();
+        let Ok(WorkerResponse::Generated { job: 7, geometry: GeneratedGeometry::Planet(decoded) }) = WorkerResponse::decode(&encoded) else {
+            panic!("réponse planète attendue");
+        };
+        assert_eq!(decoded.position, planet.position);
+        assert_eq!(decoded.color, planet.color);
+        assert_eq!(decoded.normal, planet.normal);
+        assert_eq!(decoded.indice, planet.indice);
+        let chunk = WorkerResponse::Generated { job: 7, geometry: GeneratedGeometry::Chunk(planet) }.encode();
+        assert!(matches!(WorkerResponse::decode(&chunk), Ok(WorkerResponse::Generated { job: 7, geometry: GeneratedGeometry::Chunk(_) })));
+
+        let failed = WorkerResponse::Failed { job: 3, error: "rayon invalide".to_string() }.encode();
+        assert!(matches!(WorkerResponse::decode(&failed), Ok(WorkerResponse::Failed { job: 3, error }) if error == "rayon invalide"));
+        let progress = WorkerResponse::Progress { job: 3, fraction: 0.25 }.encode();
+        assert!(matches!(WorkerResponse::decode(&progress), Ok(WorkerResponse::Progress { job: 3, fraction }) if fraction == 0.25));
+        assert!(matches!(WorkerResponse::decode(&WorkerResponse::Cancelled { job: 3 }.encode()), Ok(WorkerResponse::Cancelled { job: 3 })));
+        assert!(matches!(WorkerResponse::decode(&WorkerResponse::Ready.encode()), Ok(WorkerResponse::Ready)));
+    }
+
+    #[test]
+    fn test_malformed_messages() {
+        let bytes = WorkerRequest::Generate { job: 1, request: star(0) }.encode();
+        assert_eq!(WorkerRequest::decode(&bytes[..bytes.len() - 1]), Err(ProtocolError::Malformed));
+        assert_eq!(WorkerRequest::decode(&[bytes.as_slice(), &[0]].concat()), Err(ProtocolError::Malformed));
+        let mut unknown_body = bytes.clone();
+        unknown_body[10] = 7;
+        assert_eq!(WorkerRequest::decode(&unknown_body), Err(ProtocolError::UnknownBody(7)));
+        // Le travail reste identifiable pour lui répondre par une erreur
+        assert_eq!(WorkerRequest::job_of(&unknown_body), Some(1));
+        let mut other_version = bytes.clone();
+        other_version[0] = PROTOCOL_VERSION + 1;
+        assert_eq!(WorkerRequest::decode(&other_version), Err(ProtocolError::Version(PROTOCOL_VERSION + 1)));
+        assert_eq!(WorkerRequest::decode(&[PROTOCOL_VERSION, 9]), Err(ProtocolError::UnknownMessage(9)));
+        // Niveau au-delà du quadtree : refusé avant tout calcul de grille
+        let chunk = ChunkKey { face: 1, level: 64, x: 0, y: 0 };
+        let request = GeometryRequest::Chunk { radius: 1.0, class: PlanetClass::Terrestrial, terrain: TerrainParameters::from_seed(1), chunk };
+        let bytes = WorkerRequest::Generate { job: 2, request }.encode();
+        assert_eq!(WorkerRequest::decode(&bytes), Err(ProtocolError::InvalidChunk(chunk)));
+        assert_eq!(WorkerRequest::job_of(&bytes), Some(2));
+        assert!(WorkerResponse::decode(&[]).is_err());
+        assert!(WorkerResponse::decode(&[PROTOCOL_VERSION, TAG_GENERATED, 1, 2]).is_err());
+        assert!(WorkerResponse::decode(&[PROTOCOL_VERSION, TAG_FAILED, 0, 0, 0, 0, 0, 0, 0, 0, 255, 255, 255, 255]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_request_fails() {
+        let mut responses = Vec::new();
+        run_job(4, GeometryRequest::Star { radius: 1.0, lod: 200 }, &mut |response| responses.push(response));
+        assert!(matches!(&responses[..], [WorkerResponse::Failed { job: 4, .. }]));
+    }
+
+    #[test]
+    fn test_worker_inbox() {
+        let mut inbox = WorkerInbox::default();
+        assert!(inbox.receive(WorkerRequest::Generate { job: 1, request: star(1) }).is_none());
+        assert!(inbox.receive(WorkerRequest::Generate { job: 2, request: star(1) }).is_none());
+        // Annulation d'un travail pas encore commencé, puis d'un travail inconnu
+        assert!(matches!(inbox.receive(WorkerRequest::Cancel { job: 1 }), Some(WorkerResponse::Cancelled { job: 1 })));
+        assert!(inbox.receive(WorkerRequest::Cancel { job: 1 }).is_none());
+
+        let mut responses = Vec::new();
+        assert!(inbox.run_next(&mut |response| responses.push(response)));
+        assert!(!inbox.run_next(&mut |response| responses.push(response)));
+        assert!(matches!(&responses[..], [WorkerResponse::Generated { job: 2, .. }]));
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::celestial_body::load_priority::{select_evictions, LoadPriority, Resident};
+
+/// Bilan de la mémoire GPU des maillages, pour l'affichage de débogage
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ResidencyStats {
+    pub resident_bytes: u64,
+    pub resident_meshes: usize,
+    pub budget: u64,
+    /// Maillages envoyés au GPU depuis le début de l'image
+    pub uploads: u32,
+    /// Maillages libérés depuis le début de l'image
+    pub evictions: u32,
+}
+
+struct ResidentMesh {
+    bytes: u64,
+    // Dernière image où le maillage a été vu, et importance de son corps à ce moment
+    last_visible: u64,
+    priority: LoadPriority,
+}
+
+/// Comptabilité des maillages en mémoire GPU, par corps et par LOD ou par morceau de terrain : le budget est tenu en
+/// libérant d'abord les maillages vus il y a le plus longtemps, puis les moins importants.
+/// Ne touche pas au GPU : l'appelant libère les tampons désignés
+pub struct Residency<K> {
+    budget: u64,
+    frame: u64,
+    meshes: HashMap<K, ResidentMesh>,
+    resident_bytes: u64,
+    uploads: u32,
+    evictions: u32,
+}
+
+impl<K: Copy + Eq + Hash> Residency<K> {
+    pub fn new(budget: u64) -> Self {
+        Self {
+            budget,
+            frame: 0,
+            meshes: HashMap::new(),
+            resident_bytes: 0,
+            uploads: 0,
+            evictions: 0,
+        }
+    }
+
+    pub fn set_budget(&mut self, budget: u64) {
+        self.budget = budget;
+    }
+
+    /// Nouvelle image : les compteurs d'envois et de libérations repartent de zéro
+    pub fn begin_frame(&mut self) {
+        self.frame += 1;
+        self.uploads = 0;
+        self.evictions = 0;
+    }
+
+    /// Maillage `key` envoyé au GPU ; il remplace le précédent de même clé
+    pub fn uploaded(&mut self, key: K, bytes: u64, priority: LoadPriority) {
+        let mesh = ResidentMesh { bytes, last_visible: self.frame, priority };
+        if let Some(previous) = self.meshes.insert(key, mesh) {
+            self.resident_bytes -= previous.bytes;
+        }
+        self.resident_bytes += bytes;
+        self.uploads += 1;
+    }
+
+    /// Maillage `key` affiché pendant l'image
+    pub fn seen(&mut self, key: K, priority: LoadPriority) {
+        if let Some(mesh) = self.meshes.get_mut(&key) {
+            mesh.last_visible = self.frame;
+            mesh.priority = priority;
+        }
+    }
+
+    /// Maillages à libérer pour revenir sous le budget, retirés de la comptabilité ;
+    /// ceux pour lesquels `in_use` est vrai restent, quitte à dépasser le budget
+    pub fn evict(&mut self, in_use: impl Fn(&K) -> bool) -> Vec<K> {
+        if self.resident_bytes <= self.budget {
+            return Vec::new();
+        }
+        let residents: Vec<Resident<K>> = self.meshes.iter().map(|(key, mesh)| Resident {
+            key: *key,
+            bytes: mesh.bytes,
+            last_visible: mesh.last_visible,
+            priority: mesh.priority,
+            in_use: in_use(key),
+        }).collect();
+        let evicted = select_evictions(&residents, self.budget);
+        for key in &evicted {
+            let mesh = self.meshes.remove(key).expect("candidat résident");
+            self.resident_bytes -= mesh.bytes;
+            self.evictions += 1;
+        }
+        evicted
+    }
+
+    pub fn stats(&self) -> ResidencyStats {
+        ResidencyStats {
+            resident_bytes: self.resident_bytes,
+            resident_meshes: self.meshes.len(),
+            budget: self.budget,
+            uploads: self.uploads,
+            evictions: self.evictions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accounting_per_frame() {
+        let mut residency = Residency::new(1000);
+        residency.begin_frame();
+        residency.uploaded((0, 3), 100, LoadPriority::LOWEST);
+        residency.uploaded((1, 3), 200, LoadPriority::LOWEST);
+        // Même corps et même LOD : le maillage est remplacé, pas ajouté
+        residency.uploaded((1, 3), 250, LoadPriority::LOWEST);
+        assert_eq!(residency.stats(), ResidencyStats { resident_bytes: 350, resident_meshes: 2, budget: 1000, uploads: 3, evictions: 0 });
+        residency.begin_frame();
+        assert_eq!(residency.stats().uploads, 0);
+        assert_eq!(residency.stats().resident_bytes, 350);
+    }
+
+    #[test]
+    fn test_least_recently_visible_evicted_first() {
+        let mut residency = Residency::new(1000);
+        residency.begin_frame();
+        for body in 0..5 {
+            residency.uploaded((body, 2), 100, LoadPriority::new(50.0, 10.0));
+        }
+        for frame in 0..3 {
+            residency.begin_frame();
+            // Le corps 0 est affiché à chaque image, le corps 1 seulement à la première
+            residency.seen((0, 2), LoadPriority::new(50.0, 10.0));
+            if frame == 0 {
+                residency.seen((1, 2), LoadPriority::new(50.0, 10.0));
+            }
+            residency.seen((2, 2), LoadPriority::new(800.0, 1.0));
+            residency.seen((3, 2), LoadPriority::new(20.0, 40.0));
+        }
+        assert!(residency.evict(|_| false).is_empty());
+
+        residency.set_budget(250);
+        let in_use = |key: &(u32, usize)| *key == (0, 2);
+        // Jamais vu, puis vu il y a longtemps, puis le moins important parmi ceux vus à l'instant
+        assert_eq!(residency.evict(in_use), vec![(4, 2), (1, 2), (3, 2)]);
+        assert_eq!(residency.stats().evictions, 3);
+        assert_eq!(residency.stats().resident_bytes, 200);
+
+        // Les maillages affichés restent, quitte à dépasser le budget
+        residency.set_budget(0);
+        assert_eq!(residency.evict(in_use), vec![(2, 2)]);
+        assert_eq!(residency.stats().resident_bytes, 100);
+    }
+}
@@ -0,0 +1,63 @@
+use crate::celestial_body::ring::ring_vertex::Vertex;
+use crate::celestial_body::planet::planet_instance::InstanceRaw;
+use crate::celestial_body::planet::render_pipeline::depth_stencil_state;
+
+pub fn ring_render_pipeline(
+    device: &wgpu::Device,
+    bind_group_layouts: &[&wgpu::BindGroupLayout],
+    config: &wgpu::SurfaceConfiguration
+) -> wgpu::RenderPipeline
+{
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Ring Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("ring.wgsl").into()),
+    });
+
+    let render_pipeline_layout =
+    device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Ring Render Pipeline Layout"),
+        bind_group_layouts,
+        push_constant_ranges: &[],
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Ring Render Pipeline"),
+        layout: Some(&render_pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: Some("vs_main"),
+            buffers: &[Vertex::desc(), InstanceRaw::desc()],
+            compilation_options: Default::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format: config.format,
+                // Anneaux semi-transparents
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: Default::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            // Les anneaux sont visibles des deux côtés
+            cull_mode: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        // Masqués par les corps plus proches, sans masquer eux-mêmes les autres anneaux
+        depth_stencil: Some(depth_stencil_state(wgpu::CompareFunction::Greater, false)),
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+        cache: None,
+    })
+}
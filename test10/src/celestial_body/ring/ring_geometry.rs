@@ -0,0 +1,137 @@
+use std::f32::consts::PI;
+use glam::{Quat, Vec3};
+use wgpu::util::DeviceExt;
+
+use crate::celestial_body::planet::planet_instance::PlanetInstance;
+use crate::celestial_body::ring::ring_vertex::Vertex;
+use crate::stellar_system::satellites::{RingSystem, RING_PROFILE_SAMPLES};
+
+const RING_ANGULAR_SEGMENTS: u32 = 128;
+// Teinte des particules de glace, de l'intérieur vers l'extérieur
+const RING_INNER_COLOR: Vec3 = Vec3::new(0.76, 0.70, 0.60);
+const RING_OUTER_COLOR: Vec3 = Vec3::new(0.92, 0.90, 0.86);
+
+/// Maillage d'un anneau plat dans le plan équatorial (XZ) de la planète
+pub struct RingGeometry {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+}
+
+impl RingGeometry {
+    /// `planet_radius` en unités de scène ; l'opacité suit le profil de densité des anneaux
+    pub fn new(planet_radius: f32, rings: &RingSystem) -> Self {
+        let radial = RING_PROFILE_SAMPLES as u32;
+        let mut vertices = Vec::with_capacity((radial * (RING_ANGULAR_SEGMENTS + 1)) as usize);
+        let mut indices = Vec::with_capacity((6 * (radial - 1) * RING_ANGULAR_SEGMENTS) as usize);
+
+        for i in 0..radial {
+            let t = i as f32 / (radial - 1) as f32;
+            let radius = rings.inner_radius as f32 + t * (rings.outer_radius - rings.inner_radius) as f32;
+            let color = RING_INNER_COLOR.lerp(RING_OUTER_COLOR, t);
+            let alpha = rings.density_profile[i as usize];
+            for j in 0..=RING_ANGULAR_SEGMENTS {
+                let angle = 2.0 * PI * j as f32 / RING_ANGULAR_SEGMENTS as f32;
+                let (sin, cos) = angle.sin_cos();
+                vertices.push(Vertex {
+                    position: [planet_radius * radius * cos, 0.0, planet_radius * radius * sin],
+                    color: [color.x, color.y, color.z, alpha],
+                });
+            }
+        }
+
+        let row = RING_ANGULAR_SEGMENTS + 1;
+        for i in 0..radial - 1 {
+            for j in 0..RING_ANGULAR_SEGMENTS {
+                let a = i * row + j;
+                let b = a + row;
+                indices.extend_from_slice(&[a, b, a + 1, a + 1, b, b + 1]);
+            }
+        }
+
+        Self { vertices, indices }
+    }
+}
+
+/// Anneaux d'une planète prêts au rendu ; la géométrie est générée immédiatement (pas de LOD)
+pub struct RingHandle {
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub instance_buffer: wgpu::Buffer,
+    pub num_indices: u32,
+    pub instance: PlanetInstance,
+    /// Identifiant du `CelestialBodyHandle` de la planète hôte
+    pub host: u32,
+}
+
+impl RingHandle {
+    pub fn new(device: &wgpu::Device, planet_radius: f32, rings: &RingSystem, position: Vec3, host: u32) -> Self {
+        let geometry = RingGeometry::new(planet_radius, rings);
+        let instance = PlanetInstance::new(position, Quat::from_rotation_x(rings.tilt as f32));
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Ring Vertex Buffer"),
+            contents: bytemuck::cast_slice(&geometry.vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Ring Index Buffer"),
+            contents: bytemuck::cast_slice(&geometry.indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Ring Instance Buffer"),
+            contents: bytemuck::cast_slice(&[instance.to_raw()]),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        });
+        Self {
+            vertex_buffer,
+            index_buffer,
+            instance_buffer,
+            num_indices: geometry.indices.len() as u32,
+            instance,
+            host,
+        }
+    }
+
+    /// Suit la planète hôte
+    pub fn set_position(&mut self, queue: &wgpu::Queue, position: Vec3) {
+        self.instance.set_position(position);
+        queue.write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(&[self.instance.to_raw()]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rings() -> RingSystem {
+        RingSystem {
+            inner_radius: 1.2,
+            outer_radius: 2.3,
+            tilt: 0.4,
+            density_profile: (0..RING_PROFILE_SAMPLES).map(|i| i as f32 / RING_PROFILE_SAMPLES as f32).collect(),
+        }
+    }
+
+    #[test]
+    fn test_ring_mesh_bounds() {
+        let geometry = RingGeometry::new(2.0, &rings());
+        assert_eq!(geometry.vertices.len(), RING_PROFILE_SAMPLES * (RING_ANGULAR_SEGMENTS as usize + 1));
+        assert!(geometry.indices.iter().all(|&i| (i as usize) < geometry.vertices.len()));
+        for vertex in &geometry.vertices {
+            let [x, y, z] = vertex.position;
+            let r = (x * x + z * z).sqrt();
+            assert_eq!(y, 0.0);
+            assert!((2.4 - 1.0e-4..=4.6 + 1.0e-4).contains(&r));
+        }
+    }
+
+    #[test]
+    fn test_ring_opacity_follows_density() {
+        let rings = rings();
+        let geometry = RingGeometry::new(1.0, &rings);
+        let row = RING_ANGULAR_SEGMENTS as usize + 1;
+        for (i, density) in rings.density_profile.iter().enumerate() {
+            assert_eq!(geometry.vertices[i * row].color[3], *density);
+        }
+    }
+}
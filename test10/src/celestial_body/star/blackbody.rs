@@ -0,0 +1,82 @@
+use glam::Vec3;
+
+use crate::stellar_system::stellar_model::SOLAR_TEMPERATURE;
+
+// Domaine de validité de l'ajustement du lieu de Planck de Kim et al. (2002), en Kelvin
+const PLANCKIAN_MIN_TEMPERATURE: f64 = 1667.0;
+const PLANCKIAN_MAX_TEMPERATURE: f64 = 25000.0;
+// Bornes de l'éclat relatif d'une étoile (naines M ternes, étoiles O éblouissantes)
+const MIN_INTENSITY: f32 = 0.4;
+const MAX_INTENSITY: f32 = 4.0;
+
+/// Chromaticité CIE 1931 (x, y) d'un corps noir à `temperature` (K)
+pub fn planckian_chromaticity(temperature: f64) -> (f64, f64) {
+    let t = temperature.clamp(PLANCKIAN_MIN_TEMPERATURE, PLANCKIAN_MAX_TEMPERATURE);
+    let (t2, t3) = (t * t, t * t * t);
+    let x = if t <= 4000.0 {
+        -0.2661239e9 / t3 - 0.2343589e6 / t2 + 0.8776956e3 / t + 0.179910
+    } else {
+        -3.0258469e9 / t3 + 2.1070379e6 / t2 + 0.2226347e3 / t + 0.240390
+    };
+    let (x2, x3) = (x * x, x * x * x);
+    let y = if t <= 2222.0 {
+        -1.1063814 * x3 - 1.34811020 * x2 + 2.18555832 * x - 0.20219683
+    } else if t <= 4000.0 {
+        -0.9549476 * x3 - 1.37418593 * x2 + 2.09137015 * x - 0.16748867
+    } else {
+        3.0817580 * x3 - 5.87338670 * x2 + 3.75112997 * x - 0.37001483
+    };
+    (x, y)
+}
+
+/// Couleur sRGB linéaire d'un corps noir, normalisée pour que la composante la plus forte vaille 1
+pub fn blackbody_color(temperature: f64) -> Vec3 {
+    let (x, y) = planckian_chromaticity(temperature);
+    // xyY -> XYZ pour une luminance unitaire
+    let (cx, cy, cz) = (x / y, 1.0, (1.0 - x - y) / y);
+    // XYZ -> sRGB linéaire (illuminant D65)
+    let rgb = Vec3::new(
+        (3.2406 * cx - 1.5372 * cy - 0.4986 * cz) as f32,
+        (-0.9689 * cx + 1.8758 * cy + 0.0415 * cz) as f32,
+        (0.0557 * cx - 0.2040 * cy + 1.0570 * cz) as f32,
+    ).max(Vec3::ZERO);
+    rgb / rgb.max_element()
+}
+
+/// Éclat de surface relatif au Soleil ; le flux ∝ T⁴ est compressé en ∝ T pour rester affichable
+pub fn emission_intensity(temperature: f64) -> f32 {
+    ((temperature / SOLAR_TEMPERATURE) as f32).clamp(MIN_INTENSITY, MAX_INTENSITY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_planckian_chromaticity() {
+        // Illuminant A (2856 K) et points du lieu de Planck (CIE 15:2004)
+        for (temperature, x, y) in [(2856.0, 0.4476, 0.4074), (5000.0, 0.3451, 0.3516), (10000.0, 0.2807, 0.2884)] {
+            let (cx, cy) = planckian_chromaticity(temperature);
+            assert!((cx - x).abs() < 0.002 && (cy - y).abs() < 0.002, "{} K : ({}, {})", temperature, cx, cy);
+        }
+    }
+
+    #[test]
+    fn test_star_colors() {
+        let m_dwarf = blackbody_color(3000.0);
+        assert!(m_dwarf.x > m_dwarf.y && m_dwarf.y > m_dwarf.z);
+        let o_star = blackbody_color(35000.0);
+        assert!(o_star.z > o_star.x);
+        // Le Soleil est presque blanc
+        let sun = blackbody_color(SOLAR_TEMPERATURE);
+        assert_eq!(sun.max_element(), 1.0);
+        assert!(sun.min_element() > 0.75, "{:?}", sun);
+    }
+
+    #[test]
+    fn test_emission_intensity() {
+        assert_eq!(emission_intensity(SOLAR_TEMPERATURE), 1.0);
+        assert!(emission_intensity(3000.0) < emission_intensity(10000.0));
+        assert_eq!(emission_intensity(100000.0), MAX_INTENSITY);
+    }
+}
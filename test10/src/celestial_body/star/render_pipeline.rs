@@ -1,5 +1,6 @@
 use crate::celestial_body::star::star_vertex::Vertex;
 use crate::celestial_body::star::star_instance::InstanceRaw;
+use crate::celestial_body::planet::render_pipeline::depth_stencil_state;
 
 pub fn star_render_pipeline(
     device: &wgpu::Device,
@@ -54,7 +55,7 @@ pub fn star_render_pipeline(
             // Requires Features::CONSERVATIVE_RASTERIZATION
             conservative: false,
         },
-        depth_stencil: None,
+        depth_stencil: Some(depth_stencil_state(wgpu::CompareFunction::Greater, true)),
         multisample: wgpu::MultisampleState {
             count: 1,
             mask: !0,
@@ -21,6 +21,8 @@ struct InstanceInput {
     @location(6) model_matrix_1: vec4<f32>,
     @location(7) model_matrix_2: vec4<f32>,
     @location(8) model_matrix_3: vec4<f32>,
+    // Couleur de corps noir (rgb) et éclat (a)
+    @location(9) emission: vec4<f32>,
 };
 
 
@@ -36,6 +38,7 @@ struct VertexOutput {
     @location(3) star_center_ndc: vec2<f32>,
     @location(4) star_center_w: f32,
     @location(5) static_pos: vec3<f32>,
+    @location(6) emission: vec4<f32>,
 };
 
 fn fade(t: f32) -> f32 { return t*t*t*(t*(t*6.0 - 15.0) + 10.0); }
@@ -137,7 +140,11 @@ fn vs_main(
         (star_center_clip.y / star_center_clip.w)
     );
     out.star_center_w = star_center_clip.w;
-    out.static_pos = (vec4<f32>(model.position, 1.0)).xyz;
+    // Sphère unité commune : remise à l'échelle du rayon de l'instance (norme d'une colonne
+    // de la matrice), pour que la granulation garde sa taille quelle que soit l'étoile
+    let radius = length(instance.model_matrix_0.xyz);
+    out.static_pos = model.position * radius;
+    out.emission = instance.emission;
     return out;
 }
 
@@ -145,14 +152,18 @@ fn vs_main(
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Éclat nul : trou noir, sans granulation ni halo
+    if (in.emission.a <= 0.0) {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
     var noise = fbm_perlin_noise(in.static_pos.x, in.static_pos.y, in.static_pos.z, 4, 0.7, 10.0);
     noise = (noise + 1.0) / 2.0 + 1.0;
     noise = pow(noise, 1.9); // augmente le contraste
 
     let dist = length(in.ndc_pos - in.star_center_ndc) * in.star_center_w * 0.095 * camera.aspect_ratio * 1.5;
     let glow = pow(1.0 - smoothstep(0.2, 0.4, dist), 2.0);
-    let base_color = vec3<f32>(1.0, 0.8, 0.2);
+    let base_color = in.emission.rgb;
     let noise_weight = mix(0.4, 1.0, glow); // Le bruit varie de 0.8 à 1.0 selon le glow
-    let color = base_color * (1.0 + 4 * glow) * noise / noise_weight;
+    let color = base_color * in.emission.a * (1.0 + 4 * glow) * noise / noise_weight;
     return vec4<f32>(color, 1.0);
 }
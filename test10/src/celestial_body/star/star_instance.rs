@@ -1,14 +1,30 @@
+use crate::celestial_body::star::blackbody::{blackbody_color, emission_intensity};
+use crate::stellar_system::stellar_model::SOLAR_TEMPERATURE;
+use crate::stellar_system::stellar_system::StellarStage;
+
 pub struct StarInstance {
     pub position: glam::Vec3,
     pub rotation: glam::Quat,
+    /// Rayon appliqué à la sphère unité commune à toutes les étoiles
+    pub radius: f32,
+    /// Couleur de corps noir (sRGB linéaire)
+    pub color: glam::Vec3,
+    pub intensity: f32,
 }
 
 impl StarInstance {
     pub fn new(position: glam::Vec3, rotation: glam::Quat) -> Self {
-        Self {
-            position,
-            rotation
-        }
+        Self::with_temperature(position, rotation, 1.0, SOLAR_TEMPERATURE, StellarStage::MainSequence)
+    }
+
+    /// Couleur et éclat déduits de la température effective (K). Un trou noir n'émet rien :
+    /// éclat nul, que le shader rend comme une ombre opaque sur le fond
+    pub fn with_temperature(position: glam::Vec3, rotation: glam::Quat, radius: f32, temperature: f64, stage: StellarStage) -> Self {
+        let (color, intensity) = match stage {
+            StellarStage::BlackHole => (glam::Vec3::ZERO, 0.0),
+            _ => (blackbody_color(temperature), emission_intensity(temperature)),
+        };
+        Self { position, rotation, radius, color, intensity }
     }
 
     pub fn set_rotation(&mut self, rotation: glam::Quat) {
@@ -21,7 +37,8 @@ impl StarInstance {
 
     pub fn to_raw(&self) -> InstanceRaw {
         InstanceRaw {
-            model: (glam::Mat4::from_translation(self.position) * glam::Mat4::from_quat(self.rotation)).to_cols_array_2d(),
+            model: glam::Mat4::from_scale_rotation_translation(glam::Vec3::splat(self.radius), self.rotation, self.position).to_cols_array_2d(),
+            emission: self.color.extend(self.intensity).to_array(),
         }
     }
 }
@@ -30,6 +47,8 @@ impl StarInstance {
 #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct InstanceRaw {
     pub model: [[f32; 4]; 4],
+    /// Couleur (rgb) et éclat (a) de l'étoile
+    pub emission: [f32; 4],
 }
 
 impl InstanceRaw {
@@ -66,7 +85,25 @@ impl InstanceRaw {
                     shader_location: 8,
                     format: wgpu::VertexFormat::Float32x4,
                 },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 16]>() as wgpu::BufferAddress,
+                    shader_location: 9,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
             ],
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_black_hole_emits_nothing() {
+        let star = StarInstance::with_temperature(glam::Vec3::ZERO, glam::Quat::IDENTITY, 1.0, SOLAR_TEMPERATURE, StellarStage::MainSequence);
+        assert!(star.intensity > 0.0);
+        // Température nulle : sans cas particulier, la couleur serait celle d'une naine rouge
+        let black_hole = StarInstance::with_temperature(glam::Vec3::ZERO, glam::Quat::IDENTITY, 1.0, 0.0, StellarStage::BlackHole);
+        assert_eq!(black_hole.to_raw().emission, [0.0; 4]);
+    }
+}
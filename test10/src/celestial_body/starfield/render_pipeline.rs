@@ -0,0 +1,92 @@
+use crate::celestial_body::planet::render_pipeline::depth_stencil_state;
+use crate::celestial_body::starfield::starfield_vertex::{SkyVertex, StarPoint};
+
+// La voûte s'ajoute au fond noir : la Voie lactée puis les étoiles qui la traversent
+const ADDITIVE_BLENDING: wgpu::BlendState = wgpu::BlendState {
+    color: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::One,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    },
+    alpha: wgpu::BlendComponent::OVER,
+};
+
+fn starfield_pipeline(
+    device: &wgpu::Device,
+    bind_group_layouts: &[&wgpu::BindGroupLayout],
+    config: &wgpu::SurfaceConfiguration,
+    label: &str,
+    entry_points: (&str, &str),
+    buffers: &[wgpu::VertexBufferLayout],
+) -> wgpu::RenderPipeline
+{
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Starfield Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("starfield.wgsl").into()),
+    });
+
+    let render_pipeline_layout =
+    device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Starfield Render Pipeline Layout"),
+        bind_group_layouts,
+        push_constant_ranges: &[],
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(label),
+        layout: Some(&render_pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: Some(entry_points.0),
+            buffers,
+            compilation_options: Default::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: Some(entry_points.1),
+            targets: &[Some(wgpu::ColorTargetState {
+                format: config.format,
+                blend: Some(ADDITIVE_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: Default::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            // La sphère céleste est vue de l'intérieur
+            cull_mode: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        // Fond de ciel, dessiné en premier : ni testé ni écrit
+        depth_stencil: Some(depth_stencil_state(wgpu::CompareFunction::Always, false)),
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+        cache: None,
+    })
+}
+
+pub fn sky_band_render_pipeline(
+    device: &wgpu::Device,
+    bind_group_layouts: &[&wgpu::BindGroupLayout],
+    config: &wgpu::SurfaceConfiguration
+) -> wgpu::RenderPipeline
+{
+    starfield_pipeline(device, bind_group_layouts, config, "Sky Band Render Pipeline", ("vs_band", "fs_band"), &[SkyVertex::desc()])
+}
+
+pub fn star_point_render_pipeline(
+    device: &wgpu::Device,
+    bind_group_layouts: &[&wgpu::BindGroupLayout],
+    config: &wgpu::SurfaceConfiguration
+) -> wgpu::RenderPipeline
+{
+    starfield_pipeline(device, bind_group_layouts, config, "Star Point Render Pipeline", ("vs_star", "fs_star"), &[StarPoint::desc()])
+}
@@ -0,0 +1,81 @@
+// Voûte céleste : Voie lactée et étoiles voisines, projetées à l'infini
+
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+    aspect_ratio: f32,
+};
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+// Une direction (w = 0) échappe à la translation de la caméra : la voûte ne bouge qu'avec son orientation
+fn project_at_infinity(direction: vec3<f32>) -> vec4<f32> {
+    var clip = camera.view_proj * vec4<f32>(direction, 0.0);
+    // À l'infini : profondeur 0 en profondeur inversée, derrière tous les corps du système
+    clip.z = 0.0;
+    return clip;
+}
+
+// Voie lactée
+
+struct SkyVertexInput {
+    @location(0) direction: vec3<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+struct SkyVertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+@vertex
+fn vs_band(model: SkyVertexInput) -> SkyVertexOutput {
+    var out: SkyVertexOutput;
+    out.clip_position = project_at_infinity(model.direction);
+    out.color = model.color;
+    return out;
+}
+
+@fragment
+fn fs_band(in: SkyVertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(in.color.rgb * in.color.a, 1.0);
+}
+
+// Étoiles
+
+struct StarPointInput {
+    @location(0) direction: vec3<f32>,
+    @location(1) size: f32,
+    @location(2) color: vec4<f32>,
+};
+
+struct StarPointOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) corner: vec2<f32>,
+};
+
+@vertex
+fn vs_star(@builtin(vertex_index) index: u32, star: StarPointInput) -> StarPointOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, -1.0), vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, 1.0), vec2<f32>(-1.0, 1.0),
+    );
+    let corner = corners[index];
+    var clip = project_at_infinity(star.direction);
+    // Quad de taille constante à l'écran
+    clip.x += corner.x * star.size / camera.aspect_ratio * clip.w;
+    clip.y += corner.y * star.size * clip.w;
+
+    var out: StarPointOutput;
+    out.clip_position = clip;
+    out.color = star.color;
+    out.corner = corner;
+    return out;
+}
+
+@fragment
+fn fs_star(in: StarPointOutput) -> @location(0) vec4<f32> {
+    // Tache gaussienne plutôt qu'un carré
+    let falloff = exp(-4.0 * dot(in.corner, in.corner));
+    return vec4<f32>(in.color.rgb * in.color.a * falloff, 1.0);
+}
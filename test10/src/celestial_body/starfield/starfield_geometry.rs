@@ -0,0 +1,185 @@
+use glam::Vec3;
+use wgpu::util::DeviceExt;
+
+use crate::celestial_body::star::blackbody::blackbody_color;
+use crate::celestial_body::starfield::starfield_vertex::{SkyVertex, StarPoint};
+use crate::galaxy::density::{stellar_density, GALAXY_RADIUS};
+use crate::galaxy::{Galaxy, SystemLocation};
+use crate::geometry::icosphere::IcoSphere;
+
+// Rayon du voisinage dont les systèmes sont dessinés un à un, en parsecs
+pub const STARFIELD_RADIUS: f32 = 40.0;
+// Magnitude bolométrique absolue du Soleil
+const SOLAR_ABSOLUTE_MAGNITUDE: f64 = 4.74;
+// Magnitudes apparentes des étoiles les plus faibles affichées et de celles dessinées en taille maximale
+const MAGNITUDE_LIMIT: f64 = 9.0;
+const BRIGHT_MAGNITUDE: f64 = -1.0;
+// Demi-taille des étoiles, en coordonnées normalisées de l'écran, et éclat des plus faibles
+const STAR_MIN_SIZE: f32 = 0.002;
+const STAR_MAX_SIZE: f32 = 0.008;
+const STAR_MIN_INTENSITY: f32 = 0.15;
+// Voie lactée : finesse de la sphère céleste et nombre de pas d'intégration le long de la ligne de visée
+const SKY_SUBDIVISIONS: u8 = 5;
+const LINE_OF_SIGHT_STEPS: u32 = 96;
+const BAND_COLOR: Vec3 = Vec3::new(0.85, 0.80, 0.70);
+const BAND_BRIGHTNESS: f32 = 0.35;
+// Densité de colonne de l'ordre de celle vue vers les pôles galactiques depuis le voisinage solaire
+const BAND_REFERENCE_COLUMN: f64 = 50.0;
+
+/// Magnitude apparente d'une étoile de luminosité `luminosity` (L☉) vue à `distance` parsecs
+pub fn apparent_magnitude(luminosity: f64, distance: f64) -> f64 {
+    SOLAR_ABSOLUTE_MAGNITUDE - 2.5 * luminosity.log10() + 5.0 * (distance / 10.0).log10()
+}
+
+/// Étoile d'arrière-plan de magnitude apparente `magnitude` dans la direction `direction`,
+/// ou None si elle est trop faible pour être vue
+pub fn star_point(direction: Vec3, magnitude: f64, temperature: f64) -> Option<StarPoint> {
+    if magnitude.is_nan() || magnitude > MAGNITUDE_LIMIT {
+        return None;
+    }
+    // Taille et éclat croissent linéairement avec la magnitude (échelle logarithmique du flux)
+    let t = ((MAGNITUDE_LIMIT - magnitude) / (MAGNITUDE_LIMIT - BRIGHT_MAGNITUDE)).clamp(0.0, 1.0) as f32;
+    let color = blackbody_color(temperature);
+    Some(StarPoint {
+        direction: direction.normalize().to_array(),
+        size: STAR_MIN_SIZE + t * (STAR_MAX_SIZE - STAR_MIN_SIZE),
+        color: [color.x, color.y, color.z, STAR_MIN_INTENSITY + t * (1.0 - STAR_MIN_INTENSITY)],
+    })
+}
+
+/// Étoiles des systèmes voisins de `home` (à moins de `radius` parsecs), vues depuis ce système
+pub fn neighbor_stars(galaxy: &Galaxy, home: &SystemLocation, radius: f32) -> Vec<StarPoint> {
+    galaxy.systems_near(home.position, radius).iter()
+        .filter(|summary| summary.location.seed != home.seed)
+        .filter_map(|summary| {
+            let offset = summary.location.position - home.position;
+            let magnitude = apparent_magnitude(summary.luminosity, offset.length() as f64);
+            star_point(offset, magnitude, summary.temperature)
+        })
+        .collect()
+}
+
+/// Densité de colonne (systèmes par parsec carré) vue depuis `observer` dans la direction `direction`
+pub fn column_density(observer: Vec3, direction: Vec3) -> f64 {
+    let direction = direction.normalize();
+    let length = 2.0 * GALAXY_RADIUS;
+    // Pas croissants : le voisinage est échantillonné finement, le disque lointain grossièrement
+    let mut column = 0.0;
+    let mut previous = 0.0;
+    for i in 1..=LINE_OF_SIGHT_STEPS {
+        let distance = length * (i as f64 / LINE_OF_SIGHT_STEPS as f64).powi(2);
+        let midpoint = 0.5 * (previous + distance);
+        column += stellar_density(observer + direction * midpoint as f32) * (distance - previous);
+        previous = distance;
+    }
+    column
+}
+
+/// Sphère céleste colorée par la Voie lactée telle qu'on la voit depuis `observer`
+pub fn sky_band(observer: Vec3) -> (Vec<SkyVertex>, Vec<u32>) {
+    let mut sphere = IcoSphere::new();
+    sphere.generate(SKY_SUBDIVISIONS);
+    let columns: Vec<f64> = sphere.vertices.iter().map(|&direction| column_density(observer, direction)).collect();
+    let max = columns.iter().cloned().fold(f64::MIN_POSITIVE, f64::max);
+    let vertices = sphere.vertices.iter().zip(&columns).map(|(direction, &column)| {
+        // Échelle logarithmique : le bulbe reste le plus brillant sans éteindre le reste de la bande
+        let brightness = BAND_BRIGHTNESS * ((column / BAND_REFERENCE_COLUMN).ln_1p() / (max / BAND_REFERENCE_COLUMN).ln_1p()) as f32;
+        SkyVertex {
+            direction: direction.to_array(),
+            color: [BAND_COLOR.x, BAND_COLOR.y, BAND_COLOR.z, brightness],
+        }
+    }).collect();
+    (vertices, sphere.indices)
+}
+
+/// Voûte céleste d'un système : Voie lactée et étoiles voisines, fixes quelle que soit la position de la caméra
+pub struct Starfield {
+    pub band_vertex_buffer: wgpu::Buffer,
+    pub band_index_buffer: wgpu::Buffer,
+    pub num_band_indices: u32,
+    pub star_buffer: wgpu::Buffer,
+    pub num_stars: u32,
+}
+
+impl Starfield {
+    pub fn new(device: &wgpu::Device, galaxy: &Galaxy, home: &SystemLocation) -> Self {
+        let (vertices, indices) = sky_band(home.position);
+        let stars = neighbor_stars(galaxy, home, STARFIELD_RADIUS);
+        log::info!("Voûte céleste : {} étoiles visibles", stars.len());
+        let band_vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Sky Band Vertex Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let band_index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Sky Band Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        let star_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Star Point Instance Buffer"),
+            contents: bytemuck::cast_slice(&stars),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        Self {
+            band_vertex_buffer,
+            band_index_buffer,
+            num_band_indices: indices.len() as u32,
+            star_buffer,
+            num_stars: stars.len() as u32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::galaxy::density::SOLAR_POSITION;
+    use crate::stellar_system::seed::DEFAULT_UNIVERSE_SEED;
+
+    #[test]
+    fn test_apparent_magnitude() {
+        // Le Soleil vu à 10 pc a sa magnitude absolue ; cent fois plus loin, il perd 10 magnitudes
+        assert!((apparent_magnitude(1.0, 10.0) - SOLAR_ABSOLUTE_MAGNITUDE).abs() < 1.0e-12);
+        assert!((apparent_magnitude(1.0, 1000.0) - apparent_magnitude(1.0, 10.0) - 10.0).abs() < 1.0e-9);
+        // Cent fois plus lumineuse : 5 magnitudes de moins
+        assert!((apparent_magnitude(100.0, 10.0) - apparent_magnitude(1.0, 10.0) + 5.0).abs() < 1.0e-9);
+    }
+
+    #[test]
+    fn test_star_points() {
+        let bright = star_point(Vec3::new(3.0, 0.0, 4.0), 0.0, 10000.0).unwrap();
+        let faint = star_point(Vec3::X, 8.0, 3000.0).unwrap();
+        assert!(bright.size > faint.size && bright.color[3] > faint.color[3]);
+        assert!((Vec3::from_array(bright.direction).length() - 1.0).abs() < 1.0e-6);
+        // Les étoiles chaudes sont bleutées, les froides rougeâtres
+        assert!(bright.color[2] > bright.color[0] && faint.color[0] > faint.color[2]);
+        assert!(star_point(Vec3::X, MAGNITUDE_LIMIT + 0.1, 5772.0).is_none());
+        // Un trou noir n'émet rien
+        assert!(star_point(Vec3::X, apparent_magnitude(0.0, 5.0), 0.0).is_none());
+    }
+
+    #[test]
+    fn test_neighbor_stars() {
+        let galaxy = Galaxy::new(DEFAULT_UNIVERSE_SEED);
+        let home = galaxy.nearest(SOLAR_POSITION, 10.0).unwrap();
+        let stars = neighbor_stars(&galaxy, &home, 15.0);
+        assert!(!stars.is_empty());
+        // Les naines rouges lointaines sont trop faibles pour être vues
+        assert!(stars.len() < galaxy.locations_near(home.position, 15.0).len());
+        assert_eq!(stars, neighbor_stars(&galaxy, &home, 15.0));
+    }
+
+    #[test]
+    fn test_milky_way_band() {
+        // Depuis le voisinage solaire, le plan galactique est bien plus dense que les pôles,
+        // et la direction du centre plus dense que l'anticentre
+        let toward_center = column_density(SOLAR_POSITION, -Vec3::X);
+        let anticenter = column_density(SOLAR_POSITION, Vec3::X);
+        let along_disk = column_density(SOLAR_POSITION, Vec3::Z);
+        let pole = column_density(SOLAR_POSITION, Vec3::Y);
+        assert!(toward_center > along_disk && along_disk > anticenter);
+        assert!(anticenter > 3.0 * pole, "anticentre {} pôle {}", anticenter, pole);
+        assert!(along_disk > 10.0 * pole, "plan {} pôle {}", along_disk, pole);
+    }
+}
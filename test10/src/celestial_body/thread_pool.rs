@@ -0,0 +1,115 @@
+use crate::celestial_body::generation::{GenerationEvent, GeometryRequest};
+use crate::celestial_body::job_queue::{JobId, JobQueue, QueueFull};
+use crate::celestial_body::load_priority::LoadPriority;
+use crate::celestial_body::protocol::{run_job, WorkerResponse};
+
+/// Pool de threads de génération (builds natifs) : les messages du protocole transitent
+/// par un canal, sans sérialisation
+pub struct GeometryPool {
+    threads: rayon::ThreadPool,
+    queue: JobQueue,
+    sender: flume::Sender<(usize, WorkerResponse)>,
+    receiver: flume::Receiver<(usize, WorkerResponse)>,
+}
+
+impl GeometryPool {
+    pub fn new(workers: usize, capacity: usize) -> Self {
+        let threads = rayon::ThreadPoolBuilder::new()
+            .num_threads(workers)
+            .thread_name(|i| format!("geometry-{i}"))
+            .build()
+            .expect("failed to build geometry thread pool");
+        let mut queue = JobQueue::new(workers, capacity);
+        (0..workers).for_each(|worker| queue.set_ready(worker));
+        let (sender, receiver) = flume::unbounded();
+        Self { threads, queue, sender, receiver }
+    }
+
+    pub fn submit(&mut self, request: GeometryRequest, priority: LoadPriority) -> Result<JobId, QueueFull> {
+        let job = self.queue.submit(request, priority)?;
+        self.dispatch();
+        Ok(job)
+    }
+
+    pub fn reprioritize(&mut self, job: JobId, priority: LoadPriority) {
+        self.queue.reprioritize(job, priority);
+    }
+
+    pub fn cancel(&mut self, job: JobId) {
+        // Un thread ne peut pas être interrompu : son résultat sera ignoré
+        self.queue.cancel(job);
+    }
+
+    /// Nouvelles des générations depuis le dernier appel, sans bloquer le thread de rendu
+    pub fn poll(&mut self) -> Vec<GenerationEvent> {
+        let mut events = Vec::new();
+        for (worker, response) in self.receiver.try_iter() {
+            match response {
+                WorkerResponse::Progress { job, fraction } if self.queue.is_active(job) => {
+                    events.push(GenerationEvent::Progress { job, fraction });
+                }
+                WorkerResponse::Generated { job, geometry } if self.queue.complete(worker, job) => {
+                    events.push(GenerationEvent::Finished { job, geometry });
+                }
+                WorkerResponse::Failed { job, error } if self.queue.complete(worker, job) => {
+                    events.push(GenerationEvent::Failed { job, error });
+                }
+                _ => {}
+            }
+        }
+        self.dispatch();
+        events
+    }
+
+    fn dispatch(&mut self) {
+        for (worker, job, request) in self.queue.dispatch() {
+            let sender = self.sender.clone();
+            self.threads.spawn(move || {
+                run_job(job, request, &mut |response| {
+                    let _ = sender.send((worker, response));
+                });
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::celestial_body::generation::GeneratedGeometry;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_pool_runs_and_cancels_jobs() {
+        let mut pool = GeometryPool::new(2, 8);
+        let jobs: Vec<JobId> = (0..4).map(|lod| pool.submit(GeometryRequest::Star { radius: 1.0, lod }, LoadPriority::LOWEST).unwrap()).collect();
+        // Deux threads : le dernier travail attend encore dans la file
+        pool.cancel(jobs[3]);
+        let invalid = pool.submit(GeometryRequest::Star { radius: -1.0, lod: 1 }, LoadPriority::LOWEST).unwrap();
+
+        let start = Instant::now();
+        let mut results = Vec::new();
+        let mut failures = Vec::new();
+        while results.len() + failures.len() < 4 {
+            assert!(start.elapsed() < Duration::from_secs(30), "génération trop longue");
+            for event in pool.poll() {
+                match event {
+                    GenerationEvent::Finished { job, geometry } => results.push((job, geometry)),
+                    GenerationEvent::Failed { job, .. } => failures.push(job),
+                    GenerationEvent::Progress { .. } => {}
+                }
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!((pool.queue.queued(), pool.queue.running()), (0, 0));
+        assert_eq!(failures, vec![invalid]);
+
+        results.sort_by_key(|(job, _)| *job);
+        assert_eq!(results.iter().map(|(job, _)| *job).collect::<Vec<_>>(), jobs[..3]);
+        for (lod, (_, geometry)) in results.into_iter().enumerate() {
+            let GeneratedGeometry::Star(star) = geometry else { panic!("étoile attendue") };
+            // 20 triangles par face de l'icosaèdre, multipliés par 4 à chaque subdivision
+            assert_eq!(star.indice.len(), 3 * 20 * 4usize.pow(lod as u32));
+        }
+    }
+}
@@ -1,17 +1,11 @@
-use js_sys::{Array, Float32Array, Uint8Array};
+use js_sys::{Array, Reflect, Uint8Array};
 use web_sys::{window, Blob, BlobPropertyBag, Url, Worker, MessageEvent};
-use crate::celestial_body::star::star_vertex;
-use crate::celestial_body::LOD_SHARED_ARRAY_BUFFER_COL;
-use crate::celestial_body::LOD_SHARED_ARRAY_BUFFER_IND;
-use crate::celestial_body::LOD_SHARED_ARRAY_BUFFER_NOR;
-use crate::celestial_body::LOD_SHARED_ARRAY_BUFFER_POS;
-use crate::celestial_body::geometry_loader::{CelestialBodyGeometry, CelestialVertex};
-use crate::celestial_body::planet::planet_vertex;
-use crate::celestial_body::planet::planet_geometry::PlanetVertex;
-use crate::celestial_body::star::star_geometry::StarVertex;
+use crate::celestial_body::generation::{GenerationEvent, GeometryRequest};
+use crate::celestial_body::job_queue::{JobId, JobQueue, QueueFull};
+use crate::celestial_body::load_priority::LoadPriority;
+use crate::celestial_body::protocol::{ProtocolError, WorkerRequest, WorkerResponse};
 
 use wasm_bindgen::{prelude::*, JsCast};
-use js_sys::{SharedArrayBuffer, Uint32Array, Reflect, Object};
 use wasm_bindgen::JsValue;
 use std::rc::Rc;
 use std::cell::RefCell;
@@ -40,144 +34,142 @@ pub fn worker_new(name: &str) -> Worker {
     Worker::new(&url).expect("failed to spawn worker")
 }
 
-pub fn generate_worker(
-    planet_rc: &Rc<RefCell<CelestialBodyGeometry>>,
-    pending: Rc<RefCell<Option<(Vec<CelestialVertex>, Vec<u32>)>>>,
-    lod: usize
-) {
-    console_error_panic_hook::set_once();
-
-    let lod_pos = SharedArrayBuffer::new(LOD_SHARED_ARRAY_BUFFER_POS[lod]);
-    let lod_col = SharedArrayBuffer::new(LOD_SHARED_ARRAY_BUFFER_COL[lod]);
-    let lod_nor = SharedArrayBuffer::new(LOD_SHARED_ARRAY_BUFFER_NOR[lod]);
-    let lod_ind = SharedArrayBuffer::new(LOD_SHARED_ARRAY_BUFFER_IND[lod]);
-
-    let config: SharedArrayBuffer = SharedArrayBuffer::new(2);
-    let config_data: Uint8Array = Uint8Array::new(&config);
-
-    let config_f32: SharedArrayBuffer = SharedArrayBuffer::new(4);
-    let config_data_f32: Float32Array = Float32Array::new(&config_f32);
-
-    config_data.set_index(0, lod as u8);
-
-    let type_id = match *planet_rc.borrow() {
-        CelestialBodyGeometry::Planet(_) => 0u8,
-        CelestialBodyGeometry::Star(_) => 1u8,
-    };
-    config_data.set_index(1, type_id);
-    let radius = match &*planet_rc.borrow() {
-        CelestialBodyGeometry::Planet(p) => p.radius,
-        CelestialBodyGeometry::Star(s) => s.radius,
-    };
-    config_data_f32.set_index(0, radius);
-
-    // Create worker
-    let worker = worker_new("worker-geometry");
+// Ce qu'un worker a fait savoir depuis le dernier poll
+enum WorkerMessage {
+    Response(WorkerResponse),
+    Unreadable(ProtocolError),
+    // Panique ou erreur de chargement : l'instance wasm du worker est inutilisable
+    Crashed(String),
+}
 
-    // Create common object buffer
-    let obj = Object::new();
-    Reflect::set(&obj, &JsValue::from_str("lod_pos"), &lod_pos).unwrap();
-    Reflect::set(&obj, &JsValue::from_str("lod_col"), &lod_col).unwrap();
-    Reflect::set(&obj, &JsValue::from_str("lod_nor"), &lod_nor).unwrap();
-    Reflect::set(&obj, &JsValue::from_str("lod_ind"), &lod_ind).unwrap();
-    Reflect::set(&obj, &JsValue::from_str("config"), &config).unwrap();
-    Reflect::set(&obj, &JsValue::from_str("config_f32"), &config_f32).unwrap();
-
-    let worker_is_ready = Rc::new(RefCell::new(false));
-    let worker_is_ready_clone = worker_is_ready.clone();
-    let planet_clone = planet_rc.clone();
-    let pending_clone = pending.clone();
-    let worker_clone = worker.clone();
+type Inbox = Rc<RefCell<Vec<(usize, WorkerMessage)>>>;
+
+fn spawn_worker(index: usize, inbox: &Inbox) -> Worker {
+    let worker = worker_new("worker-geometry");
 
+    let inbox_clone = inbox.clone();
     let onmessage = Closure::wrap(Box::new(move |msg: MessageEvent| {
+        let bytes = Uint8Array::new(&msg.data()).to_vec();
+        let message = match WorkerResponse::decode(&bytes) {
+            Ok(response) => WorkerMessage::Response(response),
+            Err(error) => WorkerMessage::Unreadable(error),
+        };
+        inbox_clone.borrow_mut().push((index, message));
+    }) as Box<dyn FnMut(MessageEvent)>);
+    worker.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+    onmessage.forget();
 
-        let data = msg.data();
-        if !*worker_is_ready_clone.borrow() {
-            if Array::is_array(&data) && Array::from(&data).length() == 0 {
-                worker_clone.post_message(&obj).expect("send SharedArrayBuffer");
-                *worker_is_ready_clone.borrow_mut() = true;
-                return;
-            }
-        }
+    let inbox_clone = inbox.clone();
+    let onerror = Closure::wrap(Box::new(move |event: JsValue| {
+        let message = Reflect::get(&event, &JsValue::from_str("message")).ok()
+            .and_then(|message| message.as_string())
+            .unwrap_or_else(|| "erreur inconnue".to_string());
+        inbox_clone.borrow_mut().push((index, WorkerMessage::Crashed(message)));
+    }) as Box<dyn FnMut(JsValue)>);
+    worker.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+    onerror.forget();
+
+    worker
+}
 
-        if data.is_object() && !Array::is_array(&data) {
-            if Reflect::has(&data, &JsValue::from_str("lod_pos")).unwrap_or(false) {
-
-                let lod_pos = Reflect::get(&data, &JsValue::from_str("lod_pos")).unwrap();
-                let lod_pos = Float32Array::new(&lod_pos);
-
-                let lod_col = Reflect::get(&data, &JsValue::from_str("lod_col")).unwrap();
-                let lod_col = Float32Array::new(&lod_col);
-
-                let lod_nor = Reflect::get(&data, &JsValue::from_str("lod_nor")).unwrap();
-                let lod_nor = Float32Array::new(&lod_nor);
-
-                let lod_ind = Reflect::get(&data, &JsValue::from_str("lod_ind")).unwrap();
-                let lod_ind = Uint32Array::new(&lod_ind);
-
-                
-                
-                let mut planet_x = planet_clone.borrow_mut();
-                
-                match &mut *planet_x {
-                    CelestialBodyGeometry::Planet(planet) => {
-                        planet.lod_levels.resize(lod + 1, PlanetVertex::new());
-                        
-                        let mut vec1 = vec![0.0; lod_pos.length() as usize];
-                        lod_pos.copy_to(&mut vec1[..]);
-                        planet.lod_levels[lod].position = vec1;
-                        
-                        let mut vec2 = vec![0.0; lod_col.length() as usize];
-                        lod_col.copy_to(&mut vec2[..]);
-                        planet.lod_levels[lod].color = vec2;
-                        
-                        let mut vec3 = vec![0.0; lod_nor.length() as usize];
-                        lod_nor.copy_to(&mut vec3[..]);
-                        planet.lod_levels[lod].normal = vec3;
-                        
-                        let mut vec4 = vec![0; lod_ind.length() as usize];
-                        lod_ind.copy_to(&mut vec4[..]);
-                        planet.lod_levels[lod].indice = vec4;
-                        
-                        // planet_clone.borrow_mut().lod_ready = true;
-                        let pv = &planet.lod_levels[lod];
-                        
-                        let vertices = planet_vertex::Vertex::planet_vertex_to_vertex(pv);
-                        let vertices: Vec<CelestialVertex> = vertices.into_iter().map(CelestialVertex::Planet).collect();
-                        let indices = planet.get_indices(lod).to_vec();
-                        
-                        *pending_clone.borrow_mut() = Some((vertices, indices));
-                        worker_clone.terminate();
+/// Pool de Web Workers de génération (builds wasm), créés une fois pour toutes :
+/// le module wasm n'est téléchargé et instancié qu'au démarrage
+pub struct GeometryPool {
+    workers: Vec<Worker>,
+    queue: JobQueue,
+    // Messages reçus par les callbacks des workers, en attente du prochain poll
+    inbox: Inbox,
+}
+
+impl GeometryPool {
+    pub fn new(workers: usize, capacity: usize) -> Self {
+        console_error_panic_hook::set_once();
+        let inbox = Rc::new(RefCell::new(Vec::new()));
+        let queue = JobQueue::new(workers, capacity);
+        let workers = (0..workers).map(|index| spawn_worker(index, &inbox)).collect();
+        Self { workers, queue, inbox }
+    }
+
+    pub fn submit(&mut self, request: GeometryRequest, priority: LoadPriority) -> Result<JobId, QueueFull> {
+        let job = self.queue.submit(request, priority)?;
+        self.dispatch();
+        Ok(job)
+    }
+
+    pub fn reprioritize(&mut self, job: JobId, priority: LoadPriority) {
+        self.queue.reprioritize(job, priority);
+    }
+
+    pub fn cancel(&mut self, job: JobId) {
+        // Le worker abandonne le travail s'il ne l'a pas encore commencé, sinon son résultat sera ignoré
+        if let Some(worker) = self.queue.cancel(job) {
+            self.post(worker, &WorkerRequest::Cancel { job });
+        }
+    }
+
+    /// Nouvelles des générations depuis le dernier appel, sans bloquer le thread de rendu
+    pub fn poll(&mut self) -> Vec<GenerationEvent> {
+        let mut events = Vec::new();
+        let messages: Vec<_> = self.inbox.borrow_mut().drain(..).collect();
+        for (worker, message) in messages {
+            match message {
+                WorkerMessage::Response(WorkerResponse::Ready) => self.queue.set_ready(worker),
+                WorkerMessage::Response(WorkerResponse::Progress { job, fraction }) => {
+                    if self.queue.is_active(job) {
+                        events.push(GenerationEvent::Progress { job, fraction });
                     }
-                    CelestialBodyGeometry::Star(star) => {
-                        star.lod_content.resize(lod + 1, StarVertex::new());
-                        
-                        let mut vec1 = vec![0.0; lod_pos.length() as usize];
-                        lod_pos.copy_to(&mut vec1[..]);
-                        star.lod_content[lod].position = vec1;
-                        
-                        let mut vec2 = vec![0.0; lod_col.length() as usize];
-                        lod_col.copy_to(&mut vec2[..]);
-                        star.lod_content[lod].color = vec2;
-                        
-                        let mut vec4 = vec![0; lod_ind.length() as usize];
-                        lod_ind.copy_to(&mut vec4[..]);
-                        star.lod_content[lod].indice = vec4;
-                        
-                        // planet_clone.borrow_mut().lod_ready = true;
-                        let pv = &star.lod_content[lod];
-                        
-                        let vertices = star_vertex::Vertex::planet_vertex_to_vertex(pv);
-                        let vertices: Vec<CelestialVertex> = vertices.into_iter().map(CelestialVertex::Star).collect();
-                        let indices = star.get_indices(lod).to_vec();
-                        
-                        *pending_clone.borrow_mut() = Some((vertices, indices));
-                        worker_clone.terminate();
+                }
+                WorkerMessage::Response(WorkerResponse::Generated { job, geometry }) => {
+                    if self.queue.complete(worker, job) {
+                        events.push(GenerationEvent::Finished { job, geometry });
+                    }
+                }
+                WorkerMessage::Response(WorkerResponse::Failed { job, error }) => {
+                    if self.queue.complete(worker, job) {
+                        events.push(GenerationEvent::Failed { job, error });
+                    }
+                }
+                WorkerMessage::Response(WorkerResponse::Cancelled { job }) => {
+                    self.queue.complete(worker, job);
+                }
+                WorkerMessage::Unreadable(error) => {
+                    // Probablement un script d'une autre version : le recréer n'y changerait rien,
+                    // le worker est écarté
+                    log::error!("Worker de géométrie {} écarté : {}", worker, error);
+                    self.workers[worker].terminate();
+                    if let Some(job) = self.queue.retire(worker) {
+                        events.push(GenerationEvent::Failed { job, error: error.to_string() });
+                    }
+                }
+                WorkerMessage::Crashed(error) => {
+                    log::error!("Worker de géométrie {} perdu, recréé : {}", worker, error);
+                    self.workers[worker].terminate();
+                    self.workers[worker] = spawn_worker(worker, &self.inbox);
+                    if let Some(job) = self.queue.restart(worker) {
+                        events.push(GenerationEvent::Failed { job, error });
                     }
                 }
             }
         }
-    }) as Box<dyn FnMut(MessageEvent)>);
-    worker.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
-    onmessage.forget();
-}
\ No newline at end of file
+        // Plus aucun worker : les générations en attente ne partiront jamais
+        for job in self.queue.abandon_if_no_workers() {
+            events.push(GenerationEvent::Failed { job, error: "aucun worker de géométrie utilisable".to_string() });
+        }
+        self.dispatch();
+        events
+    }
+
+    fn post(&self, worker: usize, request: &WorkerRequest) {
+        let message = Uint8Array::from(&request.encode()[..]);
+        // Le tampon est transféré au worker plutôt que copié
+        self.workers[worker]
+            .post_message_with_transfer(&message, &Array::of1(&message.buffer()))
+            .expect("send generation request");
+    }
+
+    fn dispatch(&mut self) {
+        for (worker, job, request) in self.queue.dispatch() {
+            self.post(worker, &WorkerRequest::Generate { job, request });
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::f64::consts::{PI, TAU};
+use glam::Vec3;
+
+// Repère galactocentrique en parsecs : disque dans le plan XZ, axe Y vertical
+
+// Disque mince (Jurić et al. 2008) : longueur d'échelle radiale et hauteur d'échelle
+const DISK_SCALE_LENGTH: f64 = 2600.0;
+const DISK_SCALE_HEIGHT: f64 = 300.0;
+// Densité centrale du disque, en systèmes par parsec cube, calée sur ~0.1 pc⁻³ au voisinage solaire
+const DISK_CENTRAL_DENSITY: f64 = 2.34;
+// Bulbe gaussien
+const BULGE_RADIUS: f64 = 1000.0;
+const BULGE_CENTRAL_DENSITY: f64 = 10.0;
+// Au-delà de ce rayon, le disque est tronqué
+pub const GALAXY_RADIUS: f64 = 15000.0;
+// Bras spiraux logarithmiques
+const ARM_COUNT: f64 = 4.0;
+const ARM_PITCH_ANGLE: f64 = 12.0 * PI / 180.0;
+const ARM_REFERENCE_RADIUS: f64 = 3000.0;
+const ARM_WIDTH: f64 = 400.0;
+// Surdensité relative du disque : (1 - ARM_CONTRAST) entre les bras, (1 + ARM_CONTRAST) sur leur axe
+const ARM_CONTRAST: f64 = 0.5;
+
+// Les supernovae à effondrement de cœur suivent les jeunes étoiles massives, très concentrées dans le plan
+const SUPERNOVA_SCALE_HEIGHT: f64 = 100.0;
+// Le disque interne a connu plus de générations d'étoiles ; le disque épais et le halo, moins enrichis
+const HISTORY_SCALE_LENGTH: f64 = 8000.0;
+const HISTORY_SCALE_HEIGHT: f64 = 900.0;
+
+// Position du Soleil, une vingtaine de parsecs au-dessus du plan
+pub const SOLAR_POSITION: Vec3 = Vec3::new(8200.0, 20.0, 0.0);
+
+fn cylindrical(position: Vec3) -> (f64, f64, f64) {
+    let (x, y, z) = (position.x as f64, position.y as f64, position.z as f64);
+    ((x * x + z * z).sqrt(), z.atan2(x), y)
+}
+
+/// Proximité de l'axe du bras spiral le plus proche : 1 sur l'axe, tend vers 0 entre les bras
+pub fn arm_proximity(position: Vec3) -> f64 {
+    let (radius, azimuth, _) = cylindrical(position);
+    if radius < 1.0 {
+        return 0.0;
+    }
+    // Azimut de l'axe d'un bras au rayon `radius` : r = r0·exp(θ·tan(pitch))
+    let arm_azimuth = (radius / ARM_REFERENCE_RADIUS).ln() / ARM_PITCH_ANGLE.tan();
+    // Écart angulaire au bras le plus proche, les bras étant espacés de 2π / ARM_COUNT
+    let phase = (ARM_COUNT * (azimuth - arm_azimuth)).rem_euclid(TAU);
+    let angle = phase.min(TAU - phase) / ARM_COUNT;
+    // Distance perpendiculaire à l'axe du bras
+    let distance = radius * angle * ARM_PITCH_ANGLE.sin();
+    (-(distance / ARM_WIDTH).powi(2)).exp()
+}
+
+/// Densité de systèmes stellaires (par parsec cube) à `position`
+pub fn stellar_density(position: Vec3) -> f64 {
+    let (radius, _, height) = cylindrical(position);
+    let distance = (radius * radius + height * height).sqrt();
+    let bulge = BULGE_CENTRAL_DENSITY * (-(distance / BULGE_RADIUS).powi(2)).exp();
+    if radius > GALAXY_RADIUS {
+        return bulge;
+    }
+    let arms = 1.0 + ARM_CONTRAST * (2.0 * arm_proximity(position) - 1.0);
+    let disk = DISK_CENTRAL_DENSITY * (-radius / DISK_SCALE_LENGTH).exp() * (-height.abs() / DISK_SCALE_HEIGHT).exp();
+    disk * arms + bulge
+}
+
+/// Environnement galactique entre 0 (halo, périphérie) et 1 (centre, bras spiraux),
+/// entrée `galactic_region` de `generate_star_composition`
+pub fn galactic_region(position: Vec3) -> f64 {
+    let (radius, _, height) = cylindrical(position);
+    let in_plane = (-height.abs() / DISK_SCALE_HEIGHT).exp();
+    let central = (-radius / DISK_SCALE_LENGTH).exp();
+    (central.max(arm_proximity(position)) * in_plane).clamp(0.0, 1.0)
+}
+
+/// Environnement de formation d'un système, entrées de `generate_star_composition` (entre 0 et 1)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GalacticContext {
+    pub supernovae_proximity: f64,
+    pub galactic_history: f64,
+    pub galactic_region: f64,
+}
+
+impl GalacticContext {
+    pub fn at(position: Vec3) -> Self {
+        let (radius, _, height) = cylindrical(position);
+        Self {
+            supernovae_proximity: arm_proximity(position) * (-height.abs() / SUPERNOVA_SCALE_HEIGHT).exp(),
+            galactic_history: (-radius / HISTORY_SCALE_LENGTH).exp() * (-height.abs() / HISTORY_SCALE_HEIGHT).exp(),
+            galactic_region: galactic_region(position),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(radius: f32, azimuth: f32, height: f32) -> Vec3 {
+        Vec3::new(radius * azimuth.cos(), height, radius * azimuth.sin())
+    }
+
+    #[test]
+    fn test_density_profile() {
+        // ~0.1 système par parsec cube autour du Soleil
+        let solar = stellar_density(SOLAR_POSITION);
+        assert!(solar > 0.04 && solar < 0.2, "densité solaire {}", solar);
+        assert!(stellar_density(Vec3::ZERO) > 50.0 * solar, "densité centrale {}", stellar_density(Vec3::ZERO));
+        assert!(stellar_density(at(8200.0, 0.0, 1000.0)) < 0.1 * solar);
+        assert!(stellar_density(at(14000.0, 0.0, 0.0)) < solar);
+        assert!(stellar_density(at(20000.0, 0.0, 0.0)) < 1.0e-6);
+    }
+
+    #[test]
+    fn test_spiral_arms() {
+        // Le long d'un cercle, la densité oscille entre les bras et l'espace inter-bras
+        let densities: Vec<f64> = (0..360).map(|i| stellar_density(at(6000.0, (i as f32).to_radians(), 0.0))).collect();
+        let max = densities.iter().cloned().fold(f64::MIN, f64::max);
+        let min = densities.iter().cloned().fold(f64::MAX, f64::min);
+        assert!(max / min > 2.0, "contraste {}", max / min);
+        let proximities: Vec<f64> = (0..360).map(|i| arm_proximity(at(6000.0, (i as f32).to_radians(), 0.0))).collect();
+        assert!(proximities.iter().all(|p| (0.0..=1.0).contains(p)));
+        assert!(proximities.iter().any(|&p| p > 0.99));
+        // Quatre bras : autant de maxima locaux sur le cercle
+        let peaks = (0..360).filter(|&i| proximities[i] > proximities[(i + 359) % 360] && proximities[i] >= proximities[(i + 1) % 360]).count();
+        assert_eq!(peaks, ARM_COUNT as usize);
+    }
+
+    #[test]
+    fn test_galactic_region() {
+        assert!(galactic_region(Vec3::ZERO) > 0.99);
+        assert!(galactic_region(at(8000.0, 0.0, 3000.0)) < 0.01);
+        let region = galactic_region(SOLAR_POSITION);
+        assert!((0.0..=1.0).contains(&region));
+    }
+
+    #[test]
+    fn test_galactic_context() {
+        let center = GalacticContext::at(at(500.0, 0.0, 0.0));
+        let solar = GalacticContext::at(SOLAR_POSITION);
+        let outskirts = GalacticContext::at(at(14000.0, 0.0, 0.0));
+        let halo = GalacticContext::at(at(8200.0, 0.0, 4000.0));
+        assert!(center.galactic_history > solar.galactic_history && solar.galactic_history > outskirts.galactic_history);
+        assert!(halo.galactic_history < 0.1 * solar.galactic_history);
+        assert!(halo.supernovae_proximity < 1.0e-6);
+        for context in [center, solar, outskirts, halo] {
+            for value in [context.supernovae_proximity, context.galactic_history, context.galactic_region] {
+                assert!((0.0..=1.0).contains(&value));
+            }
+        }
+    }
+}
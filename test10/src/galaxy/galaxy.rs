@@ -0,0 +1,179 @@
+use glam::{IVec3, Vec3};
+
+use super::density::{stellar_density, GalacticContext};
+use crate::stellar_system::seed::{sector_of, Seed, SECTOR_SIZE};
+use crate::stellar_system::stellar_system::{StarPhysicalProperties, RNG};
+use crate::stellar_system::StellarSystem;
+
+/// Emplacement d'un système dans la galaxie : de quoi le générer, sans rien générer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SystemLocation {
+    pub seed: Seed,
+    pub sector: IVec3,
+    /// Rang du système dans son secteur, qui le numérote au catalogue
+    pub index: u64,
+    pub position: Vec3, // en parsecs
+}
+
+/// Propriétés grossières d'un système, tirées de sa graine sans générer orbites ni planètes
+#[derive(Debug, Clone)]
+pub struct SystemSummary {
+    pub location: SystemLocation,
+    pub star_count: usize,
+    /// Type spectral de l'étoile la plus lumineuse
+    pub dominant_spectral_type: String,
+    pub luminosity: f64,  // luminosité totale, en luminosités solaires
+    pub temperature: f64, // température de l'étoile la plus lumineuse, en Kelvin
+    pub metallicity: f64, // fraction de masse en métaux de l'étoile la plus lumineuse
+}
+
+/// Galaxie procédurale : champ de densité découpé en secteurs, peuplés à la demande
+pub struct Galaxy {
+    seed: Seed,
+}
+
+impl Galaxy {
+    pub fn new(universe_seed: u64) -> Self {
+        Self { seed: Seed::universe(universe_seed) }
+    }
+
+    /// Nombre moyen de systèmes d'un secteur, d'après la densité en son centre
+    pub fn expected_count(sector: IVec3) -> f64 {
+        let center = (sector.as_vec3() + Vec3::splat(0.5)) * SECTOR_SIZE;
+        stellar_density(center) * (SECTOR_SIZE as f64).powi(3)
+    }
+
+    /// Systèmes du secteur `sector`, dans un ordre stable
+    pub fn sector(&self, sector: IVec3) -> Vec<SystemLocation> {
+        let sector_seed = self.seed.sector(sector);
+        let mut rng = RNG::from_seed(sector_seed.derive("census", 0).value());
+        // Arrondi aléatoire : le nombre moyen de systèmes reste celui du champ de densité
+        let expected = Self::expected_count(sector);
+        let count = expected.floor() as u64 + (rng.gen_norm() < expected.fract()) as u64;
+        let origin = sector.as_vec3() * SECTOR_SIZE;
+        (0..count).map(|i| {
+            let offset = Vec3::new(rng.gen_norm() as f32, rng.gen_norm() as f32, rng.gen_norm() as f32);
+            SystemLocation {
+                seed: sector_seed.system(i),
+                sector,
+                index: i,
+                position: origin + offset * SECTOR_SIZE,
+            }
+        }).collect()
+    }
+
+    /// Systèmes situés à moins de `radius` parsecs de `position`
+    pub fn locations_near(&self, position: Vec3, radius: f32) -> Vec<SystemLocation> {
+        let min = sector_of(position - Vec3::splat(radius));
+        let max = sector_of(position + Vec3::splat(radius));
+        let mut locations = Vec::new();
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                for z in min.z..=max.z {
+                    locations.extend(self.sector(IVec3::new(x, y, z)).into_iter()
+                        .filter(|location| location.position.distance(position) <= radius));
+                }
+            }
+        }
+        locations
+    }
+
+    /// Système le plus proche de `position`, dans un rayon de `radius` parsecs
+    pub fn nearest(&self, position: Vec3, radius: f32) -> Option<SystemLocation> {
+        self.locations_near(position, radius).into_iter()
+            .min_by(|a, b| a.position.distance(position).total_cmp(&b.position.distance(position)))
+    }
+
+    pub fn summarize(&self, location: &SystemLocation) -> SystemSummary {
+        let (_, stars) = StellarSystem::preview_stars(location.seed, &GalacticContext::at(location.position));
+        let brightest: &StarPhysicalProperties = stars.iter()
+            .max_by(|a, b| a.luminosity.total_cmp(&b.luminosity))
+            .expect("un système compte au moins une étoile");
+        SystemSummary {
+            location: *location,
+            star_count: stars.len(),
+            dominant_spectral_type: brightest.spectral_type.clone(),
+            luminosity: stars.iter().map(|star| star.luminosity).sum(),
+            temperature: brightest.temperature,
+            metallicity: brightest.metallicity,
+        }
+    }
+
+    pub fn systems_near(&self, position: Vec3, radius: f32) -> Vec<SystemSummary> {
+        self.locations_near(position, radius).iter().map(|location| self.summarize(location)).collect()
+    }
+
+    /// Système complet (orbites, planètes, lunes) situé à `location`
+    pub fn instantiate(&self, location: &SystemLocation) -> StellarSystem {
+        StellarSystem::from_seed(location.seed, location.sector, location.index, &GalacticContext::at(location.position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::galaxy::density::SOLAR_POSITION;
+    use crate::stellar_system::seed::DEFAULT_UNIVERSE_SEED;
+    use crate::stellar_system::CelestialBody;
+
+    #[test]
+    fn test_sectors_are_deterministic() {
+        let galaxy = Galaxy::new(DEFAULT_UNIVERSE_SEED);
+        let sector = sector_of(SOLAR_POSITION);
+        let systems = galaxy.sector(sector);
+        assert!(!systems.is_empty());
+        assert_eq!(systems, Galaxy::new(DEFAULT_UNIVERSE_SEED).sector(sector));
+        assert_ne!(systems, Galaxy::new(1).sector(sector));
+        for location in &systems {
+            assert_eq!(sector_of(location.position), sector);
+        }
+        // Désignations uniques dans le secteur
+        let designations: std::collections::HashSet<String> = systems.iter()
+            .map(|location| galaxy.instantiate(location).designation)
+            .collect();
+        assert_eq!(designations.len(), systems.len());
+    }
+
+    #[test]
+    fn test_counts_follow_density() {
+        let galaxy = Galaxy::new(DEFAULT_UNIVERSE_SEED);
+        let origin = sector_of(SOLAR_POSITION);
+        let sectors: Vec<IVec3> = (0..20).flat_map(|x| (0..20).map(move |z| origin + IVec3::new(x, 0, z))).collect();
+        let expected: f64 = sectors.iter().map(|&sector| Galaxy::expected_count(sector)).sum();
+        let actual: usize = sectors.iter().map(|&sector| galaxy.sector(sector).len()).sum();
+        assert!((actual as f64 - expected).abs() < 0.01 * expected, "{} systèmes pour {} attendus", actual, expected);
+        // Hors de la galaxie, les secteurs sont vides
+        assert!(galaxy.sector(IVec3::new(3000, 0, 0)).is_empty());
+    }
+
+    #[test]
+    fn test_summary_matches_full_system() {
+        let galaxy = Galaxy::new(DEFAULT_UNIVERSE_SEED);
+        for location in galaxy.sector(sector_of(SOLAR_POSITION)).iter().take(10) {
+            let summary = galaxy.summarize(location);
+            let system = galaxy.instantiate(location);
+            let stars: Vec<_> = system.bodies.iter().filter_map(|body| match body {
+                CelestialBody::Star(star) => Some(&star.physical_props),
+                _ => None,
+            }).collect();
+            assert_eq!(summary.star_count, stars.len());
+            let brightest = stars.iter().max_by(|a, b| a.luminosity.total_cmp(&b.luminosity)).unwrap();
+            assert_eq!(summary.dominant_spectral_type, brightest.spectral_type);
+            assert_eq!(summary.metallicity, brightest.metallicity);
+        }
+    }
+
+    #[test]
+    fn test_browse_neighborhood() {
+        // Des milliers de systèmes résumés sans en instancier aucun
+        let galaxy = Galaxy::new(DEFAULT_UNIVERSE_SEED);
+        let summaries = galaxy.systems_near(SOLAR_POSITION, 25.0);
+        assert!(summaries.len() > 2000, "{} systèmes", summaries.len());
+        assert!(summaries.iter().all(|summary| summary.location.position.distance(SOLAR_POSITION) <= 25.0));
+        // Les naines rouges dominent
+        let red_dwarfs = summaries.iter().filter(|summary| summary.dominant_spectral_type == "M").count();
+        assert!(red_dwarfs > summaries.len() / 2);
+        let nearest = galaxy.nearest(SOLAR_POSITION, 10.0).unwrap();
+        assert!(summaries.iter().all(|s| s.location.position.distance(SOLAR_POSITION) >= nearest.position.distance(SOLAR_POSITION)));
+    }
+}
@@ -0,0 +1,4 @@
+pub mod galaxy;
+pub mod density;
+
+pub use galaxy::{Galaxy, SystemLocation, SystemSummary};
\ No newline at end of file
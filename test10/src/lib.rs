@@ -37,6 +37,9 @@ fn time_diff_ms(start: TimeStamp, end: TimeStamp) -> f64 {
     end.duration_since(start).as_millis() as f64
 }
 
+// Temps simulé écoulé à chaque frame, en secondes (6 heures)
+const SIMULATION_SECONDS_PER_FRAME: f64 = 6.0 * 3600.0;
+
 pub mod geometry {
     pub mod icosphere;
     pub mod kdtree3d;
@@ -44,20 +47,30 @@ pub mod geometry {
 }
 
 mod camera;
-mod stellar_system;
+pub mod stellar_system;
+pub mod galaxy;
 pub mod celestial_body;
 pub mod time;
 pub mod manager;
 
 use celestial_body::planet::planet_geometry::{PlanetGeometry, PlanetVertex};
-use celestial_body::planet::render_pipeline::planet_render_pipeline;
+use celestial_body::planet::terrain_parameters::TerrainParameters;
+use celestial_body::planet::render_pipeline::{create_depth_view, planet_render_pipeline, terrain_render_pipeline};
 use celestial_body::star::render_pipeline::star_render_pipeline;
+use celestial_body::ring::render_pipeline::ring_render_pipeline;
+use celestial_body::ring::ring_geometry::RingHandle;
+use celestial_body::asteroid::asteroid_field::AsteroidField;
+use celestial_body::starfield::render_pipeline::{sky_band_render_pipeline, star_point_render_pipeline};
+use celestial_body::starfield::starfield_geometry::Starfield;
 use celestial_body::geometry_loader::{CelestialBodyHandle, CelestialBodyGeometry};
 use camera::{Camera, CameraUniform, CameraController};
 use stellar_system::{CelestialBody, StellarSystem};
-use camera::init::init_camera_scene;
+use stellar_system::seed::{sector_of, system_index_at, Seed, DEFAULT_UNIVERSE_SEED};
+use galaxy::{Galaxy, SystemLocation};
+use galaxy::density::SOLAR_POSITION;
+use camera::init::{init_camera_scene, init_relative_camera};
 use time::time::init_time_scene;
-use manager::manager::Manager;
+use manager::manager::{LoadConfig, Manager};
 
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
@@ -79,8 +92,8 @@ pub fn screen_to_ray(
     let inv_view_proj = camera.build_view_projection_matrix().inverse();
     let world_pos = inv_view_proj * ndc;
     let world_pos = world_pos.truncate() / world_pos.w;
-    let ray_origin = camera.eye;
-    let ray_dir = (world_pos - camera.eye).normalize();
+    let ray_origin = camera.eye.as_vec3();
+    let ray_dir = (world_pos - ray_origin).normalize();
     (ray_origin, ray_dir)
 }
 
@@ -106,8 +119,15 @@ pub struct State {
     camera_uniform: CameraUniform,
     camera_buffer: wgpu::Buffer,
     camera_bind_group: wgpu::BindGroup,
+    // Caméra à l'origine et profondeur de la passe des morceaux de terrain
+    terrain_camera_uniform: CameraUniform,
+    terrain_camera_buffer: wgpu::Buffer,
+    terrain_camera_bind_group: wgpu::BindGroup,
+    depth_view: wgpu::TextureView,
     window: Arc<Window>,
     manager: Manager,
+    system: StellarSystem,
+    simulation_time: f64,
     pub time_uniform_group: TimeUniformGroup,
     // Pour la gestion de la souris FPS
     last_mouse_pos: Option<winit::dpi::PhysicalPosition<f64>>,
@@ -213,14 +233,64 @@ impl State {
             &config
         );
 
-        let system = StellarSystem::new(glam::Vec3::new(0.0,0.0,0.0));
+        let render_pipeline_ring = ring_render_pipeline(
+            &device,
+            &[
+                &camera_bind_group_layout,
+            ],
+            &config
+        );
+
+        let render_pipeline_sky_band = sky_band_render_pipeline(
+            &device,
+            &[
+                &camera_bind_group_layout,
+            ],
+            &config
+        );
+
+        let render_pipeline_star_point = star_point_render_pipeline(
+            &device,
+            &[
+                &camera_bind_group_layout,
+            ],
+            &config
+        );
+
+        let render_pipeline_terrain = terrain_render_pipeline(
+            &device,
+            &[
+                &camera_bind_group_layout,
+            ],
+            &config
+        );
+        let (
+            terrain_camera_uniform,
+            terrain_camera_buffer,
+            terrain_camera_bind_group
+        ) = init_relative_camera(&device, &camera_bind_group_layout, camera_uniform.aspect_ratio);
+        let depth_view = create_depth_view(&device, &config);
+
+        // Système le plus proche de la position du Soleil dans la galaxie
+        let galaxy = Galaxy::new(DEFAULT_UNIVERSE_SEED);
+        let home = galaxy.nearest(SOLAR_POSITION, 20.0).unwrap_or(SystemLocation {
+            seed: Seed::universe(DEFAULT_UNIVERSE_SEED).system_at(SOLAR_POSITION),
+            sector: sector_of(SOLAR_POSITION),
+            index: system_index_at(SOLAR_POSITION),
+            position: SOLAR_POSITION,
+        });
+        let system = galaxy.instantiate(&home);
 
         let result: Vec<CelestialBodyHandle> = system.bodies.iter().enumerate().map(|(i, body)| {
             match body {
                 CelestialBody::Star(star) => {
                     log::info!("STAR");
                 CelestialBodyHandle::new(
-                    CelestialBodyGeometry::Star(StarGeometry::new(star.physical_props.radius as f32)),
+                    CelestialBodyGeometry::Star(StarGeometry::with_temperature(
+                        star.physical_props.render_radius(),
+                        star.physical_props.temperature,
+                        star.physical_props.stage,
+                    )),
                     glam::Vec3::new(star.position.x as f32, star.position.y as f32, star.position.z as f32),
                     glam::Quat::from_axis_angle(glam::Vec3::Z, 0.0_f32.to_radians()),
                     i as u32
@@ -228,17 +298,46 @@ impl State {
                 CelestialBody::Planet(planet) => {
                     log::info!("PLANET");
                 CelestialBodyHandle::new(
-                    CelestialBodyGeometry::Planet(PlanetGeometry::new(planet.physical_props.radius as f32)),
+                    CelestialBodyGeometry::Planet(PlanetGeometry::with_terrain(
+                        planet.physical_props.radius as f32,
+                        planet.physical_props.class,
+                        TerrainParameters::from_physics(&planet.physical_props),
+                    )),
                     planet.position,
                     glam::Quat::from_axis_angle(glam::Vec3::Z, 0.0_f32.to_radians()),
                     i as u32
                 )},
+                CelestialBody::Moon(moon) => {
+                    log::info!("MOON");
+                CelestialBodyHandle::new(
+                    CelestialBodyGeometry::Planet(PlanetGeometry::with_terrain(
+                        moon.physical_props.radius as f32,
+                        moon.physical_props.class,
+                        TerrainParameters::from_physics(&moon.physical_props),
+                    )),
+                    moon.position,
+                    glam::Quat::from_axis_angle(glam::Vec3::Z, 0.0_f32.to_radians()),
+                    i as u32
+                )},
             }
         }).collect();
 
+        let rings: Vec<RingHandle> = system.bodies.iter().enumerate().filter_map(|(i, body)| match body {
+            CelestialBody::Planet(planet) => planet.rings.as_ref().map(|rings| {
+                RingHandle::new(&device, planet.physical_props.radius as f32, rings, planet.position, i as u32)
+            }),
+            _ => None,
+        }).collect();
+
         log::info!("Taille du Vec<PlanetHandle> : {}", result.len());
 
-        let manager = Manager::new(result);
+        let asteroids: Vec<AsteroidField> = system.asteroid_belt.iter()
+            .map(|belt| AsteroidField::new(&device, belt))
+            .collect();
+
+        let starfield = Starfield::new(&device, &galaxy, &home);
+
+        let manager = Manager::new(result, rings, asteroids, starfield, LoadConfig::default());
 
 
       Ok(Self {
@@ -247,14 +346,27 @@ impl State {
             queue,
             config,
             is_surface_configured: false,
-            render_pipeline: vec![render_pipeline_planet, render_pipeline_star],
+            render_pipeline: vec![
+                render_pipeline_planet,
+                render_pipeline_star,
+                render_pipeline_ring,
+                render_pipeline_sky_band,
+                render_pipeline_star_point,
+                render_pipeline_terrain,
+            ],
             camera,
             camera_controller,
             camera_buffer,
             camera_bind_group,
             camera_uniform,
+            terrain_camera_uniform,
+            terrain_camera_buffer,
+            terrain_camera_bind_group,
+            depth_view,
             window,
             manager,
+            system,
+            simulation_time: 0.0,
             time_uniform_group,
             last_mouse_pos: None,
             mouse_pressed: false,
@@ -272,6 +384,7 @@ impl State {
             self.config.height = height;
             self.camera_uniform.aspect_ratio = width as f32 / height as f32;
             self.surface.configure(&self.device, &self.config);
+            self.depth_view = create_depth_view(&self.device, &self.config);
 
             self.camera.aspect = self.config.width as f32 / self.config.height as f32;
         }
@@ -361,24 +474,59 @@ impl State {
 
         // Optimisation mise en cache des Matrices et utilisation de timestamp pour reprendre sur element non visible non compute par frame
 
+        // Corps survolé avant son déplacement : la caméra le suit
+        let anchor = self.manager.terrain_anchor();
+
+        self.simulation_time += SIMULATION_SECONDS_PER_FRAME;
+        self.system.update(self.simulation_time);
+
         for planet_instance in &mut self.manager.planet_instances {
+            if let Some(body) = self.system.bodies.get(planet_instance.id as usize) {
+                planet_instance.instance.set_position(body.position());
+            }
             planet_instance.instance.update_rotation(0.01, 0.0);
-            planet_instance.recompute_instance(&self.device);
+        }
+        for ring in &mut self.manager.ring_instances {
+            if let Some(body) = self.system.bodies.get(ring.host as usize) {
+                ring.set_position(&self.queue, body.position());
+            }
+        }
+        if let Some(belt) = &self.system.asteroid_belt {
+            for field in &mut self.manager.asteroid_fields {
+                field.update(&self.queue, belt, self.simulation_time);
+            }
+        }
+        if let Some((id, position, rotation)) = anchor {
+            if let Some(planet_instance) = self.manager.planet_instances.iter().find(|p| p.id == id) {
+                let moved = (planet_instance.instance.get_position(), planet_instance.instance.get_rotation());
+                self.camera_controller.carry(&mut self.camera, (position, rotation), moved);
+            }
         }
 
-        self.camera_uniform.get_view_proj();
-        let mat4 = CameraUniform::mat4_from_array(self.camera_uniform.get_view_proj());
-        let planes = Camera::extract_frustum_planes(&mat4);
+        let planes = Camera::extract_frustum_planes(&self.camera.build_view_projection_matrix());
         self.manager.set_planes(planes);
-        self.manager.check_visibility_cluster(&self.device);
+        self.manager.check_visibility_cluster(&self.device, &self.camera, self.config.height as f32);
+        self.manager.write_instances(&self.device, &self.queue);
 
+        self.camera_controller.adapt_speed_to_altitude(self.manager.altitude(&self.camera));
         self.camera_controller.update_camera(&mut self.camera);
-        self.camera_uniform.update_view_proj(&self.camera);
+        // Plan proche commun aux deux passes : leurs profondeurs se comparent dans le même tampon
+        let terrain_near = self.manager.prepare_terrain(&self.device, &self.queue, &self.camera);
+        let znear = terrain_near.unwrap_or(self.camera.znear);
+        self.camera_uniform.set_view_proj(self.camera.build_render_view_projection_matrix(znear));
         self.queue.write_buffer(
             &self.camera_buffer,
             0,
             bytemuck::cast_slice(&[self.camera_uniform]),
         );
+        if terrain_near.is_some() {
+            self.terrain_camera_uniform.set_view_proj(self.camera.build_relative_view_projection_matrix(znear));
+            self.queue.write_buffer(
+                &self.terrain_camera_buffer,
+                0,
+                bytemuck::cast_slice(&[self.terrain_camera_uniform]),
+            );
+        }
     }
 
     fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
@@ -406,17 +554,21 @@ impl State {
                     view: &view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.1,
-                            g: 0.2,
-                            b: 0.3,
-                            a: 1.0,
-                        }),
+                        // Fond noir : la voûte céleste s'y ajoute
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
                         store: wgpu::StoreOp::Store,
                     },
                     depth_slice: None
                 })],
-                depth_stencil_attachment: None,
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth_view,
+                    // Profondeur inversée : 0 à l'infini ; gardée pour la passe des morceaux
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(0.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
                 occlusion_query_set: None,
                 timestamp_writes: None,
             });
@@ -428,6 +580,34 @@ impl State {
             );
         }
 
+        // Planète survolée : ses morceaux testés contre la profondeur des corps déjà dessinés,
+        // qui restent devant eux s'ils sont plus proches
+        if self.manager.terrain_drawn() {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Terrain Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                    depth_slice: None
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                }),
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+            self.manager.render_terrain(&mut render_pass, &self.render_pipeline[5], &self.terrain_camera_bind_group);
+        }
+
         self.queue.submit(iter::once(encoder.finish()));
         output.present();
 
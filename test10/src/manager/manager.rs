@@ -1,24 +1,91 @@
-use crate::celestial_body::geometry_loader::CelestialBodyHandle;
-use crate::camera::Plane;
+use crate::celestial_body::generation::{GenerationEvent, GeneratedGeometry, GeometryPool, GEOMETRY_QUEUE_CAPACITY, GEOMETRY_WORKERS, MESH_BUDGET_BYTES};
+use crate::celestial_body::geometry_loader::{CelestialBodyHandle, CelestialInstance, SharedMeshes, MAX_GENERATION_ATTEMPTS};
+use crate::celestial_body::instance_buffer::{batches, InstanceBuffer};
+use crate::celestial_body::job_queue::JobId;
+use crate::celestial_body::load_priority::LoadPriority;
+use crate::celestial_body::residency::{Residency, ResidencyStats};
+use crate::celestial_body::ring::ring_geometry::RingHandle;
+use crate::celestial_body::asteroid::asteroid_field::AsteroidField;
+use crate::celestial_body::starfield::starfield_geometry::Starfield;
+use crate::celestial_body::lod::screen_diameter;
+use crate::celestial_body::planet::planet_terrain::{local_eye, TERRAIN_DISTANCE};
+use crate::celestial_body::planet::terrain_chunk::ChunkKey;
+use crate::camera::{Camera, Plane};
+use glam::{Quat, Vec3};
+use std::collections::HashSet;
+use std::ops::Range;
 use wgpu::RenderPipeline;
 
+/// Réglages du chargement des géométries
+#[derive(Debug, Clone, Copy)]
+pub struct LoadConfig {
+    /// Générations menées en parallèle
+    pub workers: usize,
+    /// Générations pouvant attendre un worker
+    pub queue_capacity: usize,
+    /// Mémoire GPU allouée aux maillages des corps et aux morceaux de terrain, en octets
+    pub mesh_budget: u64,
+}
+
+impl Default for LoadConfig {
+    fn default() -> Self {
+        Self { workers: GEOMETRY_WORKERS, queue_capacity: GEOMETRY_QUEUE_CAPACITY, mesh_budget: MESH_BUDGET_BYTES }
+    }
+}
+
+// Maillage suivi en mémoire GPU : propre à une planète, commun aux étoiles d'un même LOD,
+// ou morceau du terrain d'une planète
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum MeshKey {
+    Body(u32, usize),
+    Star(usize),
+    Chunk(u32, ChunkKey),
+}
+
+fn mesh_key(planet_handle: &CelestialBodyHandle, lod: usize) -> MeshKey {
+    if planet_handle.get_type() == 1 { MeshKey::Star(lod) } else { MeshKey::Body(planet_handle.id, lod) }
+}
+
 pub struct Manager {
     pub planet_instances: Vec<CelestialBodyHandle>,
-    pub buffer_loader: Vec<u32>,
+    pub ring_instances: Vec<RingHandle>,
+    pub asteroid_fields: Vec<AsteroidField>,
+    pub starfield: Starfield,
+    generation: GeometryPool,
+    // Maillages des corps en mémoire GPU
+    residency: Residency<MeshKey>,
+    star_meshes: SharedMeshes,
+    // Instances de l'image, une plage par appel de dessin : une instance par planète,
+    // toutes les étoiles d'un même LOD ensemble
+    planet_instance_buffer: InstanceBuffer,
+    star_instance_buffer: InstanceBuffer,
+    planet_draws: Vec<(usize, u32)>,
+    star_draws: Vec<(usize, Range<u32>)>,
+    // Anneaux du plus lointain au plus proche de la caméra
+    ring_order: Vec<usize>,
     planes: [Plane; 6],
-    pub in_computing: bool,
-    pub id_in_computing: u32
+    // Planète survolée, dessinée par morceaux de terrain
+    terrain_focus: Option<usize>,
 }
 
 impl Manager {
 
-    pub fn new(planets: Vec<CelestialBodyHandle>) -> Self {
+    pub fn new(planets: Vec<CelestialBodyHandle>, rings: Vec<RingHandle>, asteroids: Vec<AsteroidField>, starfield: Starfield, config: LoadConfig) -> Self {
         Manager {
             planet_instances: planets,
-            buffer_loader: Vec::new(),
+            ring_instances: rings,
+            asteroid_fields: asteroids,
+            starfield,
+            generation: GeometryPool::new(config.workers, config.queue_capacity),
+            residency: Residency::new(config.mesh_budget),
+            star_meshes: SharedMeshes::new(),
+            planet_instance_buffer: InstanceBuffer::new("Planet Instance Buffer"),
+            star_instance_buffer: InstanceBuffer::new("Star Instance Buffer"),
+            planet_draws: Vec::new(),
+            star_draws: Vec::new(),
+            ring_order: Vec::new(),
             planes: [Plane::default(); 6],
-            in_computing: false,
-            id_in_computing: 0
+            terrain_focus: None,
         }
     }
 
@@ -27,42 +94,234 @@ impl Manager {
         self.planes = planes;
     }
 
-    pub fn check_visibility_cluster(&mut self, device: &wgpu::Device)
+    pub fn check_visibility_cluster(&mut self, device: &wgpu::Device, camera: &Camera, viewport_height: f32)
     {
+        self.residency.begin_frame();
         for planet_instance in &mut self.planet_instances {
             let mut visible = true;
-            for plane in &self.planes {
+            // Le plan lointain (le dernier) est ignoré : la passe principale projette jusqu'à
+            // l'infini, un corps n'est jamais trop loin pour être dessiné
+            for plane in &self.planes[..5] {
                 if plane.normal.dot(planet_instance.instance.get_position()) + plane.d < -1.5 {
                     visible = false;
                     break;
                 }
             }
             planet_instance.is_visible = visible;
-            if visible && !planet_instance.is_ready()
+            if visible
             {
-                self.buffer_loader.push(planet_instance.id)
+                let position = planet_instance.instance.get_position();
+                let radius = planet_instance.body.borrow().radius();
+                let distance = camera.eye.as_vec3().distance(position);
+                let diameter = screen_diameter(radius, distance, camera.fovy.to_radians(), viewport_height);
+                let priority = LoadPriority::new(diameter, distance);
+                planet_instance.set_priority(priority, &mut self.generation);
+                let lod = planet_instance.update_lod(diameter);
+                if let Some(displayed) = planet_instance.displayed_lod() {
+                    self.residency.seen(mesh_key(planet_instance, displayed), priority);
+                }
+                // Le LOD voulu a changé depuis la demande : elle est remplacée
+                if planet_instance.job_lod().is_some_and(|job_lod| job_lod != lod) {
+                    planet_instance.cancel_generation(&mut self.generation);
+                }
+                planet_instance.share_meshes(&self.star_meshes);
+                if planet_instance.needs_generation() {
+                    // File pleine : la demande sera renouvelée à la prochaine image
+                    let _ = planet_instance.generate_async(&mut self.generation, lod);
+                }
+            }
+            else
+            {
+                // Sorti du champ : sa génération est abandonnée et ses maillages libérés en premier
+                planet_instance.set_priority(LoadPriority::LOWEST, &mut self.generation);
+                if planet_instance.job().is_some() {
+                    planet_instance.cancel_generation(&mut self.generation);
+                }
             }
         }
-        if !self.in_computing
-        {
-            if let Some(id) = self.buffer_loader.get(0) {
-                self.id_in_computing = *id;
-                if let Some(planet_handle) = self.planet_instances.iter_mut().find(|p| p.id == *id) {
-                    self.in_computing = true;
-                    planet_handle.generate_async(5);
+
+        // Planète la plus proche, à moins de TERRAIN_DISTANCE rayons : sa surface est découpée
+        // en morceaux affinés autour de la caméra
+        self.terrain_focus = self.planet_instances.iter().enumerate()
+            .filter(|(_, p)| p.terrain.is_some())
+            .map(|(i, p)| (i, camera.eye.distance(p.instance.get_position().as_dvec3()) / p.body.borrow().radius() as f64))
+            .filter(|(_, distance)| *distance < TERRAIN_DISTANCE)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i);
+        let chunk_priority = |radius: f64, distance: f64| {
+            let diameter = screen_diameter(radius as f32, distance as f32, camera.fovy.to_radians(), viewport_height);
+            LoadPriority::new(diameter, distance as f32)
+        };
+        for (index, planet_instance) in self.planet_instances.iter_mut().enumerate() {
+            let (position, rotation) = (planet_instance.instance.get_position(), planet_instance.instance.get_rotation());
+            let Some(terrain) = &mut planet_instance.terrain else { continue };
+            if Some(index) == self.terrain_focus {
+                for (chunk, priority) in terrain.update(local_eye(position, rotation, camera.eye), &mut self.generation, &chunk_priority) {
+                    self.residency.seen(MeshKey::Chunk(planet_instance.id, chunk), priority);
                 }
+            } else {
+                terrain.deactivate(&mut self.generation);
             }
         }
-        else
-        {
-            if let Some(planet_handle) = self.planet_instances.iter_mut().find(|p| p.id == self.id_in_computing) {
-                if planet_handle.upload_if_ready(&device)
-                {
-                    self.in_computing = false;
+
+        // Une demande partagée par plusieurs corps leur est rendue à chacun
+        for event in self.generation.poll() {
+            match event {
+                GenerationEvent::Progress { job, fraction } => {
+                    for planet_handle in self.planet_instances.iter_mut().filter(|p| p.job() == Some(job)) {
+                        planet_handle.set_progress(job, fraction);
+                    }
+                }
+                GenerationEvent::Finished { job, geometry } => {
+                    let mut recipients: Vec<&mut CelestialBodyHandle> = self.planet_instances.iter_mut().filter(|p| awaits(p, job)).collect();
+                    let Some(last) = recipients.pop() else { continue };
+                    for planet_handle in recipients {
+                        deliver(planet_handle, &mut self.residency, &mut self.star_meshes, device, job, geometry.clone());
+                    }
+                    deliver(last, &mut self.residency, &mut self.star_meshes, device, job, geometry);
+                }
+                GenerationEvent::Failed { job, error } => {
+                    for planet_handle in self.planet_instances.iter_mut().filter(|p| awaits(p, job)) {
+                        if let Some(chunk) = planet_handle.terrain.as_mut().and_then(|t| t.fail(job)) {
+                            log::warn!("Génération du morceau {:?} du corps {} échouée : {}", chunk, planet_handle.id, error);
+                        } else if planet_handle.fail(job, error.clone()) {
+                            let (failures, error) = planet_handle.failures();
+                            if failures < MAX_GENERATION_ATTEMPTS {
+                                log::warn!("Génération du corps {} échouée ({}), nouvelle tentative", planet_handle.id, error.unwrap_or_default());
+                            } else {
+                                log::error!("Génération du corps {} abandonnée après {} échecs : {}", planet_handle.id, failures, error.unwrap_or_default());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // Anneaux semi-transparents : les plus lointains d'abord, pour que les plus proches
+        // se mélangent par-dessus
+        let eye = camera.eye.as_vec3();
+        let rings = &self.ring_instances;
+        self.ring_order = (0..rings.len()).collect();
+        self.ring_order.sort_by(|&a, &b| {
+            eye.distance_squared(rings[b].instance.position).total_cmp(&eye.distance_squared(rings[a].instance.position))
+        });
+
+        self.enforce_mesh_budget();
+    }
+
+    // Libère les maillages vus il y a le plus longtemps tant que le budget est dépassé ;
+    // ceux affichés restent, morceaux de terrain compris
+    fn enforce_mesh_budget(&mut self) {
+        let mut displayed: HashSet<MeshKey> = self.planet_instances.iter()
+            .filter(|p| p.is_visible)
+            .filter_map(|p| p.displayed_lod().map(|lod| mesh_key(p, lod)))
+            .collect();
+        for planet_handle in &self.planet_instances {
+            let Some(terrain) = &planet_handle.terrain else { continue };
+            displayed.extend(terrain.drawn().iter().map(|chunk| MeshKey::Chunk(planet_handle.id, *chunk)));
+        }
+        for key in self.residency.evict(|key| displayed.contains(key)) {
+            match key {
+                MeshKey::Body(id, lod) => {
+                    if let Some(planet_handle) = self.planet_instances.iter_mut().find(|p| p.id == id) {
+                        planet_handle.evict(lod);
+                        log::info!("Maillage du corps {} (LOD {}) libéré", id, lod);
+                    }
+                }
+                MeshKey::Star(lod) => {
+                    self.star_meshes.remove(&lod);
+                    for planet_handle in self.planet_instances.iter_mut().filter(|p| p.get_type() == 1) {
+                        planet_handle.evict(lod);
+                    }
+                    log::info!("Maillage commun des étoiles (LOD {}) libéré", lod);
+                }
+                MeshKey::Chunk(id, chunk) => {
+                    if let Some(terrain) = self.planet_instances.iter_mut().find(|p| p.id == id).and_then(|p| p.terrain.as_mut()) {
+                        terrain.evict(chunk);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Réécrit les tampons d'instances partagés d'après les positions de l'image et prépare
+    /// les appels de dessin : un par planète, un par LOD pour toutes les étoiles
+    pub fn write_instances(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let mut planets = Vec::new();
+        let mut stars = Vec::new();
+        self.planet_draws.clear();
+        for (index, planet_instance) in self.planet_instances.iter().enumerate() {
+            // Planète survolée : dessinée par morceaux dans une passe à part
+            if !planet_instance.is_visible || !planet_instance.is_ready() || planet_instance.terrain.as_ref().is_some_and(|t| t.is_drawn()) {
+                continue;
+            }
+            let Some(lod) = planet_instance.displayed_lod() else { continue };
+            match &planet_instance.instance {
+                CelestialInstance::Planet(planet) => {
+                    self.planet_draws.push((index, planets.len() as u32));
+                    planets.push(planet.to_raw());
                 }
-                self.buffer_loader.clear();
+                CelestialInstance::Star(star) => stars.push((lod, star.to_raw())),
             }
         }
+        stars.sort_by_key(|(lod, _)| *lod);
+        let lods: Vec<usize> = stars.iter().map(|(lod, _)| *lod).collect();
+        self.star_draws = batches(&lods);
+        let stars: Vec<_> = stars.into_iter().map(|(_, raw)| raw).collect();
+        self.planet_instance_buffer.write(device, queue, &planets);
+        self.star_instance_buffer.write(device, queue, &stars);
+    }
+
+    /// Mémoire GPU des maillages et mouvements de l'image, pour l'affichage de débogage
+    pub fn residency_stats(&self) -> ResidencyStats {
+        self.residency.stats()
+    }
+
+    pub fn set_mesh_budget(&mut self, budget: u64) {
+        self.residency.set_budget(budget);
+    }
+
+    /// Corps survolé (identifiant, position, rotation), auquel la caméra est attachée
+    pub fn terrain_anchor(&self) -> Option<(u32, Vec3, Quat)> {
+        self.terrain_focus.map(|index| {
+            let planet = &self.planet_instances[index];
+            (planet.id, planet.instance.get_position(), planet.instance.get_rotation())
+        })
+    }
+
+    /// Altitude de la caméra au-dessus du corps le plus proche ; au-dessus du relief pour la planète survolée
+    pub fn altitude(&self, camera: &Camera) -> f32 {
+        self.planet_instances.iter().enumerate().map(|(index, p)| {
+            let (position, rotation) = (p.instance.get_position(), p.instance.get_rotation());
+            match &p.terrain {
+                Some(terrain) if Some(index) == self.terrain_focus => terrain.altitude(local_eye(position, rotation, camera.eye)),
+                _ => camera.eye.distance(position.as_dvec3()) - p.body.borrow().radius() as f64,
+            }
+        }).fold(f64::INFINITY, f64::min) as f32
+    }
+
+    /// Les morceaux de la planète survolée remplacent son maillage entier
+    pub fn terrain_drawn(&self) -> bool {
+        self.terrain_focus.and_then(|index| self.planet_instances[index].terrain.as_ref()).is_some_and(|t| t.is_drawn())
+    }
+
+    /// Place les morceaux de la planète survolée par rapport à la caméra, une fois celle-ci
+    /// déplacée pour l'image, et renvoie le plan proche de leur passe
+    pub fn prepare_terrain(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, camera: &Camera) -> Option<f32> {
+        let planet = &mut self.planet_instances[self.terrain_focus?];
+        let (position, rotation) = (planet.instance.get_position(), planet.instance.get_rotation());
+        let terrain = planet.terrain.as_mut().filter(|t| t.is_drawn())?;
+        let eye = local_eye(position, rotation, camera.eye);
+        terrain.write_instances(device, queue, rotation, eye);
+        Some(terrain.near_plane(eye))
+    }
+
+    /// Passe des morceaux de terrain, avec tampon de profondeur et caméra relative
+    pub fn render_terrain(&self, render_pass: &mut wgpu::RenderPass, pipeline: &RenderPipeline, camera_bind_group: &wgpu::BindGroup) {
+        let Some(terrain) = self.terrain_focus.and_then(|index| self.planet_instances[index].terrain.as_ref()) else { return };
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        terrain.render(render_pass);
     }
 
     pub fn render_visible_object(
@@ -73,31 +332,92 @@ impl Manager {
     
     )
     {
-        for planet_instance in &mut self.planet_instances {
-            if planet_instance.is_visible && planet_instance.is_ready()
-            {
-                if let (Some(vb), Some(ib), Some(jo)) = (&planet_instance.vertex_buffer, &planet_instance.index_buffer, &planet_instance.instance_buffer) {
-                    if planet_instance.get_type() == 1
-                    {
-                        // log::info!("STAR");
-                        render_pass.set_pipeline(&pipeline_render[1]);
-                    }
-                    else
-                    {
-                        render_pass.set_pipeline(&pipeline_render[0]);
-                    }
-                    
-                    render_pass.set_bind_group(0, camera_bind_group, &[]);
-                    if planet_instance.get_type() == 1
-                    {
-                        render_pass.set_bind_group(1, time_bgl, &[]);
-                    }
-                    render_pass.set_vertex_buffer(0, vb.slice(..));
-                    render_pass.set_vertex_buffer(1, jo.slice(..));
-                    render_pass.set_index_buffer(ib.slice(..), wgpu::IndexFormat::Uint32);
-                    render_pass.draw_indexed(0..planet_instance.num_indices, 0, 0..1);
-                }
+        // Voûte céleste en premier : tous les corps du système se dessinent par-dessus
+        let starfield = &self.starfield;
+        render_pass.set_pipeline(&pipeline_render[3]);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, starfield.band_vertex_buffer.slice(..));
+        render_pass.set_index_buffer(starfield.band_index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..starfield.num_band_indices, 0, 0..1);
+        if starfield.num_stars > 0 {
+            render_pass.set_pipeline(&pipeline_render[4]);
+            render_pass.set_vertex_buffer(0, starfield.star_buffer.slice(..));
+            render_pass.draw(0..6, 0..starfield.num_stars);
+        }
+
+        // Étoiles : un appel de dessin instancié par LOD, sur la sphère unité commune
+        if let Some(instances) = self.star_instance_buffer.buffer() {
+            render_pass.set_pipeline(&pipeline_render[1]);
+            render_pass.set_bind_group(0, camera_bind_group, &[]);
+            render_pass.set_bind_group(1, time_bgl, &[]);
+            render_pass.set_vertex_buffer(1, instances.slice(..));
+            for (lod, range) in &self.star_draws {
+                let Some(mesh) = self.star_meshes.get(lod) else { continue };
+                render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                render_pass.draw_indexed(0..mesh.num_indices, 0, range.clone());
+            }
+        }
+
+        if let Some(instances) = self.planet_instance_buffer.buffer() {
+            render_pass.set_pipeline(&pipeline_render[0]);
+            render_pass.set_bind_group(0, camera_bind_group, &[]);
+            render_pass.set_vertex_buffer(1, instances.slice(..));
+            for &(index, instance) in &self.planet_draws {
+                let Some(mesh) = self.planet_instances[index].mesh() else { continue };
+                render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                render_pass.draw_indexed(0..mesh.num_indices, 0, instance..instance + 1);
+            }
+        }
+
+        // Astéroïdes : un appel de dessin instancié par maillage de roche
+        for field in &self.asteroid_fields {
+            for batch in field.batches.iter().filter(|batch| batch.num_instances() > 0) {
+                render_pass.set_pipeline(&pipeline_render[0]);
+                render_pass.set_bind_group(0, camera_bind_group, &[]);
+                render_pass.set_vertex_buffer(0, batch.vertex_buffer.slice(..));
+                render_pass.set_vertex_buffer(1, batch.instance_buffer.slice(..));
+                render_pass.set_index_buffer(batch.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                render_pass.draw_indexed(0..batch.num_indices, 0, 0..batch.num_instances());
+            }
+        }
+
+        // Anneaux après les corps opaques, testés contre leur profondeur : la moitié située
+        // derrière la planète est masquée. Seulement si leur planète est affichée
+        for ring in self.ring_order.iter().map(|&index| &self.ring_instances[index]) {
+            let host_drawn = self.planet_instances.iter()
+                .any(|p| p.id == ring.host && p.is_visible && p.is_ready());
+            if host_drawn {
+                render_pass.set_pipeline(&pipeline_render[2]);
+                render_pass.set_bind_group(0, camera_bind_group, &[]);
+                render_pass.set_vertex_buffer(0, ring.vertex_buffer.slice(..));
+                render_pass.set_vertex_buffer(1, ring.instance_buffer.slice(..));
+                render_pass.set_index_buffer(ring.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                render_pass.draw_indexed(0..ring.num_indices, 0, 0..1);
             }
         }
     }
-}
\ No newline at end of file
+}
+
+// Le corps attend `job`, pour lui-même ou pour un morceau de son terrain
+fn awaits(planet_handle: &CelestialBodyHandle, job: JobId) -> bool {
+    planet_handle.job() == Some(job) || planet_handle.terrain.as_ref().is_some_and(|t| t.has_job(job))
+}
+
+fn deliver(
+    planet_handle: &mut CelestialBodyHandle, residency: &mut Residency<MeshKey>, star_meshes: &mut SharedMeshes,
+    device: &wgpu::Device, job: JobId, geometry: GeneratedGeometry,
+) {
+    if planet_handle.job() == Some(job) {
+        planet_handle.receive(job, geometry);
+        if let Some((lod, bytes)) = planet_handle.upload_if_ready(device, star_meshes) {
+            residency.uploaded(mesh_key(planet_handle, lod), bytes, planet_handle.priority());
+        }
+    } else if let Some(terrain) = &mut planet_handle.terrain {
+        terrain.receive(job, geometry);
+        for (chunk, bytes, priority) in terrain.upload_if_ready(device) {
+            residency.uploaded(MeshKey::Chunk(planet_handle.id, chunk), bytes, priority);
+        }
+    }
+}
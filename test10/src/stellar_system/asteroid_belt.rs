@@ -0,0 +1,195 @@
+use std::f64::consts::PI;
+use glam::{DVec3, Quat, Vec3};
+
+use super::orbit::{gravitational_parameter, Orbit, OrbitalElements};
+use super::multiplicity::PlanetaryZone;
+use super::stellar_system::{RNG, SCENE_UNIT};
+
+// Ligne des glaces d'une étoile de type solaire, en UA (Hayashi 1981) ; elle suit sqrt(L)
+const SOLAR_SNOW_LINE: f64 = 2.7;
+// Demi-largeur de la ceinture, en fraction de son rayon central
+const BELT_HALF_WIDTH: f64 = 0.15;
+// Écart minimal (rapport de demi-grands axes) entre la ceinture et une orbite planétaire
+const BELT_PLANET_CLEARANCE: f64 = 1.2;
+pub const BELT_ASTEROID_COUNT: (u32, u32) = (2000, 5000);
+const ASTEROID_MAX_ECCENTRICITY: f64 = 0.2;
+const ASTEROID_MAX_INCLINATION: f64 = 10.0 * PI / 180.0;
+// Distribution de Dohnanyi : dN/dR ∝ R^-3.5, en km
+const ASTEROID_MIN_RADIUS: f64 = 5.0;
+const ASTEROID_MAX_RADIUS: f64 = 500.0;
+const ASTEROID_SIZE_INDEX: f64 = 2.5;
+// Nombre de maillages de roche partagés par les astéroïdes
+pub const ASTEROID_VARIANTS: u32 = 4;
+
+/// Ligne des glaces (en UA) pour une luminosité en luminosités solaires
+pub fn snow_line(luminosity: f64) -> f64 {
+    SOLAR_SNOW_LINE * luminosity.max(0.0).sqrt()
+}
+
+/// Petit corps de la ceinture, sans masse : il ne perturbe pas les planètes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Asteroid {
+    pub elements: OrbitalElements,
+    pub radius: f64, // en km
+    /// Maillage de roche utilisé, entre 0 et ASTEROID_VARIANTS
+    pub variant: u32,
+    pub orientation: Quat,
+}
+
+/// Ceinture d'astéroïdes partageant le foyer des planètes
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsteroidBelt {
+    pub inner_radius: f64, // en UA
+    pub outer_radius: f64, // en UA
+    /// Repère du foyer, comme pour `PlanetaryZone`
+    pub host: Option<Orbit>,
+    pub central_mass: f64, // en masses solaires
+    /// Graine des maillages de roche
+    pub mesh_seed: u64,
+    pub asteroids: Vec<Asteroid>,
+}
+
+/// Bornes (en UA) d'une ceinture centrée sur `center` si elle tient dans la zone sans croiser d'orbite
+fn belt_bounds(center: f64, zone: &PlanetaryZone, planet_axes: &[f64]) -> Option<(f64, f64)> {
+    let inner = center * (1.0 - BELT_HALF_WIDTH);
+    let outer = center * (1.0 + BELT_HALF_WIDTH);
+    let inside = inner >= zone.min_semi_major_axis && outer <= zone.max_semi_major_axis;
+    let clear = planet_axes.iter()
+        .all(|&a| a * BELT_PLANET_CLEARANCE <= inner || a >= outer * BELT_PLANET_CLEARANCE);
+    if inside && clear { Some((inner, outer)) } else { None }
+}
+
+impl AsteroidBelt {
+    /// Bornes de la ceinture : sur la ligne des glaces si elle est libre, sinon dans le plus grand
+    /// écart entre deux orbites planétaires consécutives
+    pub fn place(zone: &PlanetaryZone, planet_axes: &[f64], luminosity: f64) -> Option<(f64, f64)> {
+        if let Some(bounds) = belt_bounds(snow_line(luminosity), zone, planet_axes) {
+            return Some(bounds);
+        }
+        let mut axes = planet_axes.to_vec();
+        axes.sort_by(f64::total_cmp);
+        let widest = axes.windows(2).max_by(|a, b| (a[1] / a[0]).total_cmp(&(b[1] / b[0])))?;
+        belt_bounds((widest[0] * widest[1]).sqrt(), zone, planet_axes)
+    }
+
+    /// Ceinture placée dans `zone`, None si aucune région libre ne peut l'accueillir
+    pub fn generate(rng: &mut RNG, zone: &PlanetaryZone, planet_axes: &[f64], luminosity: f64) -> Option<Self> {
+        let (inner_radius, outer_radius) = Self::place(zone, planet_axes, luminosity)?;
+        let count = rng.u32(BELT_ASTEROID_COUNT.0, BELT_ASTEROID_COUNT.1);
+        let asteroids = (0..count).map(|_| {
+            let elements = OrbitalElements {
+                semi_major_axis: rng.f64(inner_radius, outer_radius),
+                eccentricity: ASTEROID_MAX_ECCENTRICITY * rng.gen_norm().powi(2),
+                inclination: ASTEROID_MAX_INCLINATION * rng.gen_norm().powi(2),
+                longitude_ascending_node: rng.f64(0.0, 2.0 * PI),
+                argument_periapsis: rng.f64(0.0, 2.0 * PI),
+                mean_anomaly_epoch: rng.f64(0.0, 2.0 * PI),
+            };
+            // Tirage inverse de la loi de puissance, borné au plus gros corps
+            let u = rng.gen_norm().max(1.0e-9);
+            let radius = (ASTEROID_MIN_RADIUS * u.powf(-1.0 / ASTEROID_SIZE_INDEX)).min(ASTEROID_MAX_RADIUS);
+            let axis = Vec3::new(rng.f64(-1.0, 1.0) as f32, rng.f64(-1.0, 1.0) as f32, rng.f64(-1.0, 1.0) as f32);
+            let orientation = Quat::from_axis_angle(axis.try_normalize().unwrap_or(Vec3::Y), rng.f64(0.0, 2.0 * PI) as f32);
+            Asteroid {
+                elements,
+                radius,
+                variant: rng.u32(0, ASTEROID_VARIANTS),
+                orientation,
+            }
+        }).collect();
+
+        Some(Self {
+            inner_radius,
+            outer_radius,
+            host: zone.host.clone(),
+            central_mass: zone.central_mass,
+            mesh_seed: rng.next_u64(),
+            asteroids,
+        })
+    }
+
+    /// Positions de tous les astéroïdes à l'instant `time` (s), en unités de scène.
+    /// Le foyer n'est évalué qu'une fois pour toute la ceinture.
+    pub fn positions(&self, time: f64) -> Vec<Vec3> {
+        let all: Vec<usize> = (0..self.asteroids.len()).collect();
+        self.positions_of(&all, time)
+    }
+
+    /// Positions des astéroïdes d'indices `members` à l'instant `time` (s), en unités de scène
+    pub fn positions_of(&self, members: &[usize], time: f64) -> Vec<Vec3> {
+        let focus = self.host.as_ref().map_or(DVec3::ZERO, |host| host.state_at(time).0);
+        let mu = gravitational_parameter(self.central_mass);
+        members.iter()
+            .map(|&i| ((focus + self.asteroids[i].elements.state_at(time, mu).0) / SCENE_UNIT).as_vec3())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::multiplicity::PlanetOrbitType;
+    use super::super::stellar_system::ASTRONOMICAL_UNIT;
+
+    fn zone() -> PlanetaryZone {
+        PlanetaryZone {
+            orbit_type: PlanetOrbitType::SType,
+            host: None,
+            central_mass: 1.0,
+            min_semi_major_axis: 0.3,
+            max_semi_major_axis: 25.0,
+        }
+    }
+
+    #[test]
+    fn test_snow_line() {
+        assert!((snow_line(1.0) - 2.7).abs() < 1.0e-12);
+        assert!((snow_line(4.0) - 5.4).abs() < 1.0e-12);
+    }
+
+    #[test]
+    fn test_belt_on_free_snow_line() {
+        let (inner, outer) = AsteroidBelt::place(&zone(), &[0.7, 1.0, 1.5, 5.2], 1.0).unwrap();
+        assert!(inner < 2.7 && outer > 2.7);
+    }
+
+    #[test]
+    fn test_belt_avoids_planets() {
+        // Planète sur la ligne des glaces : la ceinture va dans le plus grand écart
+        let axes = [1.0, 2.7, 4.0, 16.0];
+        let (inner, outer) = AsteroidBelt::place(&zone(), &axes, 1.0).unwrap();
+        assert!(inner > 4.0 * BELT_PLANET_CLEARANCE && outer * BELT_PLANET_CLEARANCE < 16.0);
+        // Orbites trop serrées : pas de ceinture
+        assert!(AsteroidBelt::place(&zone(), &[1.0, 1.4, 2.0, 2.7, 3.8], 1.0).is_none());
+    }
+
+    #[test]
+    fn test_asteroids_inside_belt() {
+        let mut rng = RNG::from_seed(7);
+        let belt = AsteroidBelt::generate(&mut rng, &zone(), &[1.0], 1.0).unwrap();
+        let count = belt.asteroids.len() as u32;
+        assert!((BELT_ASTEROID_COUNT.0..BELT_ASTEROID_COUNT.1).contains(&count));
+        for asteroid in &belt.asteroids {
+            let a = asteroid.elements.semi_major_axis;
+            assert!(a >= belt.inner_radius && a <= belt.outer_radius);
+            assert!((ASTEROID_MIN_RADIUS..=ASTEROID_MAX_RADIUS).contains(&asteroid.radius));
+            assert!(asteroid.variant < ASTEROID_VARIANTS);
+        }
+        // Beaucoup de petits corps, peu de gros
+        let small = belt.asteroids.iter().filter(|a| a.radius < 2.0 * ASTEROID_MIN_RADIUS).count();
+        assert!(small > belt.asteroids.len() / 2);
+    }
+
+    #[test]
+    fn test_positions_follow_orbits() {
+        let mut rng = RNG::from_seed(3);
+        let belt = AsteroidBelt::generate(&mut rng, &zone(), &[], 1.0).unwrap();
+        let positions = belt.positions(1.0e7);
+        assert_eq!(positions.len(), belt.asteroids.len());
+        let scene_au = (ASTRONOMICAL_UNIT / SCENE_UNIT) as f32;
+        let max_distance = belt.outer_radius as f32 * (1.0 + ASTEROID_MAX_ECCENTRICITY as f32) * scene_au;
+        let min_distance = belt.inner_radius as f32 * (1.0 - ASTEROID_MAX_ECCENTRICITY as f32) * scene_au;
+        assert!(positions.iter().all(|p| (min_distance..=max_distance).contains(&p.length())));
+        assert_eq!(belt.positions_of(&[4, 1], 1.0e7), vec![positions[4], positions[1]]);
+    }
+}
@@ -0,0 +1,183 @@
+use super::orbit::Orbit;
+use super::stellar_system::{StarPhysicalProperties, ASTRONOMICAL_UNIT, STEFAN_BOLTZMANN};
+
+// Flux solaire reçu par la Terre, en W/m²
+pub const SOLAR_CONSTANT: f64 = 1361.0;
+// Nombre d'échantillons sur une orbite pour moyenner l'insolation
+const INSOLATION_SAMPLES: u32 = 64;
+// Domaine de validité des ajustements de Kopparapu et al. (2014), en Kelvin
+const KOPPARAPU_MIN_TEMPERATURE: f64 = 2600.0;
+const KOPPARAPU_MAX_TEMPERATURE: f64 = 7200.0;
+// Température solaire autour de laquelle ces ajustements sont développés
+const KOPPARAPU_REFERENCE_TEMPERATURE: f64 = 5780.0;
+
+// Coefficients (S_eff☉, a, b, c, d) de Kopparapu et al. (2014) pour une planète d'une masse terrestre
+const RUNAWAY_GREENHOUSE: [f64; 5] = [1.107, 1.332e-4, 1.580e-8, -8.308e-12, -1.931e-15];
+const MAXIMUM_GREENHOUSE: [f64; 5] = [0.356, 6.171e-5, 1.698e-9, -3.198e-12, -5.575e-16];
+
+/// Flux effectif (en flux terrestres) d'une limite de zone habitable pour une étoile de température `temperature`
+fn effective_flux(coefficients: &[f64; 5], temperature: f64) -> f64 {
+    let t = temperature.clamp(KOPPARAPU_MIN_TEMPERATURE, KOPPARAPU_MAX_TEMPERATURE) - KOPPARAPU_REFERENCE_TEMPERATURE;
+    let [s, a, b, c, d] = *coefficients;
+    s + t * (a + t * (b + t * (c + t * d)))
+}
+
+/// Zone habitable conservative (effet de serre galopant / maximal) autour d'une étoile
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HabitableZone {
+    pub inner_flux: f64, // en flux terrestres
+    pub outer_flux: f64, // en flux terrestres
+    pub inner_edge: f64, // en UA
+    pub outer_edge: f64, // en UA
+}
+
+impl HabitableZone {
+    /// Limites de la zone habitable pour une luminosité (en luminosités solaires) et une température (en Kelvin)
+    pub fn new(luminosity: f64, temperature: f64) -> Self {
+        let inner_flux = effective_flux(&RUNAWAY_GREENHOUSE, temperature);
+        let outer_flux = effective_flux(&MAXIMUM_GREENHOUSE, temperature);
+        Self {
+            inner_flux,
+            outer_flux,
+            inner_edge: (luminosity / inner_flux).sqrt(),
+            outer_edge: (luminosity / outer_flux).sqrt(),
+        }
+    }
+
+    pub fn of_star(star: &StarPhysicalProperties) -> Self {
+        Self::new(star.luminosity, star.temperature)
+    }
+
+    pub fn contains(&self, distance: f64) -> bool {
+        (self.inner_edge..=self.outer_edge).contains(&distance)
+    }
+}
+
+/// Position d'une planète par rapport à la zone habitable
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Habitability {
+    /// Plus irradiée que la limite interne (effet de serre galopant)
+    TooHot,
+    Habitable,
+    /// Moins irradiée que la limite externe (effet de serre maximal)
+    TooCold,
+}
+
+/// Insolation (en flux terrestres) reçue à `distance` (en UA) d'une étoile de luminosité `luminosity` (en luminosités solaires)
+pub fn insolation(luminosity: f64, distance: f64) -> f64 {
+    luminosity / (distance * distance)
+}
+
+/// Température d'équilibre (en Kelvin) d'une planète en rotation rapide, pour une insolation en flux terrestres
+pub fn equilibrium_temperature(insolation: f64, albedo: f64) -> f64 {
+    (SOLAR_CONSTANT * insolation * (1.0 - albedo) / (4.0 * STEFAN_BOLTZMANN)).powf(0.25)
+}
+
+/// Insolation moyennée sur une période de `orbit`, sommée sur toutes les étoiles.
+/// `stars` associe à chaque étoile son orbite autour du barycentre.
+pub fn mean_insolation(orbit: &Orbit, stars: &[(&StarPhysicalProperties, &Orbit)]) -> f64 {
+    let period = orbit.period();
+    if !period.is_finite() || period <= 0.0 {
+        return 0.0;
+    }
+    let mut total = 0.0;
+    for k in 0..INSOLATION_SAMPLES {
+        let time = period * k as f64 / INSOLATION_SAMPLES as f64;
+        let (position, _) = orbit.state_at(time);
+        for (star, star_orbit) in stars {
+            let (star_position, _) = star_orbit.state_at(time);
+            let distance = (position - star_position).length() / ASTRONOMICAL_UNIT;
+            total += insolation(star.luminosity, distance);
+        }
+    }
+    total / INSOLATION_SAMPLES as f64
+}
+
+/// Classe une insolation totale par rapport aux limites de zone habitable de chaque étoile,
+/// pondérées par la part du flux qu'elle apporte
+pub fn classify(stars: &[(&StarPhysicalProperties, f64)]) -> Habitability {
+    let total: f64 = stars.iter().map(|(_, flux)| flux).sum();
+    if total <= 0.0 {
+        return Habitability::TooCold;
+    }
+    let (mut inner, mut outer) = (0.0, 0.0);
+    for (star, flux) in stars {
+        let zone = HabitableZone::of_star(star);
+        inner += zone.inner_flux * flux / total;
+        outer += zone.outer_flux * flux / total;
+    }
+    if total > inner {
+        Habitability::TooHot
+    } else if total < outer {
+        Habitability::TooCold
+    } else {
+        Habitability::Habitable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::orbit::OrbitalElements;
+    use super::super::stellar_system::StellarStage;
+    use super::super::stellar_model::SOLAR_METALLICITY;
+
+    fn sun() -> StarPhysicalProperties {
+        StarPhysicalProperties {
+            mass: 1.0,
+            initial_mass: 1.0,
+            luminosity: 1.0,
+            radius: 1.0,
+            temperature: 5778.0,
+            lifetime: 10.0,
+            spectral_type: "G".to_string(),
+            stage: StellarStage::MainSequence,
+            metallicity: SOLAR_METALLICITY,
+        }
+    }
+
+    #[test]
+    fn test_earth_equilibrium_temperature() {
+        // ≈ 255 K pour l'albédo terrestre, ≈ 278 K pour un corps noir
+        assert!((equilibrium_temperature(1.0, 0.306) - 254.0).abs() < 1.5);
+        assert!((equilibrium_temperature(1.0, 0.0) - 278.3).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_solar_habitable_zone() {
+        let zone = HabitableZone::of_star(&sun());
+        assert!((zone.inner_edge - 0.95).abs() < 0.02, "limite interne {}", zone.inner_edge);
+        assert!((zone.outer_edge - 1.67).abs() < 0.03, "limite externe {}", zone.outer_edge);
+        assert!(zone.contains(1.0));
+        assert!(!zone.contains(0.72));
+        assert!(!zone.contains(5.2));
+        // Une étoile plus lumineuse repousse la zone habitable
+        assert!(HabitableZone::new(4.0, 5778.0).inner_edge > 1.8);
+    }
+
+    #[test]
+    fn test_classify() {
+        let sun = sun();
+        assert_eq!(classify(&[(&sun, insolation(1.0, 1.0))]), Habitability::Habitable);
+        assert_eq!(classify(&[(&sun, insolation(1.0, 0.72))]), Habitability::TooHot);
+        assert_eq!(classify(&[(&sun, insolation(1.0, 5.2))]), Habitability::TooCold);
+        // Deux soleils à 1 UA : trop d'énergie
+        assert_eq!(classify(&[(&sun, 1.0), (&sun, 1.0)]), Habitability::TooHot);
+    }
+
+    #[test]
+    fn test_mean_insolation_sums_stars() {
+        let sun = sun();
+        let center = Orbit::stationary();
+        let earth = Orbit::new(OrbitalElements::circular(1.0, 0.0), 1.0);
+        let single = mean_insolation(&earth, &[(&sun, &center)]);
+        assert!((single - 1.0).abs() < 1.0e-9);
+        let double = mean_insolation(&earth, &[(&sun, &center), (&sun, &center)]);
+        assert!((double - 2.0).abs() < 1.0e-9);
+        // Orbite excentrique : le flux moyen vaut L / (a² sqrt(1 - e²))
+        let mut elements = OrbitalElements::circular(1.0, 0.0);
+        elements.eccentricity = 0.3;
+        let eccentric = mean_insolation(&Orbit::new(elements, 1.0), &[(&sun, &center)]);
+        assert!((eccentric - 1.0 / (1.0_f64 - 0.09).sqrt()).abs() < 1.0e-3);
+    }
+}
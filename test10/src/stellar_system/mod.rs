@@ -1,3 +1,12 @@
 pub mod stellar_system;
+pub mod orbit;
+pub mod nbody;
+pub mod multiplicity;
+pub mod habitability;
+pub mod satellites;
+pub mod asteroid_belt;
+pub mod stellar_model;
+pub mod seed;
+pub mod naming;
 
 pub use stellar_system::{StellarSystem, CelestialBody};
\ No newline at end of file
@@ -0,0 +1,315 @@
+use std::f64::consts::PI;
+
+use super::orbit::{Orbit, OrbitalElements};
+use super::stellar_system::{StellarSystemType, RNG, PLANET_MAX_SEMI_MAJOR_AXIS, PLANET_MIN_SEMI_MAJOR_AXIS};
+
+// Binaires serrées (planètes circumbinaires) et larges (planètes circumstellaires), en UA
+pub const CLOSE_BINARY_SEPARATION: (f64, f64) = (0.05, 0.5);
+pub const WIDE_BINARY_SEPARATION: (f64, f64) = (30.0, 200.0);
+// Distance de la troisième étoile autour de la binaire interne, en UA
+pub const OUTER_STAR_SEPARATION: (f64, f64) = (20.0, 200.0);
+pub const STAR_MAX_ECCENTRICITY: f64 = 0.5;
+// Marge appliquée aux critères de stabilité
+const STABILITY_MARGIN: f64 = 1.2;
+
+/// Rapport critique a_out / a_in de Mardling & Aarseth (2001) pour un triple hiérarchique
+pub fn mardling_aarseth_ratio(outer_mass_ratio: f64, outer_eccentricity: f64, mutual_inclination: f64) -> f64 {
+    2.8 * (1.0 + outer_mass_ratio).powf(0.4)
+        * (1.0 + outer_eccentricity).powf(0.4)
+        * (1.0 - outer_eccentricity).powf(-1.2)
+        * (1.0 - 0.3 * mutual_inclination / PI)
+}
+
+/// Vrai si le triple (binaire interne a_in, troisième étoile a_out) est stable au sens de Mardling & Aarseth
+pub fn is_hierarchically_stable(
+    inner_semi_major_axis: f64,
+    outer_semi_major_axis: f64,
+    outer_mass_ratio: f64,
+    outer_eccentricity: f64,
+    mutual_inclination: f64,
+) -> bool {
+    outer_semi_major_axis / inner_semi_major_axis
+        > mardling_aarseth_ratio(outer_mass_ratio, outer_eccentricity, mutual_inclination)
+}
+
+/// Demi-grand axe critique (en unités de a_binaire) au-delà duquel une planète circumstellaire (S-type)
+/// devient instable, Holman & Wiegert (1999). `mass_ratio` = m_compagnon / (m_hôte + m_compagnon)
+pub fn s_type_critical_ratio(mass_ratio: f64, eccentricity: f64) -> f64 {
+    let (mu, e) = (mass_ratio, eccentricity);
+    0.464 - 0.380 * mu - 0.631 * e + 0.586 * mu * e + 0.150 * e * e - 0.198 * mu * e * e
+}
+
+/// Demi-grand axe critique (en unités de a_binaire) en deçà duquel une planète circumbinaire (P-type)
+/// devient instable, Holman & Wiegert (1999). `mass_ratio` = m_secondaire / (m_primaire + m_secondaire)
+pub fn p_type_critical_ratio(mass_ratio: f64, eccentricity: f64) -> f64 {
+    let (mu, e) = (mass_ratio, eccentricity);
+    1.60 + 5.10 * e - 2.22 * e * e + 4.12 * mu - 4.27 * e * mu - 5.09 * mu * mu + 4.61 * e * e * mu * mu
+}
+
+/// Type d'orbite planétaire dans un système multiple
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanetOrbitType {
+    /// Autour d'une seule étoile (ou d'une étoile unique)
+    SType,
+    /// Autour d'une binaire serrée
+    PType,
+}
+
+/// Région où les planètes peuvent orbiter de façon stable
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanetaryZone {
+    pub orbit_type: PlanetOrbitType,
+    /// Repère du foyer (étoile hôte, barycentre d'une paire), None pour le barycentre du système
+    pub host: Option<Orbit>,
+    pub central_mass: f64,        // en masses solaires
+    pub min_semi_major_axis: f64, // en UA
+    pub max_semi_major_axis: f64, // en UA
+}
+
+impl PlanetaryZone {
+    /// Orbite planétaire dans cette zone
+    pub fn orbit(&self, elements: OrbitalElements) -> Orbit {
+        match &self.host {
+            Some(host) => Orbit::around(host, elements, self.central_mass),
+            None => Orbit::new(elements, self.central_mass),
+        }
+    }
+}
+
+/// Configuration hiérarchique des étoiles d'un système
+#[derive(Debug, Clone)]
+pub struct StellarHierarchy {
+    pub system_type: StellarSystemType,
+    /// Orbite de chaque étoile, dans l'ordre des masses fournies
+    pub star_orbits: Vec<Orbit>,
+    pub planetary_zone: PlanetaryZone,
+}
+
+/// Orbites de deux corps autour de leur barycentre commun, à partir de l'orbite relative.
+/// L'orbite de chaque corps autour du barycentre est képlérienne avec M' = m_autre³ / M².
+fn binary_orbits(parent: Option<&Orbit>, relative: OrbitalElements, m1: f64, m2: f64) -> (Orbit, Orbit) {
+    let total = m1 + m2;
+    let mut first = relative;
+    first.semi_major_axis = relative.semi_major_axis * m2 / total;
+    let mut second = relative;
+    second.semi_major_axis = relative.semi_major_axis * m1 / total;
+    second.argument_periapsis = relative.argument_periapsis + PI;
+
+    let make = |elements: OrbitalElements, other: f64| {
+        let central_mass = other.powi(3) / (total * total);
+        match parent {
+            Some(parent) => Orbit::around(parent, elements, central_mass),
+            None => Orbit::new(elements, central_mass),
+        }
+    };
+    (make(first, m2), make(second, m1))
+}
+
+fn random_relative_orbit(rng: &mut RNG, separation: (f64, f64)) -> OrbitalElements {
+    OrbitalElements {
+        semi_major_axis: rng.f64(separation.0, separation.1),
+        eccentricity: STAR_MAX_ECCENTRICITY * rng.gen_norm().powi(2),
+        inclination: rng.f64(0.0, 0.1),
+        longitude_ascending_node: rng.f64(0.0, 2.0 * PI),
+        argument_periapsis: rng.f64(0.0, 2.0 * PI),
+        mean_anomaly_epoch: rng.f64(0.0, 2.0 * PI),
+    }
+}
+
+impl StellarHierarchy {
+    /// Construit une configuration stable pour 1 à 3 étoiles de masses `masses` (en masses solaires)
+    pub fn generate(rng: &mut RNG, masses: &[f64]) -> Self {
+        match masses.len() {
+            0 | 1 => Self::single(masses.first().copied().unwrap_or(0.0)),
+            2 => Self::binary(rng, masses[0], masses[1]),
+            _ => Self::ternary(rng, masses[0], masses[1], masses[2]),
+        }
+    }
+
+    fn single(mass: f64) -> Self {
+        StellarHierarchy {
+            system_type: StellarSystemType::Single,
+            star_orbits: vec![Orbit::stationary()],
+            planetary_zone: PlanetaryZone {
+                orbit_type: PlanetOrbitType::SType,
+                host: None,
+                central_mass: mass,
+                min_semi_major_axis: PLANET_MIN_SEMI_MAJOR_AXIS,
+                max_semi_major_axis: PLANET_MAX_SEMI_MAJOR_AXIS,
+            },
+        }
+    }
+
+    fn binary(rng: &mut RNG, m1: f64, m2: f64) -> Self {
+        let close = rng.gen_norm() < 0.5;
+        let separation = if close { CLOSE_BINARY_SEPARATION } else { WIDE_BINARY_SEPARATION };
+        let relative = random_relative_orbit(rng, separation);
+        let (orbit1, orbit2) = binary_orbits(None, relative, m1, m2);
+
+        let planetary_zone = if close {
+            // Planètes circumbinaires, au-delà de la limite de stabilité P-type
+            let mass_ratio = m1.min(m2) / (m1 + m2);
+            let min = p_type_critical_ratio(mass_ratio, relative.eccentricity) * relative.semi_major_axis * STABILITY_MARGIN;
+            PlanetaryZone {
+                orbit_type: PlanetOrbitType::PType,
+                host: None,
+                central_mass: m1 + m2,
+                min_semi_major_axis: min.max(PLANET_MIN_SEMI_MAJOR_AXIS),
+                max_semi_major_axis: PLANET_MAX_SEMI_MAJOR_AXIS,
+            }
+        } else {
+            // Planètes autour de l'étoile la plus massive, en deçà de la limite S-type
+            let (host, host_mass, companion_mass) = if m1 >= m2 { (&orbit1, m1, m2) } else { (&orbit2, m2, m1) };
+            let mass_ratio = companion_mass / (host_mass + companion_mass);
+            let max = s_type_critical_ratio(mass_ratio, relative.eccentricity) * relative.semi_major_axis / STABILITY_MARGIN;
+            PlanetaryZone {
+                orbit_type: PlanetOrbitType::SType,
+                host: Some(host.clone()),
+                central_mass: host_mass,
+                min_semi_major_axis: PLANET_MIN_SEMI_MAJOR_AXIS,
+                max_semi_major_axis: max.min(PLANET_MAX_SEMI_MAJOR_AXIS),
+            }
+        };
+
+        StellarHierarchy {
+            system_type: StellarSystemType::Binary,
+            star_orbits: vec![orbit1, orbit2],
+            planetary_zone,
+        }
+    }
+
+    fn ternary(rng: &mut RNG, m1: f64, m2: f64, m3: f64) -> Self {
+        let inner_mass = m1 + m2;
+        let inner = random_relative_orbit(rng, CLOSE_BINARY_SEPARATION);
+        let mut outer = random_relative_orbit(rng, OUTER_STAR_SEPARATION);
+
+        // Planètes circumbinaires autour de la paire interne, à l'intérieur de la limite S-type
+        // imposée par la troisième étoile
+        let inner_ratio = m1.min(m2) / inner_mass;
+        let min = (p_type_critical_ratio(inner_ratio, inner.eccentricity) * inner.semi_major_axis * STABILITY_MARGIN)
+            .max(PLANET_MIN_SEMI_MAJOR_AXIS);
+        let outer_ratio = m3 / (inner_mass + m3);
+        let s_type_ratio = s_type_critical_ratio(outer_ratio, outer.eccentricity);
+
+        // Critère de Mardling & Aarseth imposé sur la troisième étoile, qui doit aussi laisser
+        // de la place à au moins une planète
+        let mutual_inclination = (outer.inclination - inner.inclination).abs();
+        let critical = mardling_aarseth_ratio(m3 / inner_mass, outer.eccentricity, mutual_inclination);
+        outer.semi_major_axis = outer.semi_major_axis
+            .max(critical * inner.semi_major_axis * STABILITY_MARGIN)
+            .max(2.0 * min * STABILITY_MARGIN / s_type_ratio);
+        let max = s_type_ratio * outer.semi_major_axis / STABILITY_MARGIN;
+
+        // La binaire interne (via son barycentre) et la troisième étoile orbitent le barycentre du système
+        let (inner_barycenter, orbit3) = binary_orbits(None, outer, inner_mass, m3);
+        let (orbit1, orbit2) = binary_orbits(Some(&inner_barycenter), inner, m1, m2);
+
+        StellarHierarchy {
+            system_type: StellarSystemType::Ternary,
+            star_orbits: vec![orbit1, orbit2, orbit3],
+            planetary_zone: PlanetaryZone {
+                orbit_type: PlanetOrbitType::PType,
+                host: Some(inner_barycenter),
+                central_mass: inner_mass,
+                min_semi_major_axis: min,
+                max_semi_major_axis: max.min(PLANET_MAX_SEMI_MAJOR_AXIS),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::{DVec3, Vec3};
+
+    fn barycenter(orbits: &[Orbit], masses: &[f64], time: f64) -> DVec3 {
+        let total: f64 = masses.iter().sum();
+        orbits.iter().zip(masses).map(|(orbit, m)| orbit.state_at(time).0 * *m).sum::<DVec3>() / total
+    }
+
+    #[test]
+    fn test_mardling_aarseth_ratio() {
+        // Masses égales, orbite externe circulaire et coplanaire : 2.8 * 2^0.4
+        let ratio = mardling_aarseth_ratio(1.0, 0.0, 0.0);
+        assert!((ratio - 2.8 * 2.0_f64.powf(0.4)).abs() < 1.0e-12);
+        // L'excentricité externe rend le système moins stable
+        assert!(mardling_aarseth_ratio(1.0, 0.5, 0.0) > ratio);
+        assert!(is_hierarchically_stable(0.1, 10.0, 0.5, 0.2, 0.0));
+        assert!(!is_hierarchically_stable(1.0, 2.0, 0.5, 0.2, 0.0));
+    }
+
+    #[test]
+    fn test_holman_wiegert_limits() {
+        // Binaire circulaire de masses égales : a_c ≈ 0.26 a_b (S-type) et ≈ 2.39 a_b (P-type)
+        assert!((s_type_critical_ratio(0.5, 0.0) - 0.274).abs() < 1.0e-3);
+        assert!((p_type_critical_ratio(0.5, 0.0) - 2.3875).abs() < 1.0e-3);
+        // L'excentricité réduit la zone S-type et repousse la limite P-type
+        assert!(s_type_critical_ratio(0.5, 0.4) < s_type_critical_ratio(0.5, 0.0));
+        assert!(p_type_critical_ratio(0.5, 0.4) > p_type_critical_ratio(0.5, 0.0));
+    }
+
+    #[test]
+    fn test_system_type_from_star_count() {
+        let mut rng = RNG::new(Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(StellarHierarchy::generate(&mut rng, &[1.0]).system_type, StellarSystemType::Single);
+        assert_eq!(StellarHierarchy::generate(&mut rng, &[1.0, 0.5]).system_type, StellarSystemType::Binary);
+        assert_eq!(StellarHierarchy::generate(&mut rng, &[1.0, 0.5, 0.3]).system_type, StellarSystemType::Ternary);
+    }
+
+    #[test]
+    fn test_binary_orbits_share_barycenter() {
+        let mut rng = RNG::new(Vec3::new(4.0, 0.0, 0.0));
+        let masses = [1.2, 0.7];
+        let hierarchy = StellarHierarchy::generate(&mut rng, &masses);
+        let period = hierarchy.star_orbits[0].period();
+        assert!((period - hierarchy.star_orbits[1].period()).abs() / period < 1.0e-9);
+        for k in 0..10 {
+            let time = period * k as f64 / 7.0;
+            assert!(barycenter(&hierarchy.star_orbits, &masses, time).length() < 1.0);
+        }
+    }
+
+    #[test]
+    fn test_ternary_is_stable_and_bound() {
+        for i in 0..20 {
+            let mut rng = RNG::new(Vec3::new(i as f32, 1.0, -2.0));
+            let masses = [1.0, 0.6, 0.8];
+            let hierarchy = StellarHierarchy::generate(&mut rng, &masses);
+            let inner = &hierarchy.star_orbits[0];
+            let outer = &hierarchy.star_orbits[2];
+            let a_in = inner.elements.semi_major_axis * (masses[0] + masses[1]) / masses[1];
+            let a_out = outer.elements.semi_major_axis * (masses[0] + masses[1] + masses[2]) / (masses[0] + masses[1]);
+            assert!(is_hierarchically_stable(a_in, a_out, masses[2] / 1.6, outer.elements.eccentricity, 0.1));
+            for k in 0..5 {
+                let time = outer.period() * k as f64 / 5.0;
+                assert!(barycenter(&hierarchy.star_orbits, &masses, time).length() < 10.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_planetary_zone_follows_binary_type() {
+        for i in 0..40 {
+            let mut rng = RNG::new(Vec3::new(0.0, i as f32, 0.0));
+            let hierarchy = StellarHierarchy::generate(&mut rng, &[1.0, 0.8]);
+            let zone = &hierarchy.planetary_zone;
+            let binary = &hierarchy.star_orbits[0];
+            let a_bin = binary.elements.semi_major_axis * 1.8 / 0.8;
+            match zone.orbit_type {
+                PlanetOrbitType::PType => {
+                    assert!(a_bin <= CLOSE_BINARY_SEPARATION.1);
+                    assert!(zone.min_semi_major_axis > 1.6 * a_bin);
+                    assert!(zone.host.is_none());
+                }
+                PlanetOrbitType::SType => {
+                    assert!(a_bin >= WIDE_BINARY_SEPARATION.0);
+                    assert!(zone.max_semi_major_axis < 0.464 * a_bin);
+                    assert_eq!(zone.central_mass, 1.0);
+                    assert!(zone.host.is_some());
+                }
+            }
+            assert!(zone.min_semi_major_axis < zone.max_semi_major_axis);
+        }
+    }
+}
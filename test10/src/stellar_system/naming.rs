@@ -0,0 +1,121 @@
+use glam::IVec3;
+
+use super::stellar_system::RNG;
+
+// Syllabes : attaque, noyau vocalique, coda (les entrées vides rendent la coda facultative)
+const ONSETS: [&str; 24] = [
+    "", "b", "c", "d", "f", "g", "k", "l", "m", "n", "p", "r",
+    "s", "t", "v", "z", "th", "sh", "kr", "tr", "st", "dr", "gl", "br",
+];
+const NUCLEI: [&str; 11] = ["a", "e", "i", "o", "u", "ae", "ai", "ia", "eo", "ou", "y"];
+const CODAS: [&str; 11] = ["", "", "", "n", "r", "s", "l", "th", "x", "m", "k"];
+// Probabilités relatives d'un nom de 2, 3 ou 4 syllabes
+const SYLLABLE_WEIGHTS: [f64; 3] = [5.0, 4.0, 1.0];
+
+fn pick<'a>(rng: &mut RNG, table: &[&'a str]) -> &'a str {
+    table[rng.u32(0, table.len() as u32) as usize]
+}
+
+/// Nom propre prononçable construit syllabe par syllabe
+pub fn generate_name(rng: &mut RNG) -> String {
+    let syllables = 2 + rng.weighted_choice(&SYLLABLE_WEIGHTS).unwrap_or(0);
+    let mut name = String::new();
+    for i in 0..syllables {
+        // Pas d'attaque vide après une coda vide, pour éviter les suites de voyelles
+        let onset = loop {
+            let onset = pick(rng, &ONSETS);
+            if i == 0 || !onset.is_empty() || !name.ends_with(|c: char| "aeiouy".contains(c)) {
+                break onset;
+            }
+        };
+        name.push_str(onset);
+        name.push_str(pick(rng, &NUCLEI));
+        // Coda systématique en fin de nom, occasionnelle ailleurs
+        if i + 1 == syllables || rng.gen_norm() < 0.3 {
+            name.push_str(pick(rng, &CODAS));
+        }
+    }
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => name,
+    }
+}
+
+/// Code d'un secteur, par exemple `S-001+000+002`
+pub fn sector_code(sector: IVec3) -> String {
+    format!("S{:+04}{:+04}{:+04}", sector.x, sector.y, sector.z)
+}
+
+/// Désignation de catalogue d'un système : code du secteur suivi de son rang dans le secteur,
+/// unique parmi les systèmes du secteur
+pub fn catalog_designation(sector: IVec3, index: u64) -> String {
+    format!("{}-{:04}", sector_code(sector), index)
+}
+
+/// Suffixe d'une étoile dans un système multiple : A, B, C...
+pub fn star_letter(index: usize) -> char {
+    (b'A' + (index % 26) as u8) as char
+}
+
+/// Suffixe d'une planète par ordre orbital : b, c, d... (la lettre a désigne l'étoile)
+pub fn planet_letter(index: usize) -> char {
+    (b'b' + (index % 25) as u8) as char
+}
+
+/// Numéro de satellite en chiffres romains (1 -> I)
+pub fn roman_numeral(mut value: usize) -> String {
+    const NUMERALS: [(usize, &str); 13] = [
+        (1000, "M"), (900, "CM"), (500, "D"), (400, "CD"), (100, "C"), (90, "XC"),
+        (50, "L"), (40, "XL"), (10, "X"), (9, "IX"), (5, "V"), (4, "IV"), (1, "I"),
+    ];
+    let mut roman = String::new();
+    for (amount, numeral) in NUMERALS {
+        while value >= amount {
+            roman.push_str(numeral);
+            value -= amount;
+        }
+    }
+    roman
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_are_seeded() {
+        let name = generate_name(&mut RNG::from_seed(12));
+        assert_eq!(name, generate_name(&mut RNG::from_seed(12)));
+        let names: Vec<String> = (0..50).map(|i| generate_name(&mut RNG::from_seed(i))).collect();
+        for name in &names {
+            assert!(name.len() >= 2 && name.len() <= 24, "{}", name);
+            assert!(name.chars().next().unwrap().is_uppercase());
+            assert!(name.chars().skip(1).all(|c| c.is_ascii_lowercase()));
+        }
+        let mut unique = names.clone();
+        unique.sort();
+        unique.dedup();
+        assert!(unique.len() > 45);
+    }
+
+    #[test]
+    fn test_designations() {
+        assert_eq!(sector_code(IVec3::new(-1, 0, 12)), "S-001+000+012");
+        assert_eq!(catalog_designation(IVec3::ZERO, 42), "S+000+000+000-0042");
+        // Pas de repli modulo : deux rangs différents ne se confondent jamais
+        assert_eq!(catalog_designation(IVec3::ZERO, 123_456), "S+000+000+000-123456");
+        assert_eq!(star_letter(1), 'B');
+        assert_eq!(planet_letter(0), 'b');
+        assert_eq!(planet_letter(2), 'd');
+    }
+
+    #[test]
+    fn test_roman_numerals() {
+        let expected = ["I", "II", "III", "IV", "V", "VI", "VII", "VIII", "IX", "X"];
+        for (i, numeral) in expected.iter().enumerate() {
+            assert_eq!(roman_numeral(i + 1), *numeral);
+        }
+        assert_eq!(roman_numeral(1994), "MCMXCIV");
+    }
+}
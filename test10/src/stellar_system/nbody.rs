@@ -0,0 +1,360 @@
+use glam::DVec3;
+
+use super::stellar_system::G;
+
+// Adoucissement gravitationnel (m²) pour éviter la divergence lors des rencontres proches
+const SOFTENING_SQUARED: f64 = 1.0e6;
+
+/// Schéma d'intégration numérique des équations du mouvement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Integrator {
+    /// Leapfrog / Verlet vitesse (kick-drift-kick), symplectique
+    Leapfrog,
+    /// Runge-Kutta d'ordre 4, non symplectique (pour comparaison)
+    Rk4,
+}
+
+/// Mode de simulation d'un système stellaire
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimulationMode {
+    /// Orbites képlériennes analytiques
+    Kepler,
+    /// Intégration gravitationnelle à N corps
+    NBody(Integrator),
+}
+
+/// Etat d'un système à N corps, en unités SI
+#[derive(Debug, Clone)]
+pub struct NBodyState {
+    pub positions: Vec<DVec3>,  // m
+    pub velocities: Vec<DVec3>, // m/s
+    pub masses: Vec<f64>,       // kg
+    accelerations: Option<Vec<DVec3>>,
+}
+
+impl NBodyState {
+    pub fn new(positions: Vec<DVec3>, velocities: Vec<DVec3>, masses: Vec<f64>) -> Self {
+        assert!(positions.len() == velocities.len() && positions.len() == masses.len());
+        Self {
+            positions,
+            velocities,
+            masses,
+            accelerations: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.masses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.masses.is_empty()
+    }
+
+    /// Accélérations gravitationnelles mutuelles pour un jeu de positions
+    fn compute_accelerations(&self, positions: &[DVec3]) -> Vec<DVec3> {
+        let mut accelerations = vec![DVec3::ZERO; positions.len()];
+        for i in 0..positions.len() {
+            for j in (i + 1)..positions.len() {
+                let r = positions[j] - positions[i];
+                let dist_sq = r.length_squared() + SOFTENING_SQUARED;
+                let inv_r3 = 1.0 / (dist_sq * dist_sq.sqrt());
+                accelerations[i] += r * (G * self.masses[j] * inv_r3);
+                accelerations[j] -= r * (G * self.masses[i] * inv_r3);
+            }
+        }
+        accelerations
+    }
+
+    /// Avance le système d'un pas `dt` (s)
+    pub fn step(&mut self, dt: f64, integrator: Integrator) {
+        match integrator {
+            Integrator::Leapfrog => self.step_leapfrog(dt),
+            Integrator::Rk4 => self.step_rk4(dt),
+        }
+    }
+
+    fn step_leapfrog(&mut self, dt: f64) {
+        let accelerations = match self.accelerations.take() {
+            Some(accelerations) => accelerations,
+            None => self.compute_accelerations(&self.positions),
+        };
+        // Kick (demi-pas) puis drift
+        for ((position, velocity), acceleration) in self.positions.iter_mut().zip(self.velocities.iter_mut()).zip(&accelerations) {
+            *velocity += *acceleration * (0.5 * dt);
+            *position += *velocity * dt;
+        }
+        // Kick (demi-pas) avec les nouvelles accélérations, conservées pour le pas suivant
+        let accelerations = self.compute_accelerations(&self.positions);
+        for (velocity, acceleration) in self.velocities.iter_mut().zip(&accelerations) {
+            *velocity += *acceleration * (0.5 * dt);
+        }
+        self.accelerations = Some(accelerations);
+    }
+
+    fn step_rk4(&mut self, dt: f64) {
+        let x0 = self.positions.clone();
+        let v0 = self.velocities.clone();
+        let offset = |base: &[DVec3], delta: &[DVec3], h: f64| -> Vec<DVec3> {
+            base.iter().zip(delta).map(|(b, d)| *b + *d * h).collect()
+        };
+
+        let k1_x = v0.clone();
+        let k1_v = self.compute_accelerations(&x0);
+
+        let k2_x = offset(&v0, &k1_v, 0.5 * dt);
+        let k2_v = self.compute_accelerations(&offset(&x0, &k1_x, 0.5 * dt));
+
+        let k3_x = offset(&v0, &k2_v, 0.5 * dt);
+        let k3_v = self.compute_accelerations(&offset(&x0, &k2_x, 0.5 * dt));
+
+        let k4_x = offset(&v0, &k3_v, dt);
+        let k4_v = self.compute_accelerations(&offset(&x0, &k3_x, dt));
+
+        for i in 0..self.len() {
+            self.positions[i] = x0[i] + (k1_x[i] + 2.0 * k2_x[i] + 2.0 * k3_x[i] + k4_x[i]) * (dt / 6.0);
+            self.velocities[i] = v0[i] + (k1_v[i] + 2.0 * k2_v[i] + 2.0 * k3_v[i] + k4_v[i]) * (dt / 6.0);
+        }
+        self.accelerations = None;
+    }
+
+    /// Energie totale (cinétique + potentielle), en joules
+    pub fn total_energy(&self) -> f64 {
+        let mut kinetic = 0.0;
+        let mut potential = 0.0;
+        for i in 0..self.len() {
+            kinetic += 0.5 * self.masses[i] * self.velocities[i].length_squared();
+            for j in (i + 1)..self.len() {
+                let dist_sq = (self.positions[j] - self.positions[i]).length_squared() + SOFTENING_SQUARED;
+                potential -= G * self.masses[i] * self.masses[j] / dist_sq.sqrt();
+            }
+        }
+        kinetic + potential
+    }
+
+    /// Moment cinétique total par rapport à l'origine, en kg.m²/s
+    pub fn angular_momentum(&self) -> DVec3 {
+        (0..self.len())
+            .map(|i| self.positions[i].cross(self.velocities[i]) * self.masses[i])
+            .sum()
+    }
+
+    /// Centre de masse, en mètres
+    pub fn center_of_mass(&self) -> DVec3 {
+        let total: f64 = self.masses.iter().sum();
+        if total <= 0.0 {
+            return DVec3::ZERO;
+        }
+        (0..self.len()).map(|i| self.positions[i] * self.masses[i]).sum::<DVec3>() / total
+    }
+
+    /// Quantité de mouvement totale, en kg.m/s
+    pub fn momentum(&self) -> DVec3 {
+        (0..self.len()).map(|i| self.velocities[i] * self.masses[i]).sum()
+    }
+
+    /// Passe dans le repère du centre de masse : centre de masse à l'origine et quantité de
+    /// mouvement nulle, pour que le barycentre ne dérive pas
+    pub fn to_center_of_mass_frame(&mut self) {
+        let total: f64 = self.masses.iter().sum();
+        if total <= 0.0 {
+            return;
+        }
+        let center = self.center_of_mass();
+        let velocity = self.momentum() / total;
+        for (position, body_velocity) in self.positions.iter_mut().zip(self.velocities.iter_mut()) {
+            *position -= center;
+            *body_velocity -= velocity;
+        }
+        self.accelerations = None;
+    }
+}
+
+/// Suivi de la dérive des quantités conservées depuis l'état initial
+#[derive(Debug, Clone, Copy)]
+pub struct ConservationDiagnostics {
+    pub initial_energy: f64,
+    pub initial_angular_momentum: DVec3,
+}
+
+impl ConservationDiagnostics {
+    pub fn new(state: &NBodyState) -> Self {
+        Self {
+            initial_energy: state.total_energy(),
+            initial_angular_momentum: state.angular_momentum(),
+        }
+    }
+
+    /// Dérive relative de l'énergie |E - E0| / |E0|
+    pub fn energy_drift(&self, state: &NBodyState) -> f64 {
+        let delta = (state.total_energy() - self.initial_energy).abs();
+        if self.initial_energy != 0.0 {
+            delta / self.initial_energy.abs()
+        } else {
+            delta
+        }
+    }
+
+    /// Dérive relative du moment cinétique |L - L0| / |L0|
+    pub fn angular_momentum_drift(&self, state: &NBodyState) -> f64 {
+        let delta = (state.angular_momentum() - self.initial_angular_momentum).length();
+        let norm = self.initial_angular_momentum.length();
+        if norm > 0.0 {
+            delta / norm
+        } else {
+            delta
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stellar_system::orbit::{Orbit, OrbitalElements};
+    use crate::stellar_system::stellar_system::{ASTRONOMICAL_UNIT, EARTH_MASS, SOLAR_MASS};
+
+    const YEAR: f64 = 365.25 * 86400.0;
+    const JUPITER_MASS: f64 = 1.898e27;
+
+    /// Soleil + une planète sur orbite képlérienne, dans le repère du centre de masse
+    fn two_body(eccentricity: f64, planet_mass: f64) -> (NBodyState, Orbit) {
+        let orbit = Orbit::new(
+            OrbitalElements {
+                semi_major_axis: 1.0,
+                eccentricity,
+                inclination: 0.2,
+                longitude_ascending_node: 0.4,
+                argument_periapsis: 0.9,
+                mean_anomaly_epoch: 0.0,
+            },
+            (SOLAR_MASS + planet_mass) / SOLAR_MASS,
+        );
+        let (r, v) = orbit.state_at(0.0);
+        let mu_ratio = planet_mass / (SOLAR_MASS + planet_mass);
+        let state = NBodyState::new(
+            vec![-r * mu_ratio, r * (1.0 - mu_ratio)],
+            vec![-v * mu_ratio, v * (1.0 - mu_ratio)],
+            vec![SOLAR_MASS, planet_mass],
+        );
+        (state, orbit)
+    }
+
+    fn run(state: &mut NBodyState, integrator: Integrator, duration: f64, steps: usize) {
+        let dt = duration / steps as f64;
+        for _ in 0..steps {
+            state.step(dt, integrator);
+        }
+    }
+
+    #[test]
+    fn test_leapfrog_two_body_conservation() {
+        let (mut state, orbit) = two_body(0.1, EARTH_MASS);
+        let diagnostics = ConservationDiagnostics::new(&state);
+        run(&mut state, Integrator::Leapfrog, 10.0 * orbit.period(), 10 * 2000);
+        assert!(diagnostics.energy_drift(&state) < 1.0e-5, "derive energie {}", diagnostics.energy_drift(&state));
+        assert!(diagnostics.angular_momentum_drift(&state) < 1.0e-10);
+        assert!(state.center_of_mass().length() < 1.0);
+    }
+
+    #[test]
+    fn test_leapfrog_matches_kepler() {
+        let (mut state, orbit) = two_body(0.3, 1.0);
+        let duration = 0.75 * orbit.period();
+        run(&mut state, Integrator::Leapfrog, duration, 20000);
+        let (expected, _) = orbit.state_at(duration);
+        let relative = state.positions[1] - state.positions[0];
+        assert!((relative - expected).length() / ASTRONOMICAL_UNIT < 1.0e-4);
+    }
+
+    #[test]
+    fn test_rk4_two_body_accuracy() {
+        let (mut state, orbit) = two_body(0.1, EARTH_MASS);
+        let diagnostics = ConservationDiagnostics::new(&state);
+        run(&mut state, Integrator::Rk4, orbit.period(), 2000);
+        assert!(diagnostics.energy_drift(&state) < 1.0e-8);
+        let (expected, _) = orbit.state_at(orbit.period());
+        let relative = state.positions[1] - state.positions[0];
+        assert!((relative - expected).length() / ASTRONOMICAL_UNIT < 1.0e-6);
+    }
+
+    #[test]
+    fn test_leapfrog_energy_does_not_drift_secularly() {
+        // L'erreur d'un schéma symplectique oscille mais reste bornée : son maximum
+        // sur 45 orbites ne dépasse pas celui des 5 premières
+        let (mut state, orbit) = two_body(0.5, EARTH_MASS);
+        let diagnostics = ConservationDiagnostics::new(&state);
+        let dt = orbit.period() / 1000.0;
+        let max_drift = |state: &mut NBodyState, steps: usize| {
+            let mut max: f64 = 0.0;
+            for _ in 0..steps {
+                state.step(dt, Integrator::Leapfrog);
+                max = max.max(diagnostics.energy_drift(state));
+            }
+            max
+        };
+        let short = max_drift(&mut state, 5 * 1000);
+        let long = max_drift(&mut state, 45 * 1000);
+        assert!(long < 1.0e-3);
+        assert!(long < 1.1 * short, "court={} long={}", short, long);
+    }
+
+    #[test]
+    fn test_ternary_hierarchical_conservation() {
+        // Binaire serrée + troisième étoile lointaine
+        let m = SOLAR_MASS;
+        let a_in = 0.2 * ASTRONOMICAL_UNIT;
+        let a_out = 5.0 * ASTRONOMICAL_UNIT;
+        let v_in = (G * 2.0 * m / a_in).sqrt() / 2.0;
+        let v_out = (G * 3.0 * m / a_out).sqrt();
+        let mut state = NBodyState::new(
+            vec![
+                DVec3::new(-a_in / 2.0 - a_out / 3.0, 0.0, 0.0),
+                DVec3::new(a_in / 2.0 - a_out / 3.0, 0.0, 0.0),
+                DVec3::new(2.0 * a_out / 3.0, 0.0, 0.0),
+            ],
+            vec![
+                DVec3::new(0.0, -v_in - v_out / 3.0, 0.0),
+                DVec3::new(0.0, v_in - v_out / 3.0, 0.0),
+                DVec3::new(0.0, 2.0 * v_out / 3.0, 0.0),
+            ],
+            vec![m, m, m],
+        );
+        let diagnostics = ConservationDiagnostics::new(&state);
+        run(&mut state, Integrator::Leapfrog, 2.0 * YEAR, 40000);
+        assert!(diagnostics.energy_drift(&state) < 1.0e-4, "derive energie {}", diagnostics.energy_drift(&state));
+        assert!(diagnostics.angular_momentum_drift(&state) < 1.0e-9);
+    }
+
+    #[test]
+    fn test_center_of_mass_frame() {
+        // Soleil immobile et Terre en mouvement : le barycentre dériverait
+        let orbit = Orbit::new(OrbitalElements::circular(1.0, 0.0), 1.0);
+        let (r, v) = orbit.state_at(0.0);
+        let mut state = NBodyState::new(vec![DVec3::ZERO, r], vec![DVec3::ZERO, v], vec![SOLAR_MASS, EARTH_MASS]);
+        assert!(state.momentum().length() > 0.0);
+        state.to_center_of_mass_frame();
+        let scale = EARTH_MASS * v.length();
+        assert!(state.momentum().length() < 1.0e-9 * scale);
+        assert!(state.center_of_mass().length() < 1.0e-3);
+        run(&mut state, Integrator::Leapfrog, orbit.period(), 2000);
+        assert!(state.center_of_mass().length() < 1.0);
+    }
+
+    #[test]
+    fn test_planet_perturbation_is_small() {
+        // Jupiter perturbe peu la Terre sur une année
+        let (state, orbit) = two_body(0.0, EARTH_MASS);
+        let jupiter = Orbit::new(OrbitalElements::circular(5.2, 2.0), 1.0);
+        let (rj, vj) = jupiter.state_at(0.0);
+        let mut state = NBodyState::new(
+            vec![state.positions[0], state.positions[1], rj],
+            vec![state.velocities[0], state.velocities[1], vj],
+            vec![SOLAR_MASS, EARTH_MASS, JUPITER_MASS],
+        );
+        let diagnostics = ConservationDiagnostics::new(&state);
+        run(&mut state, Integrator::Leapfrog, orbit.period(), 5000);
+        assert!(diagnostics.energy_drift(&state) < 1.0e-6);
+        let distance = (state.positions[1] - state.positions[0]).length() / ASTRONOMICAL_UNIT;
+        assert!((distance - 1.0).abs() < 0.01);
+    }
+}
@@ -0,0 +1,260 @@
+use std::f64::consts::PI;
+use glam::{DQuat, DVec3};
+
+use super::stellar_system::{ASTRONOMICAL_UNIT, G, SOLAR_MASS};
+
+const KEPLER_TOLERANCE: f64 = 1.0e-12;
+const KEPLER_MAX_ITERATIONS: u32 = 50;
+
+/// Paramètre gravitationnel mu = G * M (m³/s²) pour une masse en masses solaires
+pub fn gravitational_parameter(mass: f64) -> f64 {
+    G * mass * SOLAR_MASS
+}
+
+/// Résout l'équation de Kepler M = E - e sin(E) par Newton-Raphson et renvoie l'anomalie excentrique E
+pub fn solve_kepler(mean_anomaly: f64, eccentricity: f64) -> f64 {
+    let m = mean_anomaly.rem_euclid(2.0 * PI);
+    // Pour les fortes excentricités, partir de PI converge mieux
+    let mut e_anomaly = if eccentricity < 0.8 { m } else { PI };
+    for _ in 0..KEPLER_MAX_ITERATIONS {
+        let f = e_anomaly - eccentricity * e_anomaly.sin() - m;
+        let delta = f / (1.0 - eccentricity * e_anomaly.cos());
+        e_anomaly -= delta;
+        if delta.abs() < KEPLER_TOLERANCE {
+            break;
+        }
+    }
+    e_anomaly
+}
+
+/// Eléments orbitaux képlériens classiques
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitalElements {
+    pub semi_major_axis: f64,          // a, en UA
+    pub eccentricity: f64,             // e
+    pub inclination: f64,              // i, en radians, par rapport au plan XY
+    pub longitude_ascending_node: f64, // Ω, en radians
+    pub argument_periapsis: f64,       // ω, en radians
+    pub mean_anomaly_epoch: f64,       // M0, anomalie moyenne à t = 0, en radians
+}
+
+impl OrbitalElements {
+    /// Orbite circulaire dans le plan XY passant par `angle` à t = 0
+    pub fn circular(semi_major_axis: f64, angle: f64) -> Self {
+        Self {
+            semi_major_axis,
+            eccentricity: 0.0,
+            inclination: 0.0,
+            longitude_ascending_node: 0.0,
+            argument_periapsis: 0.0,
+            mean_anomaly_epoch: angle,
+        }
+    }
+
+    /// Distance au périastre, en UA
+    pub fn periapsis(&self) -> f64 {
+        self.semi_major_axis * (1.0 - self.eccentricity)
+    }
+
+    /// Distance à l'apoastre, en UA
+    pub fn apoapsis(&self) -> f64 {
+        self.semi_major_axis * (1.0 + self.eccentricity)
+    }
+
+    /// Mouvement moyen n = sqrt(mu / a³), en rad/s
+    pub fn mean_motion(&self, mu: f64) -> f64 {
+        let a = self.semi_major_axis * ASTRONOMICAL_UNIT;
+        (mu / (a * a * a)).sqrt()
+    }
+
+    /// Période orbitale (3e loi de Kepler), en secondes
+    pub fn period(&self, mu: f64) -> f64 {
+        2.0 * PI / self.mean_motion(mu)
+    }
+
+    /// Rotation du plan orbital vers le repère du système : Rz(Ω) * Rx(i) * Rz(ω)
+    fn orientation(&self) -> DQuat {
+        DQuat::from_rotation_z(self.longitude_ascending_node)
+            * DQuat::from_rotation_x(self.inclination)
+            * DQuat::from_rotation_z(self.argument_periapsis)
+    }
+
+    /// Position (m) et vitesse (m/s) relatives au foyer à l'instant `time` (s)
+    pub fn state_at(&self, time: f64, mu: f64) -> (DVec3, DVec3) {
+        if self.semi_major_axis <= 0.0 {
+            return (DVec3::ZERO, DVec3::ZERO);
+        }
+        let a = self.semi_major_axis * ASTRONOMICAL_UNIT;
+        let e = self.eccentricity;
+        let n = self.mean_motion(mu);
+
+        let e_anomaly = solve_kepler(self.mean_anomaly_epoch + n * time, e);
+        let (sin_e, cos_e) = e_anomaly.sin_cos();
+        let b_ratio = (1.0 - e * e).sqrt();
+
+        // Coordonnées dans le plan orbital (périastre sur l'axe X)
+        let position = DVec3::new(a * (cos_e - e), a * b_ratio * sin_e, 0.0);
+        let e_dot = n / (1.0 - e * cos_e);
+        let velocity = DVec3::new(-a * sin_e * e_dot, a * b_ratio * cos_e * e_dot, 0.0);
+
+        let orientation = self.orientation();
+        (orientation * position, orientation * velocity)
+    }
+}
+
+/// Orbite d'un corps : éléments képlériens et masse centrale attractive.
+/// Le foyer est le barycentre du système, ou un repère parent lui-même en orbite
+/// (barycentre d'une binaire interne, étoile hôte d'une planète, ...).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Orbit {
+    pub elements: OrbitalElements,
+    pub central_mass: f64, // en masses solaires
+    pub parent: Option<Box<Orbit>>,
+}
+
+impl Orbit {
+    pub fn new(elements: OrbitalElements, central_mass: f64) -> Self {
+        Self { elements, central_mass, parent: None }
+    }
+
+    /// Orbite dont le foyer suit l'orbite `parent`
+    pub fn around(parent: &Orbit, elements: OrbitalElements, central_mass: f64) -> Self {
+        Self {
+            elements,
+            central_mass,
+            parent: Some(Box::new(parent.clone())),
+        }
+    }
+
+    /// Orbite d'un corps immobile au foyer
+    pub fn stationary() -> Self {
+        Self::new(OrbitalElements::circular(0.0, 0.0), 0.0)
+    }
+
+    pub fn mu(&self) -> f64 {
+        gravitational_parameter(self.central_mass)
+    }
+
+    /// Période orbitale autour du foyer, en secondes
+    pub fn period(&self) -> f64 {
+        self.elements.period(self.mu())
+    }
+
+    /// Position (m) et vitesse (m/s) relatives au barycentre du système à l'instant `time` (s)
+    pub fn state_at(&self, time: f64) -> (DVec3, DVec3) {
+        let (position, velocity) = self.elements.state_at(time, self.mu());
+        match &self.parent {
+            Some(parent) => {
+                let (parent_position, parent_velocity) = parent.state_at(time);
+                (parent_position + position, parent_velocity + velocity)
+            }
+            None => (position, velocity),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: f64 = 86400.0;
+
+    fn earth_orbit(eccentricity: f64) -> Orbit {
+        Orbit::new(
+            OrbitalElements {
+                semi_major_axis: 1.0,
+                eccentricity,
+                inclination: 0.1,
+                longitude_ascending_node: 0.7,
+                argument_periapsis: 1.3,
+                mean_anomaly_epoch: 0.0,
+            },
+            1.0,
+        )
+    }
+
+    #[test]
+    fn test_solve_kepler() {
+        for &e in &[0.0, 0.1, 0.5, 0.9, 0.99] {
+            for k in 0..16 {
+                let m = k as f64 * 0.4;
+                let e_anomaly = solve_kepler(m, e);
+                let residual = e_anomaly - e * e_anomaly.sin() - m.rem_euclid(2.0 * PI);
+                assert!(residual.abs() < 1.0e-10, "e={} M={} residu={}", e, m, residual);
+            }
+        }
+    }
+
+    #[test]
+    fn test_earth_period() {
+        let period_days = earth_orbit(0.0167).period() / DAY;
+        assert!((period_days - 365.256).abs() < 0.1, "periode={}", period_days);
+    }
+
+    #[test]
+    fn test_period_scales_with_mass() {
+        // T ∝ a^(3/2) / sqrt(M)
+        let light = Orbit::new(OrbitalElements::circular(4.0, 0.0), 1.0);
+        let heavy = Orbit::new(OrbitalElements::circular(4.0, 4.0), 4.0);
+        let earth = earth_orbit(0.0);
+        assert!((light.period() / earth.period() - 8.0).abs() < 1.0e-9);
+        assert!((light.period() / heavy.period() - 2.0).abs() < 1.0e-9);
+    }
+
+    #[test]
+    fn test_perihelion_aphelion() {
+        let orbit = earth_orbit(0.5);
+        let period = orbit.period();
+        let mut min: f64 = f64::MAX;
+        let mut max: f64 = 0.0;
+        for k in 0..2000 {
+            let (position, _) = orbit.state_at(period * k as f64 / 2000.0);
+            let r = position.length() / ASTRONOMICAL_UNIT;
+            min = min.min(r);
+            max = max.max(r);
+        }
+        assert!((min - orbit.elements.periapsis()).abs() < 1.0e-6);
+        assert!((max - orbit.elements.apoapsis()).abs() < 1.0e-3);
+        // A t = 0 (M0 = 0) le corps est au périastre
+        let (start, _) = orbit.state_at(0.0);
+        assert!((start.length() / ASTRONOMICAL_UNIT - 0.5).abs() < 1.0e-9);
+    }
+
+    #[test]
+    fn test_vis_viva_and_periodicity() {
+        let orbit = earth_orbit(0.3);
+        let mu = orbit.mu();
+        let a = ASTRONOMICAL_UNIT;
+        for k in 0..10 {
+            let (position, velocity) = orbit.state_at(k as f64 * 37.0 * DAY);
+            let expected = mu * (2.0 / position.length() - 1.0 / a);
+            assert!((velocity.length_squared() / expected - 1.0).abs() < 1.0e-9);
+        }
+        let (p0, v0) = orbit.state_at(0.0);
+        let (p1, v1) = orbit.state_at(orbit.period());
+        assert!((p1 - p0).length() / a < 1.0e-9);
+        assert!((v1 - v0).length() / v0.length() < 1.0e-9);
+    }
+
+    #[test]
+    fn test_nested_orbit() {
+        // Un satellite dont le foyer suit une orbite terrestre
+        let earth = earth_orbit(0.0);
+        let moon = Orbit::around(&earth, OrbitalElements::circular(0.00257, 0.0), 3.0e-6);
+        for k in 0..8 {
+            let time = k as f64 * 11.0 * DAY;
+            let (earth_position, earth_velocity) = earth.state_at(time);
+            let (moon_position, moon_velocity) = moon.state_at(time);
+            let (local_position, local_velocity) = moon.elements.state_at(time, moon.mu());
+            assert!((moon_position - earth_position - local_position).length() < 1.0e-3);
+            assert!((moon_velocity - earth_velocity - local_velocity).length() < 1.0e-9);
+        }
+    }
+
+    #[test]
+    fn test_stationary_orbit() {
+        let (position, velocity) = Orbit::stationary().state_at(1.0e9);
+        assert_eq!(position, DVec3::ZERO);
+        assert_eq!(velocity, DVec3::ZERO);
+    }
+}
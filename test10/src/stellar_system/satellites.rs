@@ -0,0 +1,229 @@
+use std::f64::consts::PI;
+
+use super::orbit::{Orbit, OrbitalElements};
+use super::stellar_system::{PlanetClass, PlanetPhysicalProperties, ASTRONOMICAL_UNIT, EARTH_MASS, RNG, SOLAR_MASS};
+use super::habitability::Habitability;
+
+pub const EARTH_RADIUS: f64 = 6.371e6; // m
+// Masse volumique moyenne de la Terre et d'un satellite glacé / rocheux, en kg/m³
+const EARTH_DENSITY: f64 = 5514.0;
+const RING_PARTICLE_DENSITY: f64 = 900.0; // glace d'eau
+// Les orbites progrades restent stables jusqu'à ~0.5 rayon de Hill
+const MOON_MAX_HILL_FRACTION: f64 = 0.4;
+// Les satellites se forment hors de la limite de Roche, avec une marge
+const MOON_MIN_ROCHE_FACTOR: f64 = 1.5;
+const MOON_MAX_ECCENTRICITY: f64 = 0.05;
+const MOON_MAX_INCLINATION: f64 = 3.0 * PI / 180.0;
+// Résolution radiale du profil de densité des anneaux
+pub const RING_PROFILE_SAMPLES: usize = 64;
+
+/// Rayon de Hill (en UA) d'un corps de masse `mass` (en masses solaires) sur `orbit`
+pub fn hill_radius(orbit: &OrbitalElements, mass: f64, central_mass: f64) -> f64 {
+    if central_mass <= 0.0 {
+        return f64::INFINITY;
+    }
+    orbit.periapsis() * (mass / (3.0 * central_mass)).cbrt()
+}
+
+/// Limite de Roche fluide (en m) d'un corps de rayon `radius` (m) et masse volumique `density`
+/// pour des satellites de masse volumique `satellite_density`
+pub fn roche_limit(radius: f64, density: f64, satellite_density: f64) -> f64 {
+    2.44 * radius * (density / satellite_density).cbrt()
+}
+
+/// Masse volumique moyenne (kg/m³) d'une planète en masses et rayons terrestres
+pub fn planet_density(props: &PlanetPhysicalProperties) -> f64 {
+    EARTH_DENSITY * props.mass / props.radius.powi(3)
+}
+
+/// Système d'anneaux dans le plan équatorial d'une géante
+#[derive(Debug, Clone, PartialEq)]
+pub struct RingSystem {
+    pub inner_radius: f64, // en rayons de la planète
+    pub outer_radius: f64, // en rayons de la planète
+    /// Inclinaison du plan des anneaux par rapport au plan orbital, en radians
+    pub tilt: f64,
+    /// Opacité (0 à 1) échantillonnée régulièrement entre inner_radius et outer_radius
+    pub density_profile: Vec<f32>,
+}
+
+impl RingSystem {
+    /// Anneaux contenus dans la limite de Roche de la planète, avec quelques divisions
+    pub fn generate(rng: &mut RNG, planet: &PlanetPhysicalProperties) -> Option<Self> {
+        let radius = planet.radius * EARTH_RADIUS;
+        let roche = roche_limit(radius, planet_density(planet), RING_PARTICLE_DENSITY) / radius;
+        let inner_radius = rng.f64(1.1, 1.5);
+        let outer_radius = rng.f64(inner_radius + 0.3, roche.max(inner_radius + 0.3)).min(roche);
+        if outer_radius <= inner_radius {
+            return None;
+        }
+
+        // Profil : densité de base modulée, avec des divisions sans particules
+        let base = rng.f64(0.3, 0.9) as f32;
+        let gaps: Vec<(f32, f32)> = (0..rng.u32(1, 4))
+            .map(|_| (rng.f64(0.15, 0.9) as f32, rng.f64(0.01, 0.05) as f32))
+            .collect();
+        let phase = rng.f64(0.0, 2.0 * PI) as f32;
+        let density_profile = (0..RING_PROFILE_SAMPLES).map(|i| {
+            let t = i as f32 / (RING_PROFILE_SAMPLES - 1) as f32;
+            // Bords adoucis et bandes fines
+            let edge = (t * 10.0).min((1.0 - t) * 10.0).min(1.0);
+            let bands = 0.75 + 0.25 * (t * 40.0 + phase).sin();
+            let in_gap = gaps.iter().any(|(center, width)| (t - center).abs() < *width);
+            if in_gap { 0.0 } else { (base * edge * bands).clamp(0.0, 1.0) }
+        }).collect();
+
+        Some(Self {
+            inner_radius,
+            outer_radius,
+            tilt: rng.f64(0.0, 0.5),
+            density_profile,
+        })
+    }
+
+    /// Opacité à `radius` (en rayons de la planète), nulle hors des anneaux
+    pub fn density_at(&self, radius: f64) -> f32 {
+        if radius < self.inner_radius || radius > self.outer_radius {
+            return 0.0;
+        }
+        let t = (radius - self.inner_radius) / (self.outer_radius - self.inner_radius);
+        let index = (t * (RING_PROFILE_SAMPLES - 1) as f64).round() as usize;
+        self.density_profile[index.min(RING_PROFILE_SAMPLES - 1)]
+    }
+}
+
+/// Bornes (en UA) des orbites de satellites stables autour d'une planète, None si la sphère de Hill
+/// est trop petite
+pub fn moon_orbit_bounds(planet: &PlanetPhysicalProperties, orbit: &Orbit) -> Option<(f64, f64)> {
+    let mass = planet.mass * EARTH_MASS / SOLAR_MASS;
+    let hill = hill_radius(&orbit.elements, mass, orbit.central_mass);
+    let radius = planet.radius * EARTH_RADIUS;
+    let roche = roche_limit(radius, planet_density(planet), EARTH_DENSITY * 0.6);
+    let min = MOON_MIN_ROCHE_FACTOR * roche / ASTRONOMICAL_UNIT;
+    let max = MOON_MAX_HILL_FRACTION * hill;
+    if min < max { Some((min, max)) } else { None }
+}
+
+/// Satellites d'une planète : orbites dans sa sphère de Hill, hors de sa limite de Roche
+pub fn generate_moons(rng: &mut RNG, planet: &PlanetPhysicalProperties, orbit: &Orbit) -> Vec<(PlanetPhysicalProperties, Orbit)> {
+    let Some((min, max)) = moon_orbit_bounds(planet, orbit) else {
+        return Vec::new();
+    };
+    let max_moons = if planet.class == PlanetClass::GasGiant { 5 } else { 2 };
+    let mut count = 0;
+    while count < max_moons && rng.gen_norm() < 0.5 {
+        count += 1;
+    }
+
+    let central_mass = planet.mass * EARTH_MASS / SOLAR_MASS;
+    let mut moons = Vec::with_capacity(count);
+    let mut semi_major_axis = min;
+    for _ in 0..count {
+        semi_major_axis *= rng.f64(1.5, 3.0);
+        if semi_major_axis > max {
+            break;
+        }
+        // Au plus 1 % de la masse de la planète, jamais plus massif que Mars
+        let mass = (planet.mass * rng.f64(1.0e-5, 1.0e-2)).min(0.1);
+        let radius = mass.powf(0.3);
+        let elements = OrbitalElements {
+            semi_major_axis,
+            eccentricity: MOON_MAX_ECCENTRICITY * rng.gen_norm().powi(2),
+            inclination: rng.f64(0.0, MOON_MAX_INCLINATION),
+            longitude_ascending_node: rng.f64(0.0, 2.0 * PI),
+            argument_periapsis: rng.f64(0.0, 2.0 * PI),
+            mean_anomaly_epoch: rng.f64(0.0, 2.0 * PI),
+        };
+        let props = PlanetPhysicalProperties {
+            mass,
+            radius,
+            albedo: rng.f64(0.1, 0.7),
+            water_fraction: 0.0,
+            terrain_seed: 0, // dérivée de la graine du corps par le système
+            insolation: 0.0,
+            equilibrium_temperature: 0.0,
+            habitability: Habitability::TooCold,
+            class: PlanetClass::classify(mass, radius, 0.0, 0.0),
+        };
+        moons.push((props, Orbit::around(orbit, elements, central_mass)));
+    }
+    moons
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec3;
+
+    fn jupiter() -> (PlanetPhysicalProperties, Orbit) {
+        let props = PlanetPhysicalProperties {
+            mass: 317.8,
+            radius: 11.2,
+            albedo: 0.34,
+            water_fraction: 0.0,
+            terrain_seed: 1,
+            insolation: 0.037,
+            equilibrium_temperature: 110.0,
+            habitability: Habitability::TooCold,
+            class: PlanetClass::GasGiant,
+        };
+        (props, Orbit::new(OrbitalElements::circular(5.2, 0.0), 1.0))
+    }
+
+    #[test]
+    fn test_hill_radius_of_earth() {
+        // ≈ 0.01 UA (1.5 million de km)
+        let hill = hill_radius(&OrbitalElements::circular(1.0, 0.0), EARTH_MASS / SOLAR_MASS, 1.0);
+        assert!((hill - 0.01).abs() < 0.0005, "rayon de Hill {}", hill);
+    }
+
+    #[test]
+    fn test_roche_limit() {
+        // Saturne : limite de Roche fluide pour la glace ≈ 2.2 rayons saturniens
+        let ratio = roche_limit(1.0, 687.0, RING_PARTICLE_DENSITY);
+        assert!((ratio - 2.23).abs() < 0.05, "limite de Roche {}", ratio);
+    }
+
+    #[test]
+    fn test_moons_inside_hill_sphere() {
+        let (planet, orbit) = jupiter();
+        let (min, max) = moon_orbit_bounds(&planet, &orbit).unwrap();
+        let hill = hill_radius(&orbit.elements, planet.mass * EARTH_MASS / SOLAR_MASS, 1.0);
+        for i in 0..20 {
+            let mut rng = RNG::new(Vec3::new(i as f32, 0.0, 0.0));
+            for (moon, moon_orbit) in generate_moons(&mut rng, &planet, &orbit) {
+                let a = moon_orbit.elements.semi_major_axis;
+                assert!(a >= min && a <= max && a < 0.5 * hill);
+                assert!(moon.mass <= planet.mass * 0.01);
+                assert_eq!(moon_orbit.parent.as_deref(), Some(&orbit));
+            }
+        }
+    }
+
+    #[test]
+    fn test_no_moons_without_hill_sphere() {
+        let (planet, _) = jupiter();
+        // Trop près d'une étoile massive : la sphère de Hill est sous la limite de Roche
+        let orbit = Orbit::new(OrbitalElements::circular(0.01, 0.0), 100.0);
+        assert!(moon_orbit_bounds(&planet, &orbit).is_none());
+        let mut rng = RNG::new(Vec3::ZERO);
+        assert!(generate_moons(&mut rng, &planet, &orbit).is_empty());
+    }
+
+    #[test]
+    fn test_rings_inside_roche_limit() {
+        let (planet, _) = jupiter();
+        let radius = planet.radius * EARTH_RADIUS;
+        let roche = roche_limit(radius, planet_density(&planet), RING_PARTICLE_DENSITY) / radius;
+        for i in 0..20 {
+            let mut rng = RNG::new(Vec3::new(0.0, i as f32, 0.0));
+            let rings = RingSystem::generate(&mut rng, &planet).unwrap();
+            assert!(rings.inner_radius > 1.0);
+            assert!(rings.outer_radius <= roche);
+            assert_eq!(rings.density_profile.len(), RING_PROFILE_SAMPLES);
+            assert!(rings.density_profile.iter().all(|d| (0.0..=1.0).contains(d)));
+            assert_eq!(rings.density_at(rings.inner_radius - 0.01), 0.0);
+            assert_eq!(rings.density_at(rings.outer_radius + 0.01), 0.0);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use glam::{IVec3, Vec3};
+
+// Version du schéma de dérivation : toute modification des fonctions ci-dessous change l'univers
+// généré et doit s'accompagner d'une nouvelle version (et de nouvelles valeurs de référence)
+pub const SEED_VERSION: u32 = 1;
+// Graine de l'univers par défaut
+pub const DEFAULT_UNIVERSE_SEED: u64 = 0x5EED_0000_0000_0001;
+// Arête d'un secteur, en parsecs (de l'ordre de 100 systèmes au voisinage solaire)
+pub const SECTOR_SIZE: f32 = 10.0;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Hachage FNV-1a 64 bits, stable d'une version de Rust à l'autre (contrairement à `DefaultHasher`)
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, &byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+}
+
+/// Finaliseur SplitMix64 : répartit les bits d'une valeur de hachage
+pub fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+/// Graine hiérarchique : univers → secteur → système → corps → terrain.
+/// Chaque niveau est dérivé du précédent par `derive`, indépendamment des tirages des autres niveaux.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Seed(u64);
+
+impl Seed {
+    pub fn universe(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn value(self) -> u64 {
+        self.0
+    }
+
+    /// Graine enfant identifiée par une étiquette et un indice
+    pub fn derive(self, label: &str, index: u64) -> Self {
+        let mut bytes = Vec::with_capacity(20 + label.len());
+        bytes.extend_from_slice(&SEED_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.0.to_le_bytes());
+        bytes.extend_from_slice(label.as_bytes());
+        bytes.extend_from_slice(&index.to_le_bytes());
+        Self(splitmix64(fnv1a(&bytes)))
+    }
+
+    pub fn sector(self, coords: IVec3) -> Self {
+        // Coordonnées signées empaquetées sur 21 bits chacune
+        let pack = |c: i32| (c as u64) & 0x1F_FFFF;
+        self.derive("sector", pack(coords.x) | pack(coords.y) << 21 | pack(coords.z) << 42)
+    }
+
+    pub fn system(self, index: u64) -> Self {
+        self.derive("system", index)
+    }
+
+    /// Système situé à `position` : secteur qui le contient, puis indice tiré de la position exacte
+    pub fn system_at(self, position: Vec3) -> Self {
+        self.sector(sector_of(position)).system(system_index_at(position))
+    }
+
+    pub fn body(self, index: u64) -> Self {
+        self.derive("body", index)
+    }
+
+    pub fn terrain(self) -> Self {
+        self.derive("terrain", 0)
+    }
+}
+
+/// Indice d'un système placé à `position` hors du recensement de son secteur, tiré de la
+/// position exacte ; distinct des rangs du recensement, qui restent petits
+pub fn system_index_at(position: Vec3) -> u64 {
+    let bits = [position.x, position.y, position.z].map(|c| c.to_bits().to_le_bytes()).concat();
+    fnv1a(&bits)
+}
+
+/// Coordonnées du secteur contenant `position`
+pub fn sector_of(position: Vec3) -> IVec3 {
+    (position / SECTOR_SIZE).floor().as_ivec3()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fnv1a_reference_values() {
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn test_splitmix64_reference_values() {
+        assert_eq!(splitmix64(0), 0xe220a8397b1dcdaf);
+        assert_eq!(splitmix64(1), 0x910a2dec89025cc1);
+    }
+
+    #[test]
+    fn test_derivation_is_independent() {
+        let universe = Seed::universe(DEFAULT_UNIVERSE_SEED);
+        let system = universe.sector(IVec3::new(-1, 0, 2)).system(3);
+        assert_eq!(system, universe.sector(IVec3::new(-1, 0, 2)).system(3));
+        assert_ne!(system, universe.sector(IVec3::new(-1, 0, 2)).system(4));
+        assert_ne!(system, universe.sector(IVec3::new(1, 0, 2)).system(3));
+        assert_ne!(system.body(0), system.body(1));
+        assert_ne!(system.body(0).terrain(), system.body(0));
+        assert_ne!(Seed::universe(1).system(0), Seed::universe(2).system(0));
+    }
+
+    #[test]
+    fn test_golden_seeds() {
+        // Valeurs de référence du schéma SEED_VERSION = 1 : un échec signifie que l'univers a changé
+        let system = Seed::universe(DEFAULT_UNIVERSE_SEED).system_at(Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(system.value(), 0x0f0192cf74d69b24);
+        assert_eq!(system.body(1).value(), 0xd427d53cb1d0613e);
+        assert_eq!(system.body(1).terrain().value(), 0x5e7751388cd31eea);
+    }
+
+    #[test]
+    fn test_sector_of() {
+        assert_eq!(sector_of(Vec3::new(0.1, 9.9, 0.0)), IVec3::ZERO);
+        assert_eq!(sector_of(Vec3::new(-0.5, 10.0, 25.0)), IVec3::new(-1, 1, 2));
+    }
+}
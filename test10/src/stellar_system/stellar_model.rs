@@ -0,0 +1,222 @@
+use super::stellar_system::{G, SOLAR_MASS};
+
+// Valeurs nominales IAU 2015 (unités solaires -> SI)
+pub const SOLAR_LUMINOSITY: f64 = 3.828e26;  // W
+pub const SOLAR_RADIUS: f64 = 6.957e8;       // m
+pub const SOLAR_TEMPERATURE: f64 = 5772.0;   // K
+// Durée de vie du Soleil sur la séquence principale, en milliards d'années
+const SOLAR_LIFETIME: f64 = 10.0;
+// Fraction de masse en métaux du Soleil (Asplund et al. 2009)
+pub const SOLAR_METALLICITY: f64 = 0.0134;
+
+// Bornes de la fonction de masse initiale : limite de combustion de l'hydrogène et étoiles les plus massives
+pub const MIN_STAR_MASS: f64 = 0.08;  // en masses solaires
+pub const MAX_STAR_MASS: f64 = 150.0; // en masses solaires
+// Fonction de masse initiale de Kroupa (2001) : dN/dM ∝ M^-alpha, pente de Salpeter au-delà de 0.5
+const KROUPA_BREAK_MASS: f64 = 0.5;
+const KROUPA_LOW_ALPHA: f64 = 1.3;
+const KROUPA_HIGH_ALPHA: f64 = 2.3;
+
+// Évolution post-séquence principale
+const SPEED_OF_LIGHT: f64 = 2.99792458e8; // m/s
+// Durée de la phase de géante, en fraction de la durée de vie sur la séquence principale
+pub const GIANT_PHASE_FRACTION: f64 = 0.1;
+// Masses initiales (en masses solaires) au-delà desquelles le cœur s'effondre en étoile à neutrons / trou noir
+pub const NEUTRON_STAR_MIN_MASS: f64 = 8.0;
+pub const BLACK_HOLE_MIN_MASS: f64 = 20.0;
+const GIANT_TEMPERATURE: f64 = 4000.0;      // K
+const SUPERGIANT_TEMPERATURE: f64 = 3600.0; // K
+const CHANDRASEKHAR_MASS: f64 = 1.38;       // en masses solaires
+pub const NEUTRON_STAR_MASS: f64 = 1.4;     // en masses solaires
+const NEUTRON_STAR_RADIUS: f64 = 1.2e4;     // m
+const NEUTRON_STAR_TEMPERATURE: f64 = 6.0e5; // K
+// Fraction de la masse initiale conservée par un trou noir stellaire
+const BLACK_HOLE_MASS_FRACTION: f64 = 0.3;
+
+/// Luminosité (en luminosités solaires) d'une étoile de la séquence principale de masse `mass`
+/// (en masses solaires), relation par morceaux de Duric (2004). La cassure à 2 M☉ est placée à
+/// l'intersection des deux lois pour garder une luminosité croissante.
+pub fn mass_luminosity(mass: f64) -> f64 {
+    if mass < 0.43 {
+        0.23 * mass.powf(2.3)
+    } else if mass < 1.96 {
+        mass.powi(4)
+    } else if mass < 55.0 {
+        1.4 * mass.powf(3.5)
+    } else {
+        32000.0 * mass
+    }
+}
+
+/// Rayon (en rayons solaires) d'une étoile de la séquence principale de masse `mass` (en masses solaires)
+pub fn mass_radius(mass: f64) -> f64 {
+    if mass < 1.0 {
+        mass.powf(0.8)
+    } else {
+        mass.powf(0.57)
+    }
+}
+
+/// Température effective (K) d'une étoile de luminosité et rayon en unités solaires (Stefan-Boltzmann)
+pub fn effective_temperature(luminosity: f64, radius: f64) -> f64 {
+    SOLAR_TEMPERATURE * (luminosity / (radius * radius)).powf(0.25)
+}
+
+/// Durée de vie sur la séquence principale (en milliards d'années) : réserve d'hydrogène ∝ M, consommée ∝ L
+pub fn main_sequence_lifetime(mass: f64, luminosity: f64) -> f64 {
+    SOLAR_LIFETIME * mass / luminosity
+}
+
+/// Classe spectrale de Harvard d'après la température effective (K)
+pub fn spectral_class(temperature: f64) -> &'static str {
+    match temperature as u32 {
+        t if t >= 30000 => "O",
+        t if t >= 10000 => "B",
+        t if t >= 7500  => "A",
+        t if t >= 6000  => "F",
+        t if t >= 5200  => "G",
+        t if t >= 3700  => "K",
+        _              => "M",
+    }
+}
+
+/// Luminosité moyenne (en luminosités solaires) d'une géante rouge ou supergéante de masse initiale `mass`
+pub fn giant_luminosity(mass: f64) -> f64 {
+    500.0 * mass.powf(1.5)
+}
+
+/// Température effective (K) d'une géante de masse initiale `mass`
+pub fn giant_temperature(mass: f64) -> f64 {
+    if mass < NEUTRON_STAR_MIN_MASS { GIANT_TEMPERATURE } else { SUPERGIANT_TEMPERATURE }
+}
+
+/// Rayon (en rayons solaires) d'une étoile de luminosité en luminosités solaires et de température en Kelvin
+pub fn radius_from_luminosity(luminosity: f64, temperature: f64) -> f64 {
+    luminosity.sqrt() / (temperature / SOLAR_TEMPERATURE).powi(2)
+}
+
+/// Masse (en masses solaires) de la naine blanche laissée par une étoile de masse initiale `mass`
+/// (relation masse initiale - masse finale de Kalirai et al. 2008)
+pub fn white_dwarf_mass(mass: f64) -> f64 {
+    (0.109 * mass + 0.394).min(CHANDRASEKHAR_MASS)
+}
+
+/// Rayon (en rayons solaires) d'une naine blanche : plus elle est massive, plus elle est compacte
+pub fn white_dwarf_radius(mass: f64) -> f64 {
+    0.0126 * mass.powf(-1.0 / 3.0)
+}
+
+/// Luminosité (en luminosités solaires) d'une naine blanche refroidie depuis `cooling_age`
+/// milliards d'années (loi de Mestel, L ∝ M t^-7/5)
+pub fn white_dwarf_luminosity(mass: f64, cooling_age: f64) -> f64 {
+    3.5e-3 * mass * cooling_age.max(1.0e-3).powf(-1.4)
+}
+
+pub fn neutron_star_radius() -> f64 {
+    NEUTRON_STAR_RADIUS / SOLAR_RADIUS
+}
+
+pub fn neutron_star_temperature() -> f64 {
+    NEUTRON_STAR_TEMPERATURE
+}
+
+/// Masse (en masses solaires) du trou noir laissé par une étoile de masse initiale `mass`
+pub fn black_hole_mass(mass: f64) -> f64 {
+    BLACK_HOLE_MASS_FRACTION * mass
+}
+
+/// Rayon de Schwarzschild (en rayons solaires) d'une masse en masses solaires
+pub fn schwarzschild_radius(mass: f64) -> f64 {
+    2.0 * G * mass * SOLAR_MASS / (SPEED_OF_LIGHT * SPEED_OF_LIGHT) / SOLAR_RADIUS
+}
+
+/// Tirage par inversion de la loi de puissance dN/dM ∝ M^-alpha entre `min` et `max`, pour `u` dans [0, 1)
+fn sample_power_law(u: f64, min: f64, max: f64, alpha: f64) -> f64 {
+    let k = 1.0 - alpha;
+    (min.powf(k) + u * (max.powf(k) - min.powf(k))).powf(1.0 / k)
+}
+
+/// Nombre relatif d'étoiles de part et d'autre de la cassure de Kroupa (fonction continue en 0.5)
+fn kroupa_weights() -> (f64, f64) {
+    let integral = |min: f64, max: f64, alpha: f64| (max.powf(1.0 - alpha) - min.powf(1.0 - alpha)) / (1.0 - alpha);
+    let low = integral(MIN_STAR_MASS, KROUPA_BREAK_MASS, KROUPA_LOW_ALPHA);
+    let high = KROUPA_BREAK_MASS.powf(KROUPA_HIGH_ALPHA - KROUPA_LOW_ALPHA)
+        * integral(KROUPA_BREAK_MASS, MAX_STAR_MASS, KROUPA_HIGH_ALPHA);
+    (low, high)
+}
+
+/// Masse (en masses solaires) tirée selon la fonction de masse initiale de Kroupa ;
+/// `segment` et `u` sont deux tirages uniformes dans [0, 1)
+pub fn sample_kroupa_mass(segment: f64, u: f64) -> f64 {
+    let (low, high) = kroupa_weights();
+    if segment * (low + high) < low {
+        sample_power_law(u, MIN_STAR_MASS, KROUPA_BREAK_MASS, KROUPA_LOW_ALPHA)
+    } else {
+        sample_power_law(u, KROUPA_BREAK_MASS, MAX_STAR_MASS, KROUPA_HIGH_ALPHA)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solar_relations() {
+        assert!((mass_luminosity(1.0) - 1.0).abs() < 1.0e-12);
+        assert!((mass_radius(1.0) - 1.0).abs() < 1.0e-12);
+        assert!((effective_temperature(1.0, 1.0) - 5778.0).abs() < 10.0);
+        assert!((main_sequence_lifetime(1.0, 1.0) - 10.0).abs() < 1.0e-12);
+        assert_eq!(spectral_class(5772.0), "G");
+    }
+
+    #[test]
+    fn test_relations_are_monotonic() {
+        let masses: Vec<f64> = (1..400).map(|i| MIN_STAR_MASS * 1.015_f64.powi(i)).collect();
+        for pair in masses.windows(2) {
+            assert!(mass_luminosity(pair[1]) > mass_luminosity(pair[0]));
+            assert!(mass_radius(pair[1]) > mass_radius(pair[0]));
+            // Les étoiles massives brûlent leur hydrogène plus vite
+            let lifetime = |m: f64| main_sequence_lifetime(m, mass_luminosity(m));
+            assert!(lifetime(pair[1]) < lifetime(pair[0]));
+        }
+    }
+
+    #[test]
+    fn test_reference_stars() {
+        // Naine rouge de 0.2 M☉ : ~0.005 L☉, type M ; étoile de 10 M☉ : ~4400 L☉, type B
+        let dwarf = mass_luminosity(0.2);
+        assert!(dwarf > 0.002 && dwarf < 0.01, "luminosité {}", dwarf);
+        assert_eq!(spectral_class(effective_temperature(dwarf, mass_radius(0.2))), "M");
+        let massive = mass_luminosity(10.0);
+        assert!(massive > 3000.0 && massive < 6000.0, "luminosité {}", massive);
+        assert_eq!(spectral_class(effective_temperature(massive, mass_radius(10.0))), "B");
+    }
+
+    #[test]
+    fn test_remnants() {
+        // Sirius B : ~1 M☉, rayon terrestre (~0.008 R☉)
+        assert!((white_dwarf_mass(5.0) - 0.94).abs() < 0.01);
+        assert!((white_dwarf_radius(1.0) - 0.0126).abs() < 1.0e-6);
+        assert!(white_dwarf_mass(7.9) <= CHANDRASEKHAR_MASS);
+        // Une naine blanche s'assombrit en refroidissant
+        assert!(white_dwarf_luminosity(0.6, 1.0) < white_dwarf_luminosity(0.6, 0.1));
+        // 2.95 km par masse solaire
+        assert!((schwarzschild_radius(1.0) * SOLAR_RADIUS - 2953.0).abs() < 5.0);
+        // Une géante de 1 M☉ est cent fois plus grande que le Soleil
+        let radius = radius_from_luminosity(giant_luminosity(1.0), giant_temperature(1.0));
+        assert!(radius > 20.0 && radius < 200.0, "rayon {}", radius);
+    }
+
+    #[test]
+    fn test_kroupa_sampling() {
+        let n = 10_000;
+        let masses: Vec<f64> = (0..n)
+            .map(|i| sample_kroupa_mass((i as f64 * 0.618_034).fract(), (i as f64 + 0.5) / n as f64))
+            .collect();
+        assert!(masses.iter().all(|m| (MIN_STAR_MASS..=MAX_STAR_MASS).contains(m)));
+        // La grande majorité des étoiles sont des naines rouges
+        let dwarfs = masses.iter().filter(|&&m| m < KROUPA_BREAK_MASS).count();
+        assert!(dwarfs > n * 7 / 10 && dwarfs < n * 85 / 100, "{} naines", dwarfs);
+        assert!(masses.iter().any(|&m| m > 8.0));
+    }
+}
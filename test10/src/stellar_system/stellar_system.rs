@@ -1,33 +1,53 @@
 use std::f64::consts::PI;
-use std::hash::{Hash, Hasher};
-use std::collections::hash_map::DefaultHasher;
-use glam::Vec3;
+use glam::{IVec3, Vec3};
+
+use super::orbit::{Orbit, OrbitalElements};
+use super::nbody::{ConservationDiagnostics, Integrator, NBodyState, SimulationMode};
+use super::multiplicity::{PlanetaryZone, StellarHierarchy};
+use super::habitability::{self, Habitability};
+use super::satellites::{generate_moons, RingSystem, EARTH_RADIUS};
+use super::asteroid_belt::AsteroidBelt;
+use super::seed::{sector_of, system_index_at, Seed, DEFAULT_UNIVERSE_SEED};
+use super::naming::{catalog_designation, generate_name, planet_letter, roman_numeral, star_letter};
+use crate::galaxy::density::GalacticContext;
+use super::stellar_model::{
+    black_hole_mass, effective_temperature, giant_luminosity, giant_temperature, main_sequence_lifetime,
+    mass_luminosity, mass_radius, neutron_star_radius, neutron_star_temperature, radius_from_luminosity,
+    sample_kroupa_mass, schwarzschild_radius, spectral_class, white_dwarf_luminosity, white_dwarf_mass,
+    white_dwarf_radius, BLACK_HOLE_MIN_MASS, GIANT_PHASE_FRACTION, MAX_STAR_MASS, NEUTRON_STAR_MASS,
+    NEUTRON_STAR_MIN_MASS, SOLAR_LUMINOSITY, SOLAR_METALLICITY, SOLAR_RADIUS, SOLAR_TEMPERATURE,
+};
 
 // Constante
-const G: f64 = 6.67430e-11;
-const STEFAN_BOLTZMANN: f64 = 5.670374419e-8;
-// 1 000                                                10**3
-// 1 000 000 kg = 1 kilotonne (kt)                      10**6
-// 1 000 000 000 kg = 1 mégatonne (Mt)                  10**9
-// 1 000 000 000 000 kg = 1 gigatonne (Gt)              10**12
+pub const G: f64 = 6.67430e-11;
+pub const STEFAN_BOLTZMANN: f64 = 5.670374419e-8;
+pub const SOLAR_MASS: f64 = 1.98847e30;          // kg
+pub const EARTH_MASS: f64 = 5.9722e24;           // kg
+pub const ASTRONOMICAL_UNIT: f64 = 1.495978707e11; // m
+// Echelle de rendu : nombre de mètres représentés par une unité de scène
+pub const SCENE_UNIT: f64 = 1.0e10;
+// Agrandissement de l'écart d'un satellite à sa planète : les planètes sont affichées en rayons
+// terrestres, l'orbite d'un satellite l'est aussi, à l'extérieur du maillage de son hôte
+pub const MOON_ORBIT_DISPLAY_SCALE: f64 = SCENE_UNIT / EARTH_RADIUS;
 
-// 1 000 000 000 000 000 kg = 1 tératonne (Tt)          10**15
+// Bornes des orbites planétaires
+pub const PLANET_MIN_SEMI_MAJOR_AXIS: f64 = 0.3;  // en UA
+pub const PLANET_MAX_SEMI_MAJOR_AXIS: f64 = 25.0; // en UA
+pub const PLANET_MAX_ECCENTRICITY: f64 = 0.3;
+pub const PLANET_MAX_INCLINATION: f64 = 5.0 * PI / 180.0; // en radians
 
-// 1 000 000 000 000 000 000 kg = 1 pétatonne (Pt)      10**18
+// Âge des systèmes générés, en milliards d'années
+const SYSTEM_MIN_AGE: f64 = 0.1;
+const SYSTEM_MAX_AGE: f64 = 13.0;
+// Rayon affiché d'une étoile : ∝ sqrt(R) pour garder géantes et résidus compacts visibles ensemble
+const STAR_MIN_RENDER_RADIUS: f64 = 0.05; // en unités de scène
 
-// 1 000 000 000 000 000 000 000 kg = 1 exatonne (Et)   10**21
+// Intégration N corps : pas de temps = plus courte période / NBODY_STEPS_PER_ORBIT
+const NBODY_STEPS_PER_ORBIT: f64 = 500.0;
+const NBODY_DEFAULT_STEP: f64 = 3600.0; // s
+// Borne le coût d'une mise à jour (au détriment de la précision)
+const NBODY_MAX_STEPS_PER_UPDATE: u32 = 10_000;
 
-// zetta  10**24
-
-// yottatonne  10**27
-
-
-// #[repr(transparent)]
-// struct Yt(u32);
-
-// type Et = u32;
-// type Zt = u32;
-type Yt = f64;
 
 
 
@@ -39,18 +59,19 @@ pub struct RNG {
 }
 
 impl RNG {
+    /// Générateur du système situé à `pos` dans l'univers par défaut
     pub fn new(pos: Vec3) -> Self {
-        // Hash la position pour obtenir une seed
-        let mut hasher = DefaultHasher::new();
-        pos.x.to_bits().hash(&mut hasher);
-        pos.y.to_bits().hash(&mut hasher);
-        pos.z.to_bits().hash(&mut hasher);
-        let seed = hasher.finish();
-        Self { state: seed }
+        Self::from_seed(Seed::universe(DEFAULT_UNIVERSE_SEED).system_at(pos).value())
+    }
+
+    /// Générateur initialisé directement depuis une graine (l'état de xorshift ne doit pas être nul)
+    pub fn from_seed(seed: u64) -> Self {
+        let state = seed ^ 0x9E3779B97F4A7C15;
+        Self { state: if state == 0 { 0x9E3779B97F4A7C15 } else { state } }
     }
 
     // Xorshift64*
-    fn next_u64(&mut self) -> u64 {
+    pub fn next_u64(&mut self) -> u64 {
         let mut x = self.state;
         x ^= x >> 12;
         x ^= x << 25;
@@ -86,16 +107,49 @@ impl RNG {
     pub fn f64(&mut self, min: f64, max: f64) -> f64 {
         min + (max - min) * self.gen_norm()
     }
+
+    /// Loi normale (Box-Muller)
+    pub fn normal(&mut self, mean: f64, std_dev: f64) -> f64 {
+        let u1 = 1.0 - self.gen_norm(); // dans ]0, 1] pour le logarithme
+        let u2 = self.gen_norm();
+        mean + std_dev * (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+    }
+
+    /// Loi exponentielle de paramètre `rate` (moyenne 1 / rate)
+    pub fn exponential(&mut self, rate: f64) -> f64 {
+        -(1.0 - self.gen_norm()).ln() / rate
+    }
+
+    /// Indice tiré proportionnellement à `weights` ; None si aucun poids n'est positif
+    pub fn weighted_choice(&mut self, weights: &[f64]) -> Option<usize> {
+        let total: f64 = weights.iter().filter(|w| **w > 0.0).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut target = self.gen_norm() * total;
+        for (i, &weight) in weights.iter().enumerate() {
+            if weight <= 0.0 {
+                continue;
+            }
+            if target < weight {
+                return Some(i);
+            }
+            target -= weight;
+        }
+        weights.iter().rposition(|w| *w > 0.0)
+    }
 }
 
 pub enum CelestialBody {
     Star(Star),
-    Planet(Planet)
+    Planet(Planet),
+    Moon(Moon)
 }
 
 pub struct Star {
     pub name: String,
     pub physical_props: StarPhysicalProperties,
+    pub orbit: Orbit,
     pub position: glam::DVec3,
     pub velocity: glam::DVec3,
 }
@@ -103,37 +157,271 @@ pub struct Star {
 pub struct Planet {
     pub name: String,
     pub physical_props: PlanetPhysicalProperties,
+    pub orbit: Orbit,
+    pub rings: Option<RingSystem>,
+    pub position: glam::Vec3,
+    pub velocity: glam::Vec3,
+}
+
+/// Satellite naturel, en orbite autour de la planète `host` (indice dans `StellarSystem::bodies`)
+pub struct Moon {
+    pub name: String,
+    pub physical_props: PlanetPhysicalProperties,
+    pub orbit: Orbit,
+    pub host: usize,
     pub position: glam::Vec3,
     pub velocity: glam::Vec3,
 }
 
+/// État SI d'un satellite pour l'affichage : son écart à l'hôte est agrandi de
+/// MOON_ORBIT_DISPLAY_SCALE, pour que l'orbite entoure le maillage de la planète
+fn moon_display_state(host: (glam::DVec3, glam::DVec3), moon: (glam::DVec3, glam::DVec3)) -> (glam::DVec3, glam::DVec3) {
+    (
+        host.0 + (moon.0 - host.0) * MOON_ORBIT_DISPLAY_SCALE,
+        host.1 + (moon.1 - host.1) * MOON_ORBIT_DISPLAY_SCALE,
+    )
+}
+
+impl CelestialBody {
+    pub fn orbit(&self) -> &Orbit {
+        match self {
+            CelestialBody::Star(star) => &star.orbit,
+            CelestialBody::Planet(planet) => &planet.orbit,
+            CelestialBody::Moon(moon) => &moon.orbit,
+        }
+    }
+
+    /// Position courante en unités de scène
+    pub fn position(&self) -> Vec3 {
+        match self {
+            CelestialBody::Star(star) => star.position.as_vec3(),
+            CelestialBody::Planet(planet) => planet.position,
+            CelestialBody::Moon(moon) => moon.position,
+        }
+    }
+
+    /// Masse du corps, en kg
+    pub fn mass_kg(&self) -> f64 {
+        match self {
+            CelestialBody::Star(star) => star.physical_props.mass * SOLAR_MASS,
+            CelestialBody::Planet(planet) => planet.physical_props.mass * EARTH_MASS,
+            CelestialBody::Moon(moon) => moon.physical_props.mass * EARTH_MASS,
+        }
+    }
+
+    /// Met à jour position et vitesse à partir d'un état SI relatif à `barycenter` (unités de scène)
+    pub fn set_state(&mut self, position: glam::DVec3, velocity: glam::DVec3, barycenter: glam::DVec3) {
+        let position = barycenter + position / SCENE_UNIT;
+        let velocity = velocity / SCENE_UNIT;
+        match self {
+            CelestialBody::Star(star) => {
+                star.position = position;
+                star.velocity = velocity;
+            }
+            CelestialBody::Planet(planet) => {
+                planet.position = position.as_vec3();
+                planet.velocity = velocity.as_vec3();
+            }
+            CelestialBody::Moon(moon) => {
+                moon.position = position.as_vec3();
+                moon.velocity = velocity.as_vec3();
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StellarSystemType {
     Single,
     Binary,
     Ternary
 }
 
+impl StellarSystemType {
+    /// Les systèmes multiples ne sont pas képlériens : on les intègre à N corps
+    pub fn default_simulation_mode(&self) -> SimulationMode {
+        match self {
+            StellarSystemType::Single => SimulationMode::Kepler,
+            StellarSystemType::Binary | StellarSystemType::Ternary => SimulationMode::NBody(Integrator::Leapfrog),
+        }
+    }
+}
+
 pub struct StellarSystem {
     pub name: String,
+    /// Désignation de catalogue (secteur et numéro)
+    pub designation: String,
     pub system_type: StellarSystemType,
+    pub seed: Seed,
+    pub age: f64, // en milliards d'années
+    pub barycenter: glam::DVec3,
     pub bodies: Vec<CelestialBody>,
+    /// Région de stabilité des orbites planétaires
+    pub planetary_zone: PlanetaryZone,
+    /// Ceinture d'astéroïdes, toujours propagée en képlérien (corps sans masse)
+    pub asteroid_belt: Option<AsteroidBelt>,
+    simulation_mode: SimulationMode,
+    time: f64,
+    nbody: Option<NBodyState>,
+    nbody_step: f64,
+    diagnostics: Option<ConservationDiagnostics>,
 }
 
 #[derive(Debug, Clone)]
 pub struct StarPhysicalProperties {
     pub mass: f64,         // en masses solaires
+    pub initial_mass: f64, // en masses solaires, à la naissance : celle du progéniteur d'un résidu
     pub luminosity: f64,   // en luminosités solaires
     pub radius: f64,       // en rayons solaires
     pub temperature: f64,  // en Kelvin
-    pub lifetime: f64,     // en milliards d'années
+    pub lifetime: f64,     // en milliards d'années, sur la séquence principale
     pub spectral_type: String,
+    pub stage: StellarStage,
+    pub metallicity: f64,  // fraction de masse en métaux, héritée du nuage natal
+}
+
+/// Stade d'évolution d'une étoile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StellarStage {
+    MainSequence,
+    /// Géante ou supergéante rouge, après épuisement de l'hydrogène du cœur
+    RedGiant,
+    WhiteDwarf,
+    NeutronStar,
+    BlackHole,
+}
+
+impl StarPhysicalProperties {
+    /// Étoile à l'âge `age` (en milliards d'années) ; `self` est l'étoile à sa naissance
+    pub fn evolved(&self, age: f64) -> Self {
+        let mass = self.mass;
+        if age < self.lifetime {
+            return self.clone();
+        }
+        if age < self.lifetime * (1.0 + GIANT_PHASE_FRACTION) {
+            let luminosity = giant_luminosity(mass);
+            let temperature = giant_temperature(mass);
+            return Self {
+                luminosity,
+                radius: radius_from_luminosity(luminosity, temperature),
+                temperature,
+                spectral_type: spectral_class(temperature).to_string(),
+                stage: StellarStage::RedGiant,
+                ..self.clone()
+            };
+        }
+
+        let cooling_age = age - self.lifetime * (1.0 + GIANT_PHASE_FRACTION);
+        let (mass, radius, luminosity, stage, spectral_type) = if mass < NEUTRON_STAR_MIN_MASS {
+            let mass = white_dwarf_mass(mass);
+            (mass, white_dwarf_radius(mass), white_dwarf_luminosity(mass, cooling_age), StellarStage::WhiteDwarf, "D")
+        } else if mass < BLACK_HOLE_MIN_MASS {
+            let radius = neutron_star_radius();
+            let luminosity = radius * radius * (neutron_star_temperature() / SOLAR_TEMPERATURE).powi(4);
+            (NEUTRON_STAR_MASS, radius, luminosity, StellarStage::NeutronStar, "NS")
+        } else {
+            let mass = black_hole_mass(mass);
+            (mass, schwarzschild_radius(mass), 0.0, StellarStage::BlackHole, "BH")
+        };
+        Self {
+            mass,
+            luminosity,
+            radius,
+            temperature: effective_temperature(luminosity, radius),
+            initial_mass: self.initial_mass,
+            lifetime: self.lifetime,
+            spectral_type: spectral_type.to_string(),
+            stage,
+            metallicity: self.metallicity,
+        }
+    }
+
+    /// Rayon (en UA) atteint au sommet de la branche des géantes, nul sur la séquence principale.
+    /// Un résidu compact garde celui de son progéniteur : les planètes en deçà ont été englouties
+    pub fn engulfment_radius(&self) -> f64 {
+        if self.stage == StellarStage::MainSequence {
+            return 0.0;
+        }
+        let peak = radius_from_luminosity(giant_luminosity(self.initial_mass), giant_temperature(self.initial_mass));
+        self.radius.max(peak) * SOLAR_RADIUS / ASTRONOMICAL_UNIT
+    }
+
+    /// Rayon de rendu en unités de scène, compressé pour afficher géantes et résidus compacts
+    pub fn render_radius(&self) -> f32 {
+        self.radius.sqrt().max(STAR_MIN_RENDER_RADIUS) as f32
+    }
+}
+
+// Seuils de classification des planètes
+const GAS_GIANT_MIN_MASS: f64 = 10.0;     // en masses terrestres
+const GAS_GIANT_MAX_DENSITY: f64 = 0.4;   // en densités terrestres
+const LAVA_WORLD_MIN_TEMPERATURE: f64 = 700.0; // en Kelvin
+const ICE_WORLD_MAX_TEMPERATURE: f64 = 180.0;  // en Kelvin
+// Monde océan : eau abondante restée liquide ; désert : peu de volatils, ou eau évaporée
+const OCEAN_WORLD_MIN_WATER: f64 = 0.85;
+const OCEAN_WORLD_MAX_TEMPERATURE: f64 = 330.0; // en Kelvin
+const DESERT_WORLD_MAX_WATER: f64 = 0.1;
+const DESERT_WORLD_MIN_TEMPERATURE: f64 = 400.0; // en Kelvin
+// Probabilité qu'une planète soit une géante autour d'une étoile de métallicité solaire, et plafond
+const GIANT_PLANET_SOLAR_PROBABILITY: f64 = 0.2;
+const GIANT_PLANET_MAX_PROBABILITY: f64 = 0.9;
+
+/// Classe de planète, qui détermine la génération de sa géométrie
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanetClass {
+    Terrestrial = 0,
+    GasGiant = 1,
+    Lava = 2,
+    Ice = 3,
+    Ocean = 4,
+    Desert = 5,
+}
+
+impl PlanetClass {
+    /// Classe déduite de la masse (en masses terrestres), du rayon (en rayons terrestres),
+    /// de la température d'équilibre (en Kelvin) et de la part d'eau et de volatils (0 à 1)
+    pub fn classify(mass: f64, radius: f64, equilibrium_temperature: f64, water_fraction: f64) -> Self {
+        let density = mass / radius.powi(3);
+        if mass >= GAS_GIANT_MIN_MASS || density < GAS_GIANT_MAX_DENSITY {
+            PlanetClass::GasGiant
+        } else if equilibrium_temperature >= LAVA_WORLD_MIN_TEMPERATURE {
+            PlanetClass::Lava
+        } else if equilibrium_temperature <= ICE_WORLD_MAX_TEMPERATURE {
+            PlanetClass::Ice
+        } else if water_fraction <= DESERT_WORLD_MAX_WATER || equilibrium_temperature >= DESERT_WORLD_MIN_TEMPERATURE {
+            PlanetClass::Desert
+        } else if water_fraction >= OCEAN_WORLD_MIN_WATER && equilibrium_temperature <= OCEAN_WORLD_MAX_TEMPERATURE {
+            PlanetClass::Ocean
+        } else {
+            PlanetClass::Terrestrial
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => PlanetClass::GasGiant,
+            2 => PlanetClass::Lava,
+            3 => PlanetClass::Ice,
+            4 => PlanetClass::Ocean,
+            5 => PlanetClass::Desert,
+            _ => PlanetClass::Terrestrial,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct PlanetPhysicalProperties {
     pub mass: f64,   // en masses terrestres
     pub radius: f64, // en rayons terrestres
-    // Vous pouvez ajouter d'autres propriétés (densité, type, etc.)
+    pub albedo: f64, // albédo de Bond
+    pub water_fraction: f64, // part de la surface couverte d'eau si elle reste liquide (0 à 1)
+    pub terrain_seed: u64,
+    // Grandeurs dérivées des étoiles du système
+    pub insolation: f64,              // en flux terrestres, moyenné sur l'orbite
+    pub equilibrium_temperature: f64, // en Kelvin
+    pub habitability: Habitability,
+    pub class: PlanetClass,
 }
 
 #[derive(Debug, Clone)]
@@ -143,35 +431,35 @@ pub struct StarComposition {
     pub metals: f32,
 }
 
-/// Calcule le rayon d'une étoile de la séquence principale en fonction de la masse (en Yt) et de la métallicité (composition.metals)
-pub fn star_radius_from_mass_composition(mass: f64, composition: &StarComposition) -> f64 {
-    // Conversion Yt -> masses solaires
-    let mass_solar = mass / 2000.0;
-    // log::info!("mass={}", mass / 2000.0);
-    // Influence de la métallicité : plus de métaux = rayon légèrement plus petit
-    // (effet empirique, typiquement -5% à -10% pour Z=0.03 vs Z=0.001)
-    let metallicity_factor = 1.0 - 0.5 * composition.metals as f64; // 0.5 = force de l'effet
-    let base_radius = mass_solar.powf(0.8); // relation séquence principale
-    base_radius * metallicity_factor
+impl StarComposition {
+    /// Composition de la photosphère solaire (Asplund et al. 2009)
+    pub fn solar() -> Self {
+        StarComposition {
+            hydrogen: 0.7381,
+            helium: 0.2485,
+            metals: SOLAR_METALLICITY as f32,
+        }
+    }
 }
 
-pub fn surface_temperature_from_mass_radius_composition(mass_yt: f64, radius_solar: f64, metallicity: f64) -> f64 {
-    let mass_solar = mass_yt / 2000.0;
-    let base_temp = 5778.0 * (mass_solar / radius_solar.powi(2)).powf(0.25);
-    let metallicity_factor = 1.0 - 0.1 * (metallicity / 0.0134 - 1.0); // Z_sun = 0.0134
-    base_temp * metallicity_factor
+/// Rayon (en rayons solaires) d'une étoile de la séquence principale de masse `mass` (en masses solaires) :
+/// plus de métaux donne un rayon légèrement plus petit
+pub fn star_radius_from_mass_composition(mass: f64, composition: &StarComposition) -> f64 {
+    let metallicity_factor = 1.0 - 0.5 * (composition.metals as f64 - SOLAR_METALLICITY);
+    mass_radius(mass) * metallicity_factor
 }
 
+/// Luminosité (en luminosités solaires) d'un corps noir de rayon en rayons solaires et de température en Kelvin
 pub fn compute_luminosity(radius: f64, temperature: f64) -> f64
 {
-    4.0 * PI * radius.powf(2.0) * STEFAN_BOLTZMANN * temperature.powf(4.0)
+    let radius = radius * SOLAR_RADIUS;
+    4.0 * PI * radius.powf(2.0) * STEFAN_BOLTZMANN * temperature.powf(4.0) / SOLAR_LUMINOSITY
 }
 
-
 /// Génère une composition stellaire en fonction de plusieurs facteurs physiques et galactiques.
 /// Les poids sont ajustables pour chaque facteur.
 pub fn generate_star_composition(
-    mass: f64,
+    mass: f64,                // en masses solaires
     age: f64,                 // en milliards d'années (0 = très jeune, 13.7 = très vieux)
     supernovae_proximity: f64,// 0.0 (loin) à 1.0 (très proche)
     galactic_history: f64,    // 0.0 (peu de générations) à 1.0 (beaucoup)
@@ -195,7 +483,7 @@ pub fn generate_star_composition(
     metals += w_supernovae * supernovae_proximity * 0.02;
     metals += w_history * galactic_history * 0.02;
     metals += w_region * galactic_region * 0.02;
-    metals += w_mass * (mass / 200000.0).min(1.0) * 0.01; // étoiles massives légèrement plus riches
+    metals += w_mass * (mass / MAX_STAR_MASS).min(1.0) * 0.01; // étoiles massives légèrement plus riches
     metals = metals.clamp(0.0001, 0.04); // bornes réalistes
 
     // Hélium augmente légèrement avec la métallicité
@@ -210,37 +498,53 @@ pub fn generate_star_composition(
     }
 }
 
-pub fn generate_star(rng: &mut RNG) -> StarPhysicalProperties {
-
-    // Masse entre 160 et 200 000 Yt
-    let mass: Yt = rng.f64(160.0,200000.0);
-    let composition = generate_star_composition(mass, 6.2, 0.0, 0.0, 0.0);
-    let radius = star_radius_from_mass_composition(mass, &composition);
-    let temperature = surface_temperature_from_mass_radius_composition(mass, radius, 1.0 - 0.5 * composition.metals as f64);
-    let luminosity = compute_luminosity(radius, temperature);
-    let lifetime = 10.0 * (mass / luminosity); // en milliards d'années
-    let spectral_type = match temperature as u32 {
-        t if t >= 30000 => "O",
-        t if t >= 10000 => "B",
-        t if t >= 7500  => "A",
-        t if t >= 6000  => "F",
-        t if t >= 5200  => "G",
-        t if t >= 3700  => "K",
-        _              => "M",
-    }.to_string();
+/// Étoile de la séquence principale de masse `mass` (en masses solaires)
+pub fn main_sequence_star(mass: f64, composition: &StarComposition) -> StarPhysicalProperties {
+    let luminosity = mass_luminosity(mass);
+    let radius = star_radius_from_mass_composition(mass, composition);
+    let temperature = effective_temperature(luminosity, radius);
     StarPhysicalProperties {
         mass,
+        initial_mass: mass,
         luminosity,
         radius,
         temperature,
-        lifetime,
-        spectral_type,
+        lifetime: main_sequence_lifetime(mass, luminosity),
+        spectral_type: spectral_class(temperature).to_string(),
+        stage: StellarStage::MainSequence,
+        metallicity: composition.metals as f64,
     }
 }
 
-pub fn generate_planet(rng: &mut RNG) -> PlanetPhysicalProperties {
-    // Masse entre 0.1 et 3000 masses terrestres (de Mercure à Jupiter)
-    let mass = rng.f64(0.1, 3000.0);
+/// Étoile dont la masse suit la fonction de masse initiale de Kroupa, née il y a `age` milliards
+/// d'années dans l'environnement galactique `context`
+pub fn generate_star(rng: &mut RNG, age: f64, context: &GalacticContext) -> StarPhysicalProperties {
+    let mass = sample_kroupa_mass(rng.gen_norm(), rng.gen_norm());
+    let composition = generate_star_composition(
+        mass,
+        age,
+        context.supernovae_proximity,
+        context.galactic_history,
+        context.galactic_region,
+    );
+    main_sequence_star(mass, &composition)
+}
+
+/// Probabilité qu'une planète soit une géante autour d'une étoile de métallicité `metallicity`
+/// (fraction de masse en métaux) : P ∝ 10^(2 [Fe/H]) (Fischer & Valenti 2005)
+pub fn giant_planet_probability(metallicity: f64) -> f64 {
+    let iron_abundance = (metallicity / SOLAR_METALLICITY).log10();
+    (GIANT_PLANET_SOLAR_PROBABILITY * 10f64.powf(2.0 * iron_abundance)).min(GIANT_PLANET_MAX_PROBABILITY)
+}
+
+/// Planète d'une étoile de métallicité `metallicity` (fraction de masse en métaux)
+pub fn generate_planet(rng: &mut RNG, metallicity: f64) -> PlanetPhysicalProperties {
+    // Géantes entre 10 et 3000 masses terrestres (jusqu'à ~10 Jupiter), sinon de Mercure aux super-Terres
+    let mass = if rng.gen_norm() < giant_planet_probability(metallicity) {
+        rng.f64(GAS_GIANT_MIN_MASS, 3000.0)
+    } else {
+        rng.f64(0.1, GAS_GIANT_MIN_MASS)
+    };
     // Rayon selon la masse (approximation simplifiée)
     // Pour les planètes telluriques (M < 10) : R ~ M^0.3
     // Pour les géantes gazeuses (M >= 10) : R ~ M^0.5 (saturé vers 11 R_terre)
@@ -249,81 +553,321 @@ pub fn generate_planet(rng: &mut RNG) -> PlanetPhysicalProperties {
     } else {
         (mass.powf(0.5)).min(11.0)
     };
+    // Albédo de Bond, de Mercure (~0.07) à Vénus (~0.75)
+    let albedo = rng.f64(0.05, 0.75);
+    let water_fraction = rng.gen_norm();
     PlanetPhysicalProperties {
         mass,
-        radius: 1.0,
+        radius,
+        albedo,
+        water_fraction,
+        terrain_seed: 0, // dérivée de la graine du corps par le système
+        insolation: 0.0,
+        equilibrium_temperature: 0.0,
+        habitability: Habitability::TooCold,
+        class: PlanetClass::classify(mass, radius, 0.0, water_fraction),
+    }
+}
+
+/// Tire l'orbite de la planète suivante, au-delà de `previous_semi_major_axis` (0 pour la première,
+/// placée entre `min_semi_major_axis` et son double, sans dépasser `max_semi_major_axis`)
+pub fn generate_planet_orbit(rng: &mut RNG, previous_semi_major_axis: f64, min_semi_major_axis: f64, max_semi_major_axis: f64) -> OrbitalElements {
+    // Espacement géométrique des orbites (type loi de Titius-Bode)
+    let semi_major_axis = if previous_semi_major_axis <= 0.0 {
+        rng.f64(min_semi_major_axis, (2.0 * min_semi_major_axis).min(max_semi_major_axis))
+    } else {
+        previous_semi_major_axis * rng.f64(1.3, 1.7)
+    };
+    // Favorise les orbites quasi circulaires
+    let eccentricity = PLANET_MAX_ECCENTRICITY * rng.gen_norm().powi(2);
+    let inclination = rng.f64(0.0, PLANET_MAX_INCLINATION);
+    let longitude_ascending_node = rng.f64(0.0, 2.0 * PI);
+    let argument_periapsis = rng.f64(0.0, 2.0 * PI);
+    let mean_anomaly_epoch = rng.f64(0.0, 2.0 * PI);
+    OrbitalElements {
+        semi_major_axis,
+        eccentricity,
+        inclination,
+        longitude_ascending_node,
+        argument_periapsis,
+        mean_anomaly_epoch,
     }
 }
 
 impl StellarSystem {
+    /// Système situé à `pos` (en parsecs, repère galactocentrique) dans l'univers par défaut
     pub fn new(pos: Vec3) -> StellarSystem {
-        let mut rng = RNG::new(pos);
-
+        Self::from_seed(Seed::universe(DEFAULT_UNIVERSE_SEED).system_at(pos), sector_of(pos), system_index_at(pos), &GalacticContext::at(pos))
+    }
 
+    /// Système de graine `seed`, de rang `index` dans le secteur `sector`, formé dans l'environnement `context`
+    pub fn from_seed(seed: Seed, sector: IVec3, index: u64, context: &GalacticContext) -> StellarSystem {
+        let mut rng = RNG::from_seed(seed.value());
+        // Générateur propre au nom : nommer un système ne modifie pas son contenu
+        let name = generate_name(&mut RNG::from_seed(seed.derive("name", 0).value()));
 
-        // let num_stars   : u32 = Self::get_number_star(&mut rng, 0.5, 3);
-        let num_stars   : u32 = 2;
-        let num_planets : u32 = Self::get_number_star(&mut rng, 0.4, 8);
-        let num_bodies  : u32 = num_stars + num_planets;
+        let (num_planets, age, stars) = Self::generate_stars(&mut rng, context);
+        let num_stars = stars.len();
         let mut bodies  : Vec<CelestialBody> = Vec::new();
 
-        if num_bodies == 1
-        {
+        // Configuration hiérarchique stable : binaires serrées, troisième étoile lointaine
+        let masses: Vec<f64> = stars.iter().map(|star| star.mass).collect();
+        let hierarchy = StellarHierarchy::generate(&mut rng, &masses);
+        let multiple = num_stars > 1;
+        for (i, (physical_props, orbit)) in stars.into_iter().zip(hierarchy.star_orbits).enumerate() {
             let star = Star {
-                name: format!("Star"),
-                physical_props: generate_star(&mut rng),
+                name: if multiple { format!("{} {}", name, star_letter(i)) } else { name.clone() },
+                physical_props,
+                orbit,
                 position: glam::DVec3::ZERO,
                 velocity: glam::DVec3::ZERO,
             };
             bodies.push(CelestialBody::Star(star));
         }
-        else {
-            for i in 0..num_stars {
-                let position = glam::DVec3::new(
-                    rng.f64(-5.0, 5.0),
-                    rng.f64(-5.0, 5.0),
-                    0.0);
-                let mass: f64 = rng.f64(0.0, 1000.0);
-                let radius: f64 = 1.0;
-
-                let temperature = 6700;
-                let spectral_type = match temperature as u32 {
-                    t if t >= 30000 => "O",
-                    t if t >= 10000 => "B",
-                    t if t >= 7500  => "A",
-                    t if t >= 6000  => "F",
-                    t if t >= 5200  => "G",
-                    t if t >= 3700  => "K",
-                    _              => "M",
-                }.to_string();
-                let star = Star {
-                    name: format!("Star"),
-                    physical_props: StarPhysicalProperties {
-                        mass,
-                        luminosity: 1.0,
-                        radius,
-                        temperature: 1.0,
-                        lifetime: 1.0,
-                        spectral_type
-                    },
-                    position,
-                    velocity: glam::DVec3::ZERO,
-                };
-                bodies.push(CelestialBody::Star(star));
+
+        // Planètes circumstellaires (S-type) ou circumbinaires (P-type) selon la configuration
+        let zone = hierarchy.planetary_zone;
+        let planet_axes = Self::generate_planets(&mut rng, &mut bodies, &zone, &name, num_planets);
+
+        let asteroid_belt = AsteroidBelt::generate(&mut rng, &zone, &planet_axes, Self::zone_luminosity(&bodies, &zone));
+
+        Self::compute_habitability(&mut bodies);
+
+        // Reliefs dérivés de la graine de chaque corps
+        for (i, body) in bodies.iter_mut().enumerate() {
+            if let CelestialBody::Planet(Planet { physical_props, .. }) | CelestialBody::Moon(Moon { physical_props, .. }) = body {
+                physical_props.terrain_seed = seed.body(i as u64).terrain().value();
             }
         }
 
-        StellarSystem {
-            name: String::from("dzdzd"),
-            system_type: StellarSystemType::Binary,
+        let system_type = hierarchy.system_type;
+        let mut system = StellarSystem {
+            name,
+            designation: catalog_designation(sector, index),
+            system_type,
+            seed,
+            age,
+            barycenter: glam::DVec3::ZERO,
             bodies,
+            planetary_zone: zone,
+            asteroid_belt,
+            simulation_mode: SimulationMode::Kepler,
+            time: 0.0,
+            nbody: None,
+            nbody_step: NBODY_DEFAULT_STEP,
+            diagnostics: None,
+        };
+        system.update(0.0);
+        system.set_simulation_mode(system_type.default_simulation_mode());
+        system
+    }
+
+    /// Âge (en milliards d'années) et étoiles du système de graine `seed`, sans générer orbites ni planètes
+    pub fn preview_stars(seed: Seed, context: &GalacticContext) -> (f64, Vec<StarPhysicalProperties>) {
+        let (_, age, stars) = Self::generate_stars(&mut RNG::from_seed(seed.value()), context);
+        (age, stars)
+    }
+
+    /// Planètes de la zone `zone`, ajoutées à `bodies` avec leurs lunes ; renvoie leurs demi-grands axes.
+    /// Les planètes englouties par une géante, ou par le progéniteur d'un résidu, sont écartées avant d'être nommées : les lettres
+    /// restent contiguës, et un système peut ne garder aucune planète
+    fn generate_planets(rng: &mut RNG, bodies: &mut Vec<CelestialBody>, zone: &PlanetaryZone, name: &str, num_planets: u32) -> Vec<f64> {
+        let mut semi_major_axis = 0.0;
+        let mut planet_axes = Vec::new();
+        // Les planètes dont le périastre passe sous la surface d'une géante ont été englouties
+        let engulfed_radius = Self::zone_stars(bodies, zone)
+            .map(|star| star.physical_props.engulfment_radius())
+            .fold(0.0, f64::max);
+        // Disque protoplanétaire de même composition que les étoiles du foyer
+        let metallicity = Self::zone_stars(bodies, zone)
+            .map(|star| star.physical_props.metallicity)
+            .fold(0.0, f64::max);
+        for _ in 0..num_planets {
+            let elements = generate_planet_orbit(rng, semi_major_axis, zone.min_semi_major_axis, zone.max_semi_major_axis);
+            if elements.semi_major_axis > zone.max_semi_major_axis {
+                break;
+            }
+            semi_major_axis = elements.semi_major_axis;
+            if elements.periapsis() < engulfed_radius {
+                continue;
+            }
+            planet_axes.push(semi_major_axis);
+
+            let physical_props = generate_planet(rng, metallicity);
+            let orbit = zone.orbit(elements);
+            let moons = generate_moons(rng, &physical_props, &orbit);
+            // Anneaux pour une géante sur deux
+            let rings = if physical_props.class == PlanetClass::GasGiant && rng.gen_norm() < 0.5 {
+                RingSystem::generate(rng, &physical_props)
+            } else {
+                None
+            };
+
+            // Désignation par ordre orbital parmi les planètes restantes : b, c, d...
+            let planet_name = format!("{} {}", name, planet_letter(planet_axes.len() - 1));
+            let host = bodies.len();
+            let planet = Planet {
+                name: planet_name.clone(),
+                physical_props,
+                orbit,
+                rings,
+                position: glam::Vec3::ZERO,
+                velocity: glam::Vec3::ZERO,
+            };
+            bodies.push(CelestialBody::Planet(planet));
+
+            for (j, (physical_props, orbit)) in moons.into_iter().enumerate() {
+                let moon = Moon {
+                    name: format!("{} {}", planet_name, roman_numeral(j + 1)),
+                    physical_props,
+                    orbit,
+                    host,
+                    position: glam::Vec3::ZERO,
+                    velocity: glam::Vec3::ZERO,
+                };
+                bodies.push(CelestialBody::Moon(moon));
+            }
+        }
+        planet_axes
+    }
+
+    /// Premiers tirages d'un système : nombre de planètes, âge (en milliards d'années) et étoiles
+    fn generate_stars(rng: &mut RNG, context: &GalacticContext) -> (u32, f64, Vec<StarPhysicalProperties>) {
+        let num_stars   : u32 = Self::get_number_star(rng, 0.5, 3);
+        let num_planets : u32 = Self::get_number_star(rng, 0.4, 8);
+        // Les étoiles naissent ensemble ; les plus massives ont déjà quitté la séquence principale
+        let age = rng.f64(SYSTEM_MIN_AGE, SYSTEM_MAX_AGE);
+        let stars = (0..num_stars).map(|_| generate_star(rng, age, context).evolved(age)).collect();
+        (num_planets, age, stars)
+    }
+
+    pub fn simulation_mode(&self) -> SimulationMode {
+        self.simulation_mode
+    }
+
+    /// Change de mode de simulation ; l'état N corps est initialisé depuis les orbites à l'instant courant
+    pub fn set_simulation_mode(&mut self, mode: SimulationMode) {
+        self.simulation_mode = mode;
+        match mode {
+            SimulationMode::Kepler => {
+                self.nbody = None;
+                self.diagnostics = None;
+                self.update(self.time);
+            }
+            SimulationMode::NBody(_) => {
+                let mut positions = Vec::with_capacity(self.bodies.len());
+                let mut velocities = Vec::with_capacity(self.bodies.len());
+                let mut masses = Vec::with_capacity(self.bodies.len());
+                let mut shortest_period = f64::MAX;
+                for body in &self.bodies {
+                    let orbit = body.orbit();
+                    let (position, velocity) = orbit.state_at(self.time);
+                    positions.push(position);
+                    velocities.push(velocity);
+                    masses.push(body.mass_kg());
+                    let period = orbit.period();
+                    if period.is_finite() && period > 0.0 {
+                        shortest_period = shortest_period.min(period);
+                    }
+                }
+                self.nbody_step = if shortest_period < f64::MAX {
+                    shortest_period / NBODY_STEPS_PER_ORBIT
+                } else {
+                    NBODY_DEFAULT_STEP
+                };
+                // Les orbites képlériennes ignorent la réaction des étoiles aux planètes : la
+                // quantité de mouvement totale n'est pas nulle et le barycentre dériverait
+                let mut state = NBodyState::new(positions, velocities, masses);
+                state.to_center_of_mass_frame();
+                self.diagnostics = Some(ConservationDiagnostics::new(&state));
+                self.nbody = Some(state);
+            }
+        }
+    }
+
+    /// Avance la simulation jusqu'à l'instant `time` (s)
+    pub fn update(&mut self, time: f64) {
+        let states: Vec<(glam::DVec3, glam::DVec3)> = match self.simulation_mode {
+            SimulationMode::Kepler => self.bodies.iter().map(|body| body.orbit().state_at(time)).collect(),
+            SimulationMode::NBody(integrator) => {
+                let Some(state) = self.nbody.as_mut() else {
+                    self.time = time;
+                    return;
+                };
+                let elapsed = time - self.time;
+                let steps = ((elapsed.abs() / self.nbody_step).ceil() as u32).min(NBODY_MAX_STEPS_PER_UPDATE);
+                if steps > 0 {
+                    let dt = elapsed / steps as f64;
+                    for _ in 0..steps {
+                        state.step(dt, integrator);
+                    }
+                }
+                state.positions.iter().copied().zip(state.velocities.iter().copied()).collect()
+            }
+        };
+        for (i, body) in self.bodies.iter_mut().enumerate() {
+            let (position, velocity) = match body {
+                CelestialBody::Moon(moon) => moon_display_state(states[moon.host], states[i]),
+                _ => states[i],
+            };
+            body.set_state(position, velocity, self.barycenter);
         }
+        self.time = time;
     }
 
-    // fn compute_barycenter()
-    // {
+    /// Dérives relatives (énergie, moment cinétique) depuis le passage en mode N corps
+    pub fn conservation_drift(&self) -> Option<(f64, f64)> {
+        match (&self.nbody, &self.diagnostics) {
+            (Some(state), Some(diagnostics)) => Some((
+                diagnostics.energy_drift(state),
+                diagnostics.angular_momentum_drift(state),
+            )),
+            _ => None,
+        }
+    }
 
-    // }
+    /// Insolation, température d'équilibre, zone habitable et classe de chaque planète et satellite,
+    /// à partir de toutes les étoiles
+    fn compute_habitability(bodies: &mut [CelestialBody]) {
+        let stars: Vec<(StarPhysicalProperties, Orbit)> = bodies.iter().filter_map(|body| match body {
+            CelestialBody::Star(star) => Some((star.physical_props.clone(), star.orbit.clone())),
+            _ => None,
+        }).collect();
+        let stars: Vec<(&StarPhysicalProperties, &Orbit)> = stars.iter().map(|(props, orbit)| (props, orbit)).collect();
+
+        for body in bodies.iter_mut() {
+            // Un satellite reçoit le flux moyen de sa planète hôte
+            let (props, orbit) = match body {
+                CelestialBody::Planet(planet) => (&mut planet.physical_props, &planet.orbit),
+                CelestialBody::Moon(moon) => (&mut moon.physical_props, moon.orbit.parent.as_deref().unwrap_or(&moon.orbit)),
+                CelestialBody::Star(_) => continue,
+            };
+            let fluxes: Vec<(&StarPhysicalProperties, f64)> = stars.iter()
+                .map(|star| (star.0, habitability::mean_insolation(orbit, std::slice::from_ref(star))))
+                .collect();
+            props.insolation = fluxes.iter().map(|(_, flux)| flux).sum();
+            props.equilibrium_temperature = habitability::equilibrium_temperature(props.insolation, props.albedo);
+            props.habitability = habitability::classify(&fluxes);
+            props.class = PlanetClass::classify(props.mass, props.radius, props.equilibrium_temperature, props.water_fraction);
+        }
+    }
+
+    /// Luminosité totale (en luminosités solaires) des étoiles au foyer de la zone planétaire
+    fn zone_luminosity(bodies: &[CelestialBody], zone: &PlanetaryZone) -> f64 {
+        Self::zone_stars(bodies, zone).map(|star| star.physical_props.luminosity).sum()
+    }
+
+    /// Étoiles au foyer de la zone planétaire
+    fn zone_stars<'a>(bodies: &'a [CelestialBody], zone: &'a PlanetaryZone) -> impl Iterator<Item = &'a Star> {
+        bodies.iter().filter_map(|body| match body {
+            CelestialBody::Star(star) => Some(star),
+            _ => None,
+        }).filter(|star| match &zone.host {
+            // Étoile hôte (S-type) ou membre de la paire centrale (P-type d'un système triple)
+            Some(host) => star.orbit == *host || star.orbit.parent.as_deref() == Some(host),
+            None => true,
+        })
+    }
 
     fn get_number_star(rng: &mut RNG, p: f64, max_stars: u32) -> u32 {
         let mut n: u32 = 1;
@@ -335,84 +879,604 @@ impl StellarSystem {
 }
 
 
-// impl StellarSystem {
-//     pub fn new(pos: Vec3) -> StellarSystem {
-//         let mut rng = RNG::new(pos);
+// Exemple d'utilisation :
+// let radius = star_radius_from_mass_composition(mass, &composition);
 
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::stellar_model::MIN_STAR_MASS;
+    use super::super::naming::sector_code;
+    use super::super::multiplicity::PlanetOrbitType;
 
-//         let num_stars: u32 = Self::get_number_star(&mut rng, 0.5, 3);
-//         let mut bodies = Vec::new();
-//         let mut barycenter = Vec3::ZERO;
-//         let mut total_mass = 0.0;
+    fn planets(system: &StellarSystem) -> Vec<&Planet> {
+        system.bodies.iter().filter_map(|body| match body {
+            CelestialBody::Planet(planet) => Some(planet),
+            _ => None,
+        }).collect()
+    }
+
+    /// Barycentre des étoiles (en unités de scène) et masse stellaire totale (en masses solaires)
+    fn compute_barycenter(bodies: &[CelestialBody]) -> (glam::DVec3, f64) {
+        let mut weighted = glam::DVec3::ZERO;
+        let mut total_mass = 0.0;
+        for body in bodies {
+            if let CelestialBody::Star(star) = body {
+                weighted += star.position * star.physical_props.mass;
+                total_mass += star.physical_props.mass;
+            }
+        }
+        if total_mass > 0.0 {
+            (weighted / total_mass, total_mass)
+        } else {
+            (glam::DVec3::ZERO, 0.0)
+        }
+    }
+
+    fn sample_positions() -> Vec<Vec3> {
+        (0..32).map(|i| Vec3::new(i as f32 * 17.0, -(i as f32) * 3.5, i as f32 * 0.25)).collect()
+    }
 
-//         for i in 0..num_stars {
-//             // let mass = rng.gen_float(1.0e30, 2.0e30);
-//             let star_props = generate_star(&mut rng);
-//             let distance = if i == 0 { 0.0 } else { rng.f64(1.0e10, 1.5e11) as f32 };
-//             let position = Vec3::new(distance / 1.0e10, 0.0, 0.0);
+    #[test]
+    fn test_rng_golden_values() {
+        let mut rng = RNG::from_seed(42);
+        assert_eq!(rng.next_u64(), 0x08328d7f03bcec1a);
+        assert_eq!(rng.next_u64(), 0x077e7279e17ab6cd);
+        assert_eq!(rng.next_u64(), 0x0c4e098f541bb09e);
+    }
 
+    #[test]
+    fn test_rng_distributions() {
+        let mut rng = RNG::from_seed(7);
+        let n = 20_000;
+        let normal: Vec<f64> = (0..n).map(|_| rng.normal(3.0, 2.0)).collect();
+        let mean = normal.iter().sum::<f64>() / n as f64;
+        let variance = normal.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n as f64;
+        assert!((mean - 3.0).abs() < 0.05 && (variance.sqrt() - 2.0).abs() < 0.05);
+        let exponential = (0..n).map(|_| rng.exponential(4.0)).sum::<f64>() / n as f64;
+        assert!((exponential - 0.25).abs() < 0.01);
+
+        let mut counts = [0; 3];
+        for _ in 0..n {
+            counts[rng.weighted_choice(&[1.0, 0.0, 3.0]).unwrap()] += 1;
+        }
+        assert_eq!(counts[1], 0);
+        assert!((counts[2] as f64 / n as f64 - 0.75).abs() < 0.02);
+        assert_eq!(rng.weighted_choice(&[0.0, -1.0]), None);
+    }
 
-//             barycenter = (barycenter * total_mass as f32 + position * star_props.mass as f32) / (total_mass + star_props.mass) as f32;
-//             total_mass += star_props.mass;
+    #[test]
+    fn test_body_names() {
+        for pos in sample_positions() {
+            let system = StellarSystem::new(pos);
+            assert_eq!(system.name, StellarSystem::new(pos).name);
+            assert!(system.designation.starts_with(&sector_code(sector_of(pos))));
+            let planets = planets(&system);
+            for (i, planet) in planets.iter().enumerate() {
+                assert_eq!(planet.name, format!("{} {}", system.name, (b'b' + i as u8) as char));
+                if i > 0 {
+                    assert!(planet.orbit.elements.semi_major_axis > planets[i - 1].orbit.elements.semi_major_axis);
+                }
+            }
+            let mut moons_per_host = std::collections::HashMap::new();
+            for body in &system.bodies {
+                if let CelestialBody::Moon(moon) = body {
+                    let CelestialBody::Planet(host) = &system.bodies[moon.host] else { panic!("hôte invalide") };
+                    let count = moons_per_host.entry(moon.host).or_insert(0);
+                    *count += 1;
+                    assert_eq!(moon.name, format!("{} {}", host.name, roman_numeral(*count)));
+                }
+                if let CelestialBody::Star(star) = body {
+                    assert!(star.name.starts_with(&system.name));
+                }
+            }
+        }
+    }
 
-//             let mut star = Star {
-//                 name: format!("Star {}", i + 1),
-//                 physical_props: star_props,
-//                 position,
-//                 velocity: Vec3::ZERO,
-//             };
+    #[test]
+    fn test_system_golden_values() {
+        // Système de référence : un changement de la génération procédurale fait échouer ce test
+        let system = StellarSystem::new(Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(system.seed.value(), 0x0f0192cf74d69b24);
+        assert_eq!(system.system_type, StellarSystemType::Single);
+        assert_eq!(system.bodies.len(), 4);
+        assert!((system.age - 12.08572653856928).abs() < 1.0e-12);
+        let CelestialBody::Star(star) = &system.bodies[0] else { panic!("le premier corps doit être l'étoile") };
+        assert!((star.physical_props.mass - 0.08268616406269244).abs() < 1.0e-12);
+        assert!((star.physical_props.metallicity - 0.013970842584967613).abs() < 1.0e-9);
+        let CelestialBody::Planet(planet) = &system.bodies[1] else { panic!("le deuxième corps doit être une planète") };
+        assert!((planet.physical_props.mass - 5.1572492446310125).abs() < 1.0e-9);
+        assert!((planet.orbit.elements.semi_major_axis - 0.4425740697284348).abs() < 1.0e-12);
+        assert_eq!(planet.physical_props.terrain_seed, system.seed.body(1).terrain().value());
+    }
 
-//             let r_vec = star.position - barycenter;
-//             let r = r_vec.length() as f64;
-//             if r > 0.0 {
-//                 let v_mag = (G * total_mass / r).sqrt() as f32;
-//                 let dir = Vec3::new(-r_vec.y, r_vec.x, 0.0).normalize();
-//                 star.velocity = dir * v_mag;
-//             }
+    #[test]
+    fn test_solar_mass_star() {
+        let sun = main_sequence_star(1.0, &StarComposition::solar());
+        assert!((sun.luminosity - 1.0).abs() < 0.01, "luminosité {}", sun.luminosity);
+        assert!((sun.radius - 1.0).abs() < 0.01, "rayon {}", sun.radius);
+        assert!((sun.temperature - 5778.0).abs() < 15.0, "température {}", sun.temperature);
+        assert!((sun.lifetime - 10.0).abs() < 0.1);
+        assert_eq!(sun.spectral_type, "G");
+        // Cohérence avec la loi de Stefan-Boltzmann en unités SI
+        assert!((compute_luminosity(sun.radius, sun.temperature) - sun.luminosity).abs() < 0.01);
+    }
 
-//             bodies.push(CelestialBody::Star(star));
-//         }
+    #[test]
+    fn test_stellar_evolution() {
+        let composition = StarComposition::solar();
+        let sun = main_sequence_star(1.0, &composition);
+        assert_eq!(sun.evolved(4.6).stage, StellarStage::MainSequence);
+        // Géante : bien plus grande et plus lumineuse, mais plus froide
+        let giant = sun.evolved(10.5);
+        assert_eq!(giant.stage, StellarStage::RedGiant);
+        assert!(giant.radius > 20.0 && giant.luminosity > 100.0 && giant.temperature < sun.temperature);
+        assert_eq!(giant.spectral_type, "K");
+        // Naine blanche : minuscule, chaude puis refroidie
+        let white_dwarf = sun.evolved(12.0);
+        assert_eq!(white_dwarf.stage, StellarStage::WhiteDwarf);
+        assert!(white_dwarf.radius < 0.02 && white_dwarf.mass < 1.0);
+        assert!(white_dwarf.temperature > sun.evolved(13.0).temperature);
+        // Résidus compacts des étoiles massives
+        let massive = main_sequence_star(12.0, &composition).evolved(13.0);
+        assert_eq!(massive.stage, StellarStage::NeutronStar);
+        assert_eq!(massive.mass, NEUTRON_STAR_MASS);
+        let black_hole = main_sequence_star(40.0, &composition).evolved(13.0);
+        assert_eq!(black_hole.stage, StellarStage::BlackHole);
+        assert_eq!(black_hole.luminosity, 0.0);
+        // Rayons affichés : géante énorme, résidus visibles
+        assert!(giant.render_radius() > 5.0 * sun.render_radius());
+        assert!(black_hole.render_radius() >= STAR_MIN_RENDER_RADIUS as f32);
+    }
 
-//         let num_planets: u32 = Self::get_number_star(&mut rng, 0.4, 8);
+    #[test]
+    fn test_planets_outside_giants() {
+        for pos in sample_positions() {
+            let system = StellarSystem::new(pos);
+            let giants = StellarSystem::zone_stars(&system.bodies, &system.planetary_zone)
+                .filter(|star| star.physical_props.stage == StellarStage::RedGiant);
+            for giant in giants {
+                let radius = giant.physical_props.radius * SOLAR_RADIUS / ASTRONOMICAL_UNIT;
+                for planet in planets(&system) {
+                    assert!(planet.orbit.elements.periapsis() >= radius);
+                }
+            }
+        }
+    }
 
-//         for i in 0..num_planets {
+    #[test]
+    fn test_giant_engulfs_inner_planets() {
+        let composition = StarComposition::solar();
+        let zone = PlanetaryZone {
+            orbit_type: PlanetOrbitType::SType,
+            host: None,
+            central_mass: 1.0,
+            min_semi_major_axis: PLANET_MIN_SEMI_MAJOR_AXIS,
+            max_semi_major_axis: PLANET_MAX_SEMI_MAJOR_AXIS,
+        };
+        let star = |physical_props| CelestialBody::Star(Star {
+            name: "Test".to_string(),
+            physical_props,
+            orbit: Orbit::stationary(),
+            position: glam::DVec3::ZERO,
+            velocity: glam::DVec3::ZERO,
+        });
+        let sun = main_sequence_star(1.0, &composition);
+        // Géante dont la surface atteint 2 UA
+        let giant = StarPhysicalProperties { radius: 2.0 * ASTRONOMICAL_UNIT / SOLAR_RADIUS, ..sun.evolved(10.5) };
+        let mut engulfed = 0;
+        for seed in 0..16 {
+            let mut young = vec![star(sun.clone())];
+            let young_axes = StellarSystem::generate_planets(&mut RNG::from_seed(seed), &mut young, &zone, "Test", 8);
+            let mut evolved = vec![star(giant.clone())];
+            let axes = StellarSystem::generate_planets(&mut RNG::from_seed(seed), &mut evolved, &zone, "Test", 8);
+            let kept: Vec<&Planet> = evolved.iter().filter_map(|body| match body {
+                CelestialBody::Planet(planet) => Some(planet),
+                _ => None,
+            }).collect();
+            assert!(kept.iter().all(|planet| planet.orbit.elements.periapsis() >= 2.0));
+            engulfed += young_axes.len().saturating_sub(axes.len());
+            // Lettres contiguës parmi les planètes restantes
+            let names: Vec<&str> = kept.iter().map(|planet| planet.name.as_str()).collect();
+            let expected: Vec<String> = (0..axes.len()).map(|i| format!("Test {}", planet_letter(i))).collect();
+            assert_eq!(names, expected);
+            for body in &evolved {
+                if let CelestialBody::Moon(moon) = body {
+                    let CelestialBody::Planet(host) = &evolved[moon.host] else { panic!("hôte invalide") };
+                    assert!(moon.name.starts_with(&host.name));
+                }
+            }
+        }
+        assert!(engulfed > 0);
+    }
 
-//             let planet_props = generate_planet(&mut rng);
-//             let distance = rng.f64(5.0e10, 5.0e11) as f32;
-//             let angle = rng.f64(0.0, std::f32::consts::TAU as f64) as f32;
-//             let pos = barycenter + Vec3::new((distance / 1.0e10) * angle.cos(), (distance / 1.0e10) * angle.sin(), 0.0);
+    #[test]
+    fn test_white_dwarf_keeps_engulfed_zone() {
+        let composition = StarComposition::solar();
+        let zone = PlanetaryZone {
+            orbit_type: PlanetOrbitType::SType,
+            host: None,
+            central_mass: 1.0,
+            min_semi_major_axis: PLANET_MIN_SEMI_MAJOR_AXIS,
+            max_semi_major_axis: PLANET_MAX_SEMI_MAJOR_AXIS,
+        };
+        let progenitor = main_sequence_star(4.0, &composition);
+        let white_dwarf = progenitor.evolved(5.0);
+        assert_eq!(white_dwarf.stage, StellarStage::WhiteDwarf);
+        // Le résidu est minuscule, mais sa géante a atteint plusieurs dixièmes d'UA
+        let giant_radius = progenitor.evolved(progenitor.lifetime).radius * SOLAR_RADIUS / ASTRONOMICAL_UNIT;
+        assert!(giant_radius > PLANET_MIN_SEMI_MAJOR_AXIS);
+        assert_eq!(white_dwarf.engulfment_radius(), giant_radius);
+        let mut engulfed = 0;
+        for seed in 0..16 {
+            let mut young = vec![CelestialBody::Star(Star {
+                name: "Test".to_string(),
+                physical_props: progenitor.clone(),
+                orbit: Orbit::stationary(),
+                position: glam::DVec3::ZERO,
+                velocity: glam::DVec3::ZERO,
+            })];
+            let young_axes = StellarSystem::generate_planets(&mut RNG::from_seed(seed), &mut young, &zone, "Test", 8);
+            let mut evolved = vec![CelestialBody::Star(Star {
+                name: "Test".to_string(),
+                physical_props: white_dwarf.clone(),
+                orbit: Orbit::stationary(),
+                position: glam::DVec3::ZERO,
+                velocity: glam::DVec3::ZERO,
+            })];
+            let axes = StellarSystem::generate_planets(&mut RNG::from_seed(seed), &mut evolved, &zone, "Test", 8);
+            for body in &evolved {
+                if let CelestialBody::Planet(planet) = body {
+                    assert!(planet.orbit.elements.periapsis() >= giant_radius);
+                }
+            }
+            engulfed += young_axes.len().saturating_sub(axes.len());
+        }
+        assert!(engulfed > 0);
+    }
 
-//             let r_vec = pos - barycenter;
-//             let v_mag = (G * total_mass / r_vec.length() as f64).sqrt() as f32;
-//             let dir = Vec3::new(-r_vec.y, r_vec.x, 0.0).normalize();
+    #[test]
+    fn test_system_stars_use_stellar_model() {
+        for pos in sample_positions() {
+            let system = StellarSystem::new(pos);
+            for body in &system.bodies {
+                if let CelestialBody::Star(star) = body {
+                    let props = &star.physical_props;
+                    if props.stage != StellarStage::MainSequence {
+                        continue;
+                    }
+                    assert!((MIN_STAR_MASS..=MAX_STAR_MASS).contains(&props.mass));
+                    assert_eq!(props.luminosity, mass_luminosity(props.mass));
+                    assert_eq!(props.spectral_type, spectral_class(props.temperature));
+                }
+            }
+        }
+    }
 
-//             let planet = Planet {
-//                 name: format!("Planet {}", i + 1),
-//                 physical_props: planet_props,
-//                 position: pos,
-//                 velocity: dir * v_mag,
-//             };
+    #[test]
+    fn test_metallicity_gradient() {
+        // Le disque interne, plus enrichi par les générations d'étoiles successives, est plus métallique
+        let mean_metallicity = |radius: f32| {
+            let metallicities: Vec<f64> = (0..24).map(|i| {
+                let angle = i as f32 * 0.26;
+                let system = StellarSystem::new(Vec3::new(radius * angle.cos(), 0.0, radius * angle.sin()));
+                let CelestialBody::Star(star) = &system.bodies[0] else { panic!("le premier corps doit être une étoile") };
+                star.physical_props.metallicity
+            }).collect();
+            metallicities.iter().sum::<f64>() / metallicities.len() as f64
+        };
+        assert!(mean_metallicity(2000.0) > mean_metallicity(8000.0));
+        assert!(mean_metallicity(8000.0) > mean_metallicity(14000.0));
+    }
 
-//             bodies.push(CelestialBody::Planet(planet));
-//         }
+    #[test]
+    fn test_giant_planets_follow_metallicity() {
+        assert!((giant_planet_probability(SOLAR_METALLICITY) - GIANT_PLANET_SOLAR_PROBABILITY).abs() < 1.0e-12);
+        // [Fe/H] = +0.3 : quatre fois plus de géantes
+        let rich = giant_planet_probability(SOLAR_METALLICITY * 10f64.powf(0.3));
+        assert!((rich / GIANT_PLANET_SOLAR_PROBABILITY - 3.98).abs() < 0.01);
+        assert_eq!(giant_planet_probability(0.04), GIANT_PLANET_MAX_PROBABILITY);
+        let giants = |metallicity: f64| {
+            let mut rng = RNG::from_seed(9);
+            (0..2000).filter(|_| generate_planet(&mut rng, metallicity).mass >= GAS_GIANT_MIN_MASS).count()
+        };
+        assert!(giants(0.003) < 100);
+        assert!(giants(0.02) > 700);
+    }
 
-//         StellarSystem {
-//             name: String::from("dzdzd"),
-//             system_type: StellarSystemType::Binary,
-//             bodies,
-//         }
-//     }
+    #[test]
+    fn test_planet_count() {
+        let mut total = 0;
+        for pos in sample_positions() {
+            let system = StellarSystem::new(pos);
+            let count = planets(&system).len();
+            // Aucune planète seulement si une géante les a toutes englouties
+            let giant = StellarSystem::zone_stars(&system.bodies, &system.planetary_zone)
+                .any(|star| star.physical_props.stage == StellarStage::RedGiant);
+            assert!(count <= 8 && (count >= 1 || giant), "nombre de planètes invalide : {}", count);
+            total += count;
+        }
+        // La loi géométrique (p = 0.4) doit produire des systèmes à plusieurs planètes
+        assert!(total > sample_positions().len());
+    }
 
-//     fn get_number_star(rng: &mut RNG, p: f64, max_stars: u32) -> u32 {
-//         let mut n: u32 = 1;
-//         while n < max_stars && rng.gen_norm() < p {
-//             n += 1;
-//         }
-//         n
-//     }
-// }
+    #[test]
+    fn test_planet_orbit_ranges() {
+        for pos in sample_positions() {
+            let system = StellarSystem::new(pos);
+            let mut previous = 0.0;
+            for planet in planets(&system) {
+                let orbit = &planet.orbit.elements;
+                assert!(orbit.semi_major_axis >= PLANET_MIN_SEMI_MAJOR_AXIS);
+                assert!(orbit.semi_major_axis <= PLANET_MAX_SEMI_MAJOR_AXIS);
+                assert!(orbit.semi_major_axis > previous, "orbites non triées");
+                assert!((0.0..PLANET_MAX_ECCENTRICITY).contains(&orbit.eccentricity));
+                assert!((0.0..PLANET_MAX_INCLINATION).contains(&orbit.inclination));
+                assert!((0.0..2.0 * PI).contains(&orbit.mean_anomaly_epoch));
+                previous = orbit.semi_major_axis;
+            }
+        }
+    }
 
-// Exemple d'utilisation :
-// let radius = star_radius_from_mass_composition(mass, &composition);
+    #[test]
+    fn test_planet_position_around_barycenter() {
+        let mut system = StellarSystem::new(Vec3::new(1.0, 2.0, 3.0));
+        system.set_simulation_mode(SimulationMode::Kepler);
+        let (barycenter, _) = compute_barycenter(&system.bodies);
+        assert!((barycenter - system.barycenter).length() < 1.0e-9);
+        for planet in planets(&system) {
+            let orbit = &planet.orbit.elements;
+            // Distance au foyer de l'orbite (étoile hôte ou barycentre d'une paire pour les systèmes multiples)
+            let focus = match &planet.orbit.parent {
+                Some(parent) => barycenter + parent.state_at(0.0).0 / SCENE_UNIT,
+                None => barycenter,
+            };
+            let distance = (planet.position.as_dvec3() - focus).length() * SCENE_UNIT / ASTRONOMICAL_UNIT;
+            assert!(distance >= orbit.periapsis() * 0.999 && distance <= orbit.apoapsis() * 1.001);
+        }
+    }
+
+    #[test]
+    fn test_system_is_deterministic() {
+        let pos = Vec3::new(42.0, -7.0, 0.5);
+        let a = StellarSystem::new(pos);
+        let b = StellarSystem::new(pos);
+        let (pa, pb) = (planets(&a), planets(&b));
+        assert_eq!(pa.len(), pb.len());
+        for (x, y) in pa.iter().zip(pb.iter()) {
+            assert_eq!(x.orbit, y.orbit);
+            assert_eq!(x.position, y.position);
+            assert_eq!(x.physical_props.mass, y.physical_props.mass);
+        }
+    }
+
+    #[test]
+    fn test_star_orbits_keep_barycenter() {
+        let mut system = StellarSystem::new(Vec3::new(-3.0, 8.0, 1.0));
+        system.set_simulation_mode(SimulationMode::Kepler);
+        for step in 0..10 {
+            system.update(step as f64 * 5.0e6);
+            let (barycenter, _) = compute_barycenter(&system.bodies);
+            assert!((barycenter - system.barycenter).length() < 1.0e-6);
+        }
+    }
+
+    #[test]
+    fn test_system_type_matches_star_count() {
+        let mut seen = [false; 3];
+        for pos in sample_positions() {
+            let system = StellarSystem::new(pos);
+            let stars = system.bodies.iter().filter(|body| matches!(body, CelestialBody::Star(_))).count();
+            let expected = match stars {
+                1 => StellarSystemType::Single,
+                2 => StellarSystemType::Binary,
+                3 => StellarSystemType::Ternary,
+                _ => panic!("nombre d'étoiles invalide : {}", stars),
+            };
+            assert_eq!(system.system_type, expected);
+            seen[stars - 1] = true;
+        }
+        assert!(seen.iter().all(|&s| s), "toutes les multiplicités doivent apparaître");
+    }
+
+    #[test]
+    fn test_planets_inside_stability_zone() {
+        for pos in sample_positions() {
+            let system = StellarSystem::new(pos);
+            let zone = &system.planetary_zone;
+            assert!(zone.min_semi_major_axis < zone.max_semi_major_axis);
+            for planet in planets(&system) {
+                let a = planet.orbit.elements.semi_major_axis;
+                assert!(a >= zone.min_semi_major_axis && a <= zone.max_semi_major_axis);
+                assert_eq!(planet.orbit.central_mass, zone.central_mass);
+                assert_eq!(planet.orbit.parent.as_deref(), zone.host.as_ref());
+            }
+        }
+    }
+
+    #[test]
+    fn test_planet_habitability() {
+        for pos in sample_positions() {
+            let system = StellarSystem::new(pos);
+            // Étoiles au foyer des orbites planétaires (une compagne lointaine peut être plus lumineuse)
+            let luminosity: f64 = StellarSystem::zone_stars(&system.bodies, &system.planetary_zone)
+                .map(|star| star.physical_props.luminosity)
+                .sum();
+            for planet in planets(&system) {
+                let props = &planet.physical_props;
+                assert!(props.insolation > 0.0);
+                // Etoiles proches du foyer : l'insolation reste de l'ordre de L / a²
+                let a = planet.orbit.elements.semi_major_axis;
+                assert!(props.insolation > 0.1 * luminosity / (a * a));
+                assert_eq!(
+                    props.equilibrium_temperature,
+                    habitability::equilibrium_temperature(props.insolation, props.albedo)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_planet_class() {
+        assert_eq!(PlanetClass::classify(1.0, 1.0, 255.0, 0.7), PlanetClass::Terrestrial);
+        assert_eq!(PlanetClass::classify(318.0, 11.0, 110.0, 0.0), PlanetClass::GasGiant);
+        // Faible densité : enveloppe gazeuse même à faible masse
+        assert_eq!(PlanetClass::classify(5.0, 3.0, 255.0, 0.7), PlanetClass::GasGiant);
+        assert_eq!(PlanetClass::classify(1.0, 1.0, 1500.0, 0.7), PlanetClass::Lava);
+        assert_eq!(PlanetClass::classify(0.5, 0.8, 120.0, 0.7), PlanetClass::Ice);
+        for class in [PlanetClass::Terrestrial, PlanetClass::GasGiant, PlanetClass::Lava, PlanetClass::Ice, PlanetClass::Ocean, PlanetClass::Desert] {
+            assert_eq!(PlanetClass::from_u8(class as u8), class);
+        }
+    }
+
+    #[test]
+    fn test_ocean_and_desert_worlds() {
+        // Eau abondante et tempérée : monde océan ; la même eau évaporée laisse un désert
+        assert_eq!(PlanetClass::classify(1.0, 1.0, 280.0, 0.95), PlanetClass::Ocean);
+        assert_eq!(PlanetClass::classify(1.0, 1.0, 360.0, 0.95), PlanetClass::Terrestrial);
+        assert_eq!(PlanetClass::classify(1.0, 1.0, 450.0, 0.95), PlanetClass::Desert);
+        // Peu de volatils : désert à toute température tempérée
+        assert_eq!(PlanetClass::classify(1.0, 1.0, 255.0, 0.05), PlanetClass::Desert);
+        // Trop froid, l'océan gèle ; trop chaud, il fond en lave
+        assert_eq!(PlanetClass::classify(1.0, 1.0, 150.0, 0.95), PlanetClass::Ice);
+        assert_eq!(PlanetClass::classify(1.0, 1.0, 900.0, 0.05), PlanetClass::Lava);
+    }
+
+    #[test]
+    fn test_planet_radius_from_mass() {
+        for pos in sample_positions() {
+            let system = StellarSystem::new(pos);
+            for planet in planets(&system) {
+                let props = &planet.physical_props;
+                assert!(props.radius > 0.0 && props.radius <= 11.0);
+                assert_eq!(props.class, PlanetClass::classify(props.mass, props.radius, props.equilibrium_temperature, props.water_fraction));
+            }
+        }
+    }
+
+    #[test]
+    fn test_moons_follow_their_host() {
+        let mut moons = 0;
+        for pos in sample_positions() {
+            let mut system = StellarSystem::new(pos);
+            system.set_simulation_mode(SimulationMode::Kepler);
+            system.update(12.0 * 86400.0);
+            for body in &system.bodies {
+                if let CelestialBody::Moon(moon) = body {
+                    let CelestialBody::Planet(host) = &system.bodies[moon.host] else {
+                        panic!("l'hôte d'un satellite doit être une planète");
+                    };
+                    assert_eq!(moon.orbit.parent.as_deref(), Some(&host.orbit));
+                    assert_eq!(moon.physical_props.insolation, host.physical_props.insolation);
+                    // Écart agrandi à l'affichage, mais toujours proportionnel à l'orbite
+                    let distance = (moon.position - host.position).length() as f64 * SCENE_UNIT / MOON_ORBIT_DISPLAY_SCALE / ASTRONOMICAL_UNIT;
+                    assert!((distance - moon.orbit.elements.semi_major_axis).abs() < 0.1 * moon.orbit.elements.semi_major_axis + 1.0e-5);
+                    moons += 1;
+                }
+            }
+        }
+        assert!(moons > 0, "aucun satellite généré");
+    }
 
+    #[test]
+    fn test_moons_outside_host_mesh() {
+        for pos in sample_positions() {
+            let mut system = StellarSystem::new(pos);
+            for mode in [SimulationMode::Kepler, SimulationMode::NBody(Integrator::Leapfrog)] {
+                system.set_simulation_mode(mode);
+                system.update(3.0 * 86400.0);
+                for body in &system.bodies {
+                    if let CelestialBody::Moon(moon) = body {
+                        let CelestialBody::Planet(host) = &system.bodies[moon.host] else {
+                            panic!("l'hôte d'un satellite doit être une planète");
+                        };
+                        // Le maillage de l'hôte a pour rayon son rayon physique en rayons terrestres
+                        let distance = (moon.position - host.position).length() as f64;
+                        assert!(distance > host.physical_props.radius, "{} à {} de son hôte de rayon {}", moon.name, distance, host.physical_props.radius);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rings_only_on_gas_giants() {
+        for pos in sample_positions() {
+            let system = StellarSystem::new(pos);
+            for planet in planets(&system) {
+                if planet.rings.is_some() {
+                    assert_eq!(planet.physical_props.class, PlanetClass::GasGiant);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_asteroid_belt_clear_of_planets() {
+        for pos in sample_positions() {
+            let system = StellarSystem::new(pos);
+            let Some(belt) = &system.asteroid_belt else { continue };
+            assert!(belt.inner_radius >= system.planetary_zone.min_semi_major_axis);
+            assert!(belt.outer_radius <= system.planetary_zone.max_semi_major_axis);
+            for planet in planets(&system) {
+                let a = planet.orbit.elements.semi_major_axis;
+                assert!(a < belt.inner_radius || a > belt.outer_radius);
+            }
+        }
+    }
+
+    #[test]
+    fn test_update_moves_planets() {
+        let mut system = StellarSystem::new(Vec3::ZERO);
+        let before: Vec<Vec3> = planets(&system).iter().map(|p| p.position).collect();
+        system.update(30.0 * 86400.0);
+        let after: Vec<Vec3> = planets(&system).iter().map(|p| p.position).collect();
+        for (a, b) in before.iter().zip(after.iter()) {
+            assert!((*a - *b).length() > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_default_simulation_mode() {
+        assert_eq!(StellarSystemType::Single.default_simulation_mode(), SimulationMode::Kepler);
+        assert_eq!(StellarSystemType::Binary.default_simulation_mode(), SimulationMode::NBody(Integrator::Leapfrog));
+        assert_eq!(StellarSystemType::Ternary.default_simulation_mode(), SimulationMode::NBody(Integrator::Leapfrog));
+    }
+
+    #[test]
+    fn test_nbody_system_conservation() {
+        let mut system = StellarSystem::new(Vec3::new(5.0, 5.0, 5.0));
+        system.set_simulation_mode(SimulationMode::NBody(Integrator::Leapfrog));
+        assert_eq!(system.conservation_drift(), Some((0.0, 0.0)));
+        for frame in 1..=50 {
+            system.update(frame as f64 * 6.0 * 3600.0);
+        }
+        let (energy, angular_momentum) = system.conservation_drift().unwrap();
+        assert!(energy < 1.0e-3, "derive energie {}", energy);
+        assert!(angular_momentum < 1.0e-6, "derive moment cinetique {}", angular_momentum);
+        // Barycentre immobile à l'origine
+        let state = system.nbody.as_ref().unwrap();
+        assert!(state.center_of_mass().length() < 1.0e3, "barycentre {:?}", state.center_of_mass());
+    }
+
+    #[test]
+    fn test_switch_back_to_kepler() {
+        let mut system = StellarSystem::new(Vec3::new(1.0, 1.0, 1.0));
+        system.set_simulation_mode(SimulationMode::NBody(Integrator::Rk4));
+        system.update(86400.0);
+        system.set_simulation_mode(SimulationMode::Kepler);
+        assert_eq!(system.conservation_drift(), None);
+        let states: Vec<_> = system.bodies.iter().map(|body| body.orbit().state_at(86400.0)).collect();
+        let expected: Vec<Vec3> = system.bodies.iter().enumerate().map(|(i, body)| {
+            let (position, _) = match body {
+                CelestialBody::Moon(moon) => moon_display_state(states[moon.host], states[i]),
+                _ => states[i],
+            };
+            (system.barycenter + position / SCENE_UNIT).as_vec3()
+        }).collect();
+        for (body, position) in system.bodies.iter().zip(expected) {
+            assert!((body.position() - position).length() < 1.0e-3);
+        }
+    }
+}